@@ -1,31 +1,139 @@
-use std::fs::{self, File};
+use std::{fs, path::PathBuf, sync::Arc, time::Duration};
 
-use arweave_client::{Rpc, Signer, Tag, Transaction, Uploader};
+use anyhow::{anyhow, Result};
+use arweave_client::{
+    Downloader, Rpc, Signer, Tag, Transaction, TxStatus, UploadProgress, Uploader,
+};
+use clap::{Parser, Subcommand};
 use reqwest::Client;
 
+const WINSTON_PER_AR: u128 = 1_000_000_000_000;
+
+#[derive(Parser)]
+#[command(about = "Arweave wallet management")]
+struct Cli {
+    /// Path to JWK wallet file
+    #[arg(long, default_value = "./wallet.json")]
+    wallet: PathBuf,
+    #[arg(long, default_value = "https://arweave.net:443")]
+    url: String,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print wallet address
+    Address,
+    /// Print balance of the wallet or given address
+    Balance { address: Option<String> },
+    /// Print price of uploading given number of bytes
+    Price { bytes: usize },
+    /// Upload file as a transaction signed by the wallet
+    Upload {
+        file: PathBuf,
+        /// Transaction tag formatted as `name=value`
+        #[arg(long = "tag", value_parser = parse_tag)]
+        tags: Vec<Tag>,
+        /// Wait until transaction is confirmed
+        #[arg(long)]
+        wait: bool,
+    },
+    /// Print transaction status
+    Status { txid: String },
+    /// Download and verify transaction data
+    Download {
+        txid: String,
+        /// Output file, data is written to stdout if not set
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+}
+
+fn parse_tag(tag: &str) -> Result<Tag> {
+    let (name, value) = tag
+        .split_once('=')
+        .ok_or(anyhow!("Tag should be formatted as name=value"))?;
+    Ok(Tag {
+        name: name.to_string(),
+        value: value.to_string(),
+    })
+}
+
+fn format_winston(winston: &str) -> Result<String> {
+    let winston: u128 = winston.trim().parse()?;
+    Ok(format!(
+        "{winston} winston ({}.{:012} AR)",
+        winston / WINSTON_PER_AR,
+        winston % WINSTON_PER_AR
+    ))
+}
+
 #[tokio::main]
-async fn main() {
-    let signer = Signer::from_file("./wallet.json").unwrap();
-    // let big_data = fs::read("./arweave_client/BigImage.png").unwrap();
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let signer = || Signer::from_file(cli.wallet.to_str().unwrap_or_default());
     let rpc = Rpc {
-        url: "https://arweave.net:443".to_string(),
+        url: cli.url.clone(),
         client: Client::new(),
     };
-    let mut tx = Transaction::builder(rpc.clone())
-        .tags(vec![Tag {
-            name: "Content-Type".to_string(),
-            value: "MpData".to_string(),
-        }])
-        .data(b"Some message".to_vec())
-        .build()
-        .await
-        .unwrap();
-    tx.sign(signer.into()).unwrap();
-    // let chunk = tx.get_chunk(3, &tx.data).unwrap();
-    // println!("root {:?}", chunk.data_root);
-    // println!("path {:?}", chunk.data_path);
-    // println!("size {:?}", chunk.data_size);
-    println!("tx {:?}", tx);
-    // let mut uploader = Uploader::new(rpc, tx);
-    // uploader.upload_chunks().await.unwrap();
+
+    match cli.command {
+        Command::Address => println!("{}", signer()?.address),
+        Command::Balance { address } => {
+            let address = match address {
+                Some(address) => address,
+                None => signer()?.address,
+            };
+            println!("{}", format_winston(&rpc.get_balance(&address).await?)?);
+        }
+        Command::Price { bytes } => {
+            println!("{}", format_winston(&rpc.get_price(bytes, &None).await?)?);
+        }
+        Command::Upload { file, tags, wait } => {
+            let signer = Arc::new(signer()?);
+            let mut tx = Transaction::builder(rpc.clone())
+                .tags(tags)
+                .data(fs::read(file)?)
+                .build()
+                .await?;
+            tx.sign(signer)?;
+
+            let mut uploader = Uploader::new(rpc, tx).on_progress(|progress| match progress {
+                UploadProgress::TxPosted => eprintln!("transaction posted"),
+                UploadProgress::ChunkUploaded {
+                    uploaded, total, ..
+                } => eprintln!("uploaded {uploaded}/{total} chunks"),
+                UploadProgress::Status(status) => eprintln!("{status:?}"),
+            });
+            uploader.upload_chunks().await?;
+            println!("{}", uploader.tx_id());
+            if wait {
+                uploader
+                    .wait_confirmation(1, Duration::from_secs(30))
+                    .await?;
+            }
+        }
+        Command::Status { txid } => match rpc.get_tx_status(&txid).await? {
+            TxStatus::NotFound => println!("not found"),
+            TxStatus::Pending => println!("pending"),
+            TxStatus::Confirmed(confirmation) => println!(
+                "confirmed in block {} ({}), {} confirmations",
+                confirmation.block_height,
+                confirmation.block_indep_hash,
+                confirmation.number_of_confirmations
+            ),
+        },
+        Command::Download { txid, output } => {
+            let data = Downloader::new(rpc).download(&txid).await?;
+            match output {
+                Some(path) => fs::write(path, data)?,
+                None => {
+                    use std::io::Write;
+                    std::io::stdout().write_all(&data)?;
+                }
+            }
+        }
+    }
+    Ok(())
 }
@@ -0,0 +1,304 @@
+use std::sync::Arc;
+
+use anyhow::{ensure, Context, Result};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+
+use crate::{
+    rpc::Rpc,
+    transaction::{Tag, Transaction},
+    utils::{deep_hash, hash_256, DeepHashChunk::Data, DeepHashChunk::List},
+    wallet::{verify, Signer},
+};
+
+/// Signature type of Arweave RSA-PSS 4096 keys
+pub const ARWEAVE_SIGNATURE_TYPE: u16 = 1;
+const SIGNATURE_LENGTH: usize = 512;
+const OWNER_LENGTH: usize = 512;
+const HASH_SIZE: usize = 32;
+
+/// ANS-104 data item. Tags are stored as is, without base64 encoding.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DataItem {
+    pub signature_type: u16,
+    pub signature: Vec<u8>,
+    pub owner: Vec<u8>,
+    pub target: Option<[u8; 32]>,
+    pub anchor: Option<[u8; 32]>,
+    pub tags: Vec<Tag>,
+    pub data: Vec<u8>,
+}
+
+impl DataItem {
+    pub fn new(data: Vec<u8>) -> Self {
+        Self {
+            signature_type: ARWEAVE_SIGNATURE_TYPE,
+            signature: vec![0; SIGNATURE_LENGTH],
+            owner: vec![0; OWNER_LENGTH],
+            target: None,
+            anchor: None,
+            tags: Vec::new(),
+            data,
+        }
+    }
+
+    pub fn tags(mut self, tags: Vec<Tag>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    pub fn target(mut self, target: [u8; 32]) -> Self {
+        self.target = Some(target);
+        self
+    }
+
+    pub fn anchor(mut self, anchor: [u8; 32]) -> Self {
+        self.anchor = Some(anchor);
+        self
+    }
+
+    pub fn get_signature_data(&self) -> Vec<u8> {
+        deep_hash(List(vec![
+            Data(b"dataitem".to_vec()),
+            Data(b"1".to_vec()),
+            Data(self.signature_type.to_string().into_bytes()),
+            Data(self.owner.clone()),
+            Data(self.target.map(Vec::from).unwrap_or_default()),
+            Data(self.anchor.map(Vec::from).unwrap_or_default()),
+            Data(serialize_tags(&self.tags)),
+            Data(self.data.clone()),
+        ]))
+    }
+
+    pub fn sign(&mut self, jwk: Arc<Signer>) -> Result<()> {
+        let owner = URL_SAFE_NO_PAD.decode(&jwk.owner)?;
+        ensure!(
+            owner.len() == OWNER_LENGTH,
+            "Only 4096 bit keys are supported"
+        );
+        self.signature_type = ARWEAVE_SIGNATURE_TYPE;
+        self.owner = owner;
+        self.signature = jwk.sign(&self.get_signature_data())?;
+        Ok(())
+    }
+
+    /// Checks signature of Arweave signature type
+    pub fn verify(&self) -> bool {
+        self.signature_type == ARWEAVE_SIGNATURE_TYPE
+            && verify(&self.owner, &self.get_signature_data(), &self.signature)
+    }
+
+    pub fn raw_id(&self) -> Vec<u8> {
+        hash_256(&self.signature)
+    }
+
+    pub fn id(&self) -> String {
+        URL_SAFE_NO_PAD.encode(self.raw_id())
+    }
+
+    pub fn serialize(&self) -> Vec<u8> {
+        let tags = serialize_tags(&self.tags);
+        let mut bytes = Vec::new();
+        bytes.extend(self.signature_type.to_le_bytes());
+        bytes.extend(&self.signature);
+        bytes.extend(&self.owner);
+        for optional in [self.target, self.anchor] {
+            match optional {
+                Some(value) => {
+                    bytes.push(1);
+                    bytes.extend(value);
+                }
+                None => bytes.push(0),
+            }
+        }
+        bytes.extend((self.tags.len() as u64).to_le_bytes());
+        bytes.extend((tags.len() as u64).to_le_bytes());
+        bytes.extend(tags);
+        bytes.extend(&self.data);
+        bytes
+    }
+
+    pub fn deserialize(bytes: &[u8]) -> Result<Self> {
+        let mut reader = Reader(bytes);
+        let signature_type = u16::from_le_bytes(reader.take(2)?.try_into()?);
+        ensure!(
+            signature_type == ARWEAVE_SIGNATURE_TYPE,
+            "Unsupported signature type {signature_type}"
+        );
+        let signature = reader.take(SIGNATURE_LENGTH)?.to_vec();
+        let owner = reader.take(OWNER_LENGTH)?.to_vec();
+        let target = reader.optional()?;
+        let anchor = reader.optional()?;
+        let tags_count = u64::from_le_bytes(reader.take(8)?.try_into()?);
+        let tags_length = u64::from_le_bytes(reader.take(8)?.try_into()?);
+        let tags = deserialize_tags(reader.take(tags_length.try_into()?)?)?;
+        ensure!(tags.len() as u64 == tags_count, "Tags count mismatch");
+        Ok(Self {
+            signature_type,
+            signature,
+            owner,
+            target,
+            anchor,
+            tags,
+            data: reader.0.to_vec(),
+        })
+    }
+}
+
+/// ANS-104 bundle of data items posted as one L1 transaction
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Bundle {
+    pub items: Vec<DataItem>,
+}
+
+impl Bundle {
+    pub fn new(items: Vec<DataItem>) -> Self {
+        Self { items }
+    }
+
+    pub fn tags() -> Vec<Tag> {
+        vec![
+            Tag {
+                name: "Bundle-Format".to_string(),
+                value: "binary".to_string(),
+            },
+            Tag {
+                name: "Bundle-Version".to_string(),
+                value: "2.0.0".to_string(),
+            },
+        ]
+    }
+
+    /// Item count, (size, id) of every item and then items, numbers are 32 byte little endian
+    pub fn serialize(&self) -> Vec<u8> {
+        let items: Vec<Vec<u8>> = self.items.iter().map(DataItem::serialize).collect();
+        let mut bytes = long_to_32_bytes(items.len() as u64);
+        for (item, raw) in self.items.iter().zip(&items) {
+            bytes.extend(long_to_32_bytes(raw.len() as u64));
+            bytes.extend(item.raw_id());
+        }
+        bytes.extend(items.concat());
+        bytes
+    }
+
+    pub fn deserialize(bytes: &[u8]) -> Result<Self> {
+        let mut reader = Reader(bytes);
+        let count = long_from_32_bytes(reader.take(32)?)?;
+        let mut headers = Vec::new();
+        for _ in 0..count {
+            let size = long_from_32_bytes(reader.take(32)?)?;
+            let id = reader.take(HASH_SIZE)?.to_vec();
+            headers.push((size, id));
+        }
+        let mut items = Vec::new();
+        for (size, id) in headers {
+            let item = DataItem::deserialize(reader.take(size.try_into()?)?)?;
+            ensure!(item.raw_id() == id, "Item id mismatch");
+            items.push(item);
+        }
+        Ok(Self { items })
+    }
+
+    /// Builds L1 transaction that carries the bundle, it still has to be signed
+    pub async fn into_transaction(self, rpc: Rpc) -> Result<Transaction> {
+        Transaction::builder(rpc)
+            .tags(Self::tags())
+            .data(self.serialize())
+            .build()
+            .await
+    }
+}
+
+/// Avro encoded array of tags, empty if there are no tags
+pub fn serialize_tags(tags: &[Tag]) -> Vec<u8> {
+    if tags.is_empty() {
+        return Vec::new();
+    }
+    let mut bytes = Vec::new();
+    write_long(&mut bytes, tags.len() as i64);
+    for tag in tags {
+        for field in [&tag.name, &tag.value] {
+            write_long(&mut bytes, field.len() as i64);
+            bytes.extend(field.as_bytes());
+        }
+    }
+    bytes.push(0);
+    bytes
+}
+
+pub fn deserialize_tags(bytes: &[u8]) -> Result<Vec<Tag>> {
+    let mut reader = Reader(bytes);
+    let mut tags = Vec::new();
+    while !reader.0.is_empty() {
+        let mut count = reader.long()?;
+        if count == 0 {
+            break;
+        }
+        // negative count is followed by block size in bytes
+        if count < 0 {
+            count = -count;
+            reader.long()?;
+        }
+        for _ in 0..count {
+            let name = reader.string()?;
+            let value = reader.string()?;
+            tags.push(Tag { name, value });
+        }
+    }
+    Ok(tags)
+}
+
+fn write_long(bytes: &mut Vec<u8>, value: i64) {
+    let mut zigzag = ((value << 1) ^ (value >> 63)) as u64;
+    while zigzag >= 0x80 {
+        bytes.push((zigzag as u8) | 0x80);
+        zigzag >>= 7;
+    }
+    bytes.push(zigzag as u8);
+}
+
+fn long_to_32_bytes(value: u64) -> Vec<u8> {
+    let mut bytes = value.to_le_bytes().to_vec();
+    bytes.resize(32, 0);
+    bytes
+}
+
+fn long_from_32_bytes(bytes: &[u8]) -> Result<u64> {
+    ensure!(bytes[8..].iter().all(|b| *b == 0), "Number is too big");
+    Ok(u64::from_le_bytes(bytes[..8].try_into()?))
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8]> {
+        ensure!(self.0.len() >= length, "Unexpected end of data");
+        let (head, rest) = self.0.split_at(length);
+        self.0 = rest;
+        Ok(head)
+    }
+
+    fn optional(&mut self) -> Result<Option<[u8; 32]>> {
+        match self.take(1)?[0] {
+            0 => Ok(None),
+            1 => Ok(Some(self.take(32)?.try_into()?)),
+            flag => anyhow::bail!("Invalid presence byte {flag}"),
+        }
+    }
+
+    fn long(&mut self) -> Result<i64> {
+        let mut zigzag = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.take(1)?[0];
+            zigzag |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(((zigzag >> 1) as i64) ^ -((zigzag & 1) as i64));
+            }
+        }
+        anyhow::bail!("Invalid varint")
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let length = self.long()?.try_into().context("Negative length")?;
+        Ok(String::from_utf8(self.take(length)?.to_vec())?)
+    }
+}
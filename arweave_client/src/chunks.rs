@@ -3,6 +3,8 @@ use serde::Serialize;
 
 const MAX_CHUNK_SIZE: usize = 256 * 1024;
 const MIN_CHUNK_SIZE: usize = 32 * 1024;
+const HASH_SIZE: usize = 32;
+const NOTE_SIZE: usize = 32;
 
 #[derive(Clone)]
 pub enum MerkelNode {
@@ -78,7 +80,7 @@ fn chunk_data(data: Vec<u8>) -> Vec<Chunk> {
 
         let next_chunk_size = rest.len() - MAX_CHUNK_SIZE;
         if next_chunk_size > 0 && next_chunk_size < MIN_CHUNK_SIZE {
-            chunk_size = (rest.len() / 2) + (rest.len() % 2 == 0) as usize;
+            chunk_size = rest.len().div_ceil(2);
             // console.log(`Last chunk will be: ${nextChunkSize} which is below ${MIN_CHUNK_SIZE}, adjusting current to ${chunkSize} with ${rest.byteLength} left.`)
         }
 
@@ -111,9 +113,7 @@ fn generate_leaves(chunks: &[Chunk]) -> Vec<MerkelNode> {
                 id: hash_256(
                     hash_256(&chunk.data_hash)
                         .into_iter()
-                        .chain(hash_256(&pad_to_32_bytes(
-                            &chunk.max_byte_range.to_be_bytes(),
-                        )))
+                        .chain(hash_256(&note(chunk.max_byte_range)))
                         .collect::<Vec<u8>>()
                         .as_ref(),
                 ),
@@ -164,9 +164,7 @@ fn hash_branch(left: MerkelNode, right: Option<MerkelNode>) -> MerkelNode {
             &hash_256(&left.id())
                 .into_iter()
                 .chain(hash_256(&right.id()))
-                .chain(hash_256(&pad_to_32_bytes(
-                    &left.max_byte_range().to_be_bytes(),
-                )))
+                .chain(hash_256(&note(left.max_byte_range())))
                 .collect::<Vec<u8>>(),
         ),
         byte_range: left.max_byte_range(),
@@ -201,6 +199,77 @@ pub fn generate_transaction_chunks(data: Vec<u8>) -> Chunks {
     }
 }
 
+/// Data hash of the leaf that ends merkle `data_path`
+pub fn leaf_data_hash(data_path: &[u8]) -> Option<&[u8]> {
+    let leaf = data_path.len().checked_sub(HASH_SIZE + NOTE_SIZE)?;
+    Some(&data_path[leaf..leaf + HASH_SIZE])
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ValidatedPath {
+    pub offset: usize,
+    pub left_bound: usize,
+    pub right_bound: usize,
+    pub chunk_size: usize,
+}
+
+/// Checks that merkle `path` of the chunk containing byte at `offset` leads to
+/// `data_root` and returns byte range of that chunk. Follows arweave-js `validatePath`.
+pub fn validate_path(
+    data_root: &[u8],
+    offset: usize,
+    left_bound: usize,
+    right_bound: usize,
+    path: &[u8],
+) -> Option<ValidatedPath> {
+    if right_bound == 0 {
+        return None;
+    }
+    if offset >= right_bound {
+        return validate_path(data_root, 0, right_bound - 1, right_bound, path);
+    }
+
+    if path.len() == HASH_SIZE + NOTE_SIZE {
+        let (data_hash, end_offset) = path.split_at(HASH_SIZE);
+        let id = hash_256(&[hash_256(data_hash), hash_256(end_offset)].concat());
+        return (id == data_root).then_some(ValidatedPath {
+            offset: right_bound - 1,
+            left_bound,
+            right_bound,
+            chunk_size: right_bound - left_bound,
+        });
+    }
+
+    if path.len() < 2 * HASH_SIZE + NOTE_SIZE {
+        return None;
+    }
+    let (left, rest) = path.split_at(HASH_SIZE);
+    let (right, rest) = rest.split_at(HASH_SIZE);
+    let (byte_range, rest) = rest.split_at(NOTE_SIZE);
+    let id = hash_256(&[hash_256(left), hash_256(right), hash_256(byte_range)].concat());
+    if id != data_root {
+        return None;
+    }
+    let byte_range = note_to_usize(byte_range)?;
+    if offset < byte_range {
+        validate_path(left, offset, left_bound, right_bound.min(byte_range), rest)
+    } else {
+        validate_path(right, offset, left_bound.max(byte_range), right_bound, rest)
+    }
+}
+
+fn note(value: usize) -> Vec<u8> {
+    pad_to_32_bytes(&value.to_be_bytes())
+}
+
+fn note_to_usize(note: &[u8]) -> Option<usize> {
+    let (high, low) = note.split_at(NOTE_SIZE - size_of::<usize>());
+    if high.iter().any(|b| *b != 0) {
+        return None;
+    }
+    Some(usize::from_be_bytes(low.try_into().ok()?))
+}
+
 fn generate_proofs(root: MerkelNode) -> Vec<Proof> {
     resolve_branch_proofs(root, None)
 }
@@ -215,6 +284,7 @@ fn resolve_branch_proofs(node: MerkelNode, proof: Option<Vec<u8>>) -> Vec<Proof>
                 .into_iter()
                 .chain(left)
                 .chain(right)
+                .chain(note(b.byte_range))
                 .collect();
             vec![
                 resolve_branch_proofs(*b.left_child.clone().unwrap(), Some(partial.clone())),
@@ -226,12 +296,13 @@ fn resolve_branch_proofs(node: MerkelNode, proof: Option<Vec<u8>>) -> Vec<Proof>
         }
         MerkelNode::Leaf(l) => {
             vec![Proof {
-                offset: node.max_byte_range() - 1,
+                // proof of empty data is discarded
+                offset: node.max_byte_range().saturating_sub(1),
                 proof: proof
                     .unwrap_or_default()
                     .into_iter()
                     .chain(l.data_hash.iter().copied())
-                    .chain(node.max_byte_range().to_be_bytes())
+                    .chain(note(node.max_byte_range()))
                     .collect(),
             }]
         }
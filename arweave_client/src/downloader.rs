@@ -0,0 +1,67 @@
+use anyhow::{anyhow, ensure, Result};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+
+use crate::{
+    chunks::{generate_transaction_chunks, leaf_data_hash, validate_path},
+    utils::hash_256,
+    Rpc,
+};
+
+pub struct Downloader {
+    rpc: Rpc,
+}
+
+impl Downloader {
+    pub fn new(rpc: Rpc) -> Self {
+        Self { rpc }
+    }
+
+    /// Fetches data of transaction `id` chunk by chunk. Every chunk is checked
+    /// against its merkle path and reassembled data against tx `data_root`.
+    pub async fn download(&self, id: &str) -> Result<Vec<u8>> {
+        let tx = self.rpc.get_tx(id).await?;
+        let data_size: usize = tx.data_size.parse()?;
+        if data_size == 0 {
+            return Ok(Vec::new());
+        }
+
+        let data_root = URL_SAFE_NO_PAD.decode(&tx.data_root)?;
+        let data = if tx.data.len() == data_size {
+            tx.data
+        } else {
+            let offset = self.rpc.get_tx_offset(id).await?;
+            ensure!(
+                offset.size == data_size as u128,
+                "Data size mismatch for {id}"
+            );
+            let start = offset.offset + 1 - offset.size;
+            let mut data = Vec::with_capacity(data_size);
+            while data.len() < data_size {
+                let chunk = self.rpc.get_chunk(start + data.len() as u128).await?;
+                let path = validate_path(&data_root, data.len(), 0, data_size, &chunk.data_path)
+                    .ok_or(anyhow!("Invalid data path at {}", data.len()))?;
+                ensure!(
+                    path.left_bound == data.len() && path.chunk_size == chunk.chunk.len(),
+                    "Chunk at {} has unexpected bounds",
+                    data.len()
+                );
+                let data_hash = leaf_data_hash(&chunk.data_path)
+                    .ok_or(anyhow!("Invalid data path at {}", data.len()))?;
+                ensure!(
+                    data_hash == hash_256(&chunk.chunk),
+                    "Chunk at {} does not match its data path",
+                    data.len()
+                );
+                data.extend(chunk.chunk);
+            }
+            data
+        };
+
+        ensure!(data.len() == data_size, "Data size mismatch for {id}");
+        ensure!(
+            generate_transaction_chunks(data.clone()).data_root == data_root,
+            "Data of {id} does not match data root"
+        );
+        Ok(data)
+    }
+}
@@ -1,4 +1,6 @@
+mod bundle;
 mod chunks;
+mod downloader;
 mod rpc;
 mod transaction;
 mod tx_builder;
@@ -7,8 +9,11 @@ mod utils;
 mod wallet;
 
 pub use crate::{
-    rpc::Rpc,
-    transaction::{Tag, Transaction},
-    uploader::Uploader,
-    wallet::{Signer, Wallet},
+    bundle::{deserialize_tags, serialize_tags, Bundle, DataItem, ARWEAVE_SIGNATURE_TYPE},
+    chunks::{generate_transaction_chunks, validate_path, Chunk, Chunks, Proof, ValidatedPath},
+    downloader::Downloader,
+    rpc::{Rpc, ANCHOR_DEPTH},
+    transaction::{Tag, Transaction, TxConfirmation, TxStatus},
+    uploader::{ProgressCallback, UploadCursor, UploadProgress, Uploader},
+    wallet::{owner_to_address, verify, Signer, Wallet},
 };
@@ -1,7 +1,16 @@
 use anyhow::Result;
-use reqwest::Client;
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
 
-use crate::transaction::{GetChunk, Transaction};
+use crate::transaction::{ChunkData, GetChunk, Transaction, TxOffset, TxStatus};
+
+/// Transaction is accepted only within this many blocks after its anchor block
+pub const ANCHOR_DEPTH: u64 = 50;
+
+#[derive(Deserialize)]
+struct Height {
+    height: u64,
+}
 
 #[derive(Default, Clone)]
 pub struct Rpc {
@@ -34,6 +43,17 @@ impl Rpc {
         Ok(val)
     }
 
+    /// Balance of `address` in winston
+    pub async fn get_balance(&self, address: &str) -> Result<String> {
+        let res = self
+            .client
+            .get(format!("{}/wallet/{}/balance", self.url, address))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(res.text().await?)
+    }
+
     pub async fn post_tx(&self, tx: &Transaction) -> Result<()> {
         // println!("{:?}", tx);
         let res = self
@@ -44,11 +64,11 @@ impl Rpc {
             .json(&tx)
             .send()
             .await?;
-        println!("{:?}", res.text().await?);
+        res.error_for_status()?;
         Ok(())
     }
 
-    pub async fn chunk(&self, chunk: GetChunk) -> Result<()> {
+    pub async fn chunk(&self, chunk: &GetChunk) -> Result<()> {
         // println!("{:?}", chunk);
         let res = self
             .client
@@ -58,7 +78,82 @@ impl Rpc {
             .json(&chunk)
             .send()
             .await?;
-        println!("{:?}", res.text().await?);
+        res.error_for_status()?;
         Ok(())
     }
+
+    pub async fn get_tx(&self, id: &str) -> Result<Transaction> {
+        let res = self
+            .client
+            .get(format!("{}/tx/{}", self.url, id))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(res.json().await?)
+    }
+
+    pub async fn get_tx_status(&self, id: &str) -> Result<TxStatus> {
+        let res = self
+            .client
+            .get(format!("{}/tx/{}/status", self.url, id))
+            .send()
+            .await?;
+        match res.status() {
+            StatusCode::NOT_FOUND => Ok(TxStatus::NotFound),
+            StatusCode::ACCEPTED => Ok(TxStatus::Pending),
+            _ => Ok(TxStatus::Confirmed(res.error_for_status()?.json().await?)),
+        }
+    }
+
+    /// Current height of the weave
+    pub async fn get_height(&self) -> Result<u64> {
+        let res = self
+            .client
+            .get(format!("{}/info", self.url))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(res.json::<Height>().await?.height)
+    }
+
+    pub async fn get_block_height(&self, hash: &str) -> Result<u64> {
+        let res = self
+            .client
+            .get(format!("{}/block/hash/{}", self.url, hash))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(res.json::<Height>().await?.height)
+    }
+
+    /// Returns true if `tx` is neither mined nor pending and its anchor is too
+    /// old for it to be accepted anymore, so it has to be signed again
+    pub async fn is_dropped(&self, tx: &Transaction) -> Result<bool> {
+        if self.get_tx_status(&tx.id).await? != TxStatus::NotFound {
+            return Ok(false);
+        }
+        let anchor = self.get_block_height(&tx.last_tx).await?;
+        Ok(self.get_height().await? > anchor + ANCHOR_DEPTH)
+    }
+
+    pub async fn get_tx_offset(&self, id: &str) -> Result<TxOffset> {
+        let res = self
+            .client
+            .get(format!("{}/tx/{}/offset", self.url, id))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(res.json().await?)
+    }
+
+    /// Returns chunk containing byte at absolute weave `offset`
+    pub async fn get_chunk(&self, offset: u128) -> Result<ChunkData> {
+        let res = self
+            .client
+            .get(format!("{}/chunk/{}", self.url, offset))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(res.json().await?)
+    }
 }
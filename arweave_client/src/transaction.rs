@@ -8,9 +8,9 @@ use crate::utils::{DeepHashChunk::Data, DeepHashChunk::List};
 use crate::wallet::Signer;
 use anyhow::{anyhow, Result};
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, prelude::*};
-use serde::{Serialize, Serializer};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-#[derive(Default, Debug, Serialize)]
+#[derive(Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Tag {
     pub name: String,
     pub value: String,
@@ -25,7 +25,38 @@ pub struct GetChunk {
     pub chunk: String,
 }
 
-#[derive(Default, Serialize, Debug)]
+#[derive(Deserialize, Debug)]
+pub struct ChunkData {
+    #[serde(deserialize_with = "data_deserializer")]
+    pub chunk: Vec<u8>,
+    #[serde(deserialize_with = "data_deserializer")]
+    pub data_path: Vec<u8>,
+}
+
+/// Absolute `offset` of the last byte of transaction data in the weave and its `size`
+#[derive(Deserialize, Debug)]
+pub struct TxOffset {
+    #[serde(deserialize_with = "number_deserializer")]
+    pub size: u128,
+    #[serde(deserialize_with = "number_deserializer")]
+    pub offset: u128,
+}
+
+#[derive(Clone, Deserialize, Debug, PartialEq, Eq)]
+pub struct TxConfirmation {
+    pub block_height: u64,
+    pub block_indep_hash: String,
+    pub number_of_confirmations: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TxStatus {
+    NotFound,
+    Pending,
+    Confirmed(TxConfirmation),
+}
+
+#[derive(Default, Serialize, Deserialize, Debug)]
 pub struct Transaction {
     pub format: u8,
     pub id: String,
@@ -35,9 +66,10 @@ pub struct Transaction {
     pub target: String,
     pub quantity: String,
     #[serde(serialize_with = "data_serializer")]
+    #[serde(deserialize_with = "data_deserializer", default)]
     pub data: Vec<u8>,
     pub reward: String,
-    #[serde(skip_serializing)]
+    #[serde(skip)]
     pub chunks: Option<Chunks>,
     pub signature: String,
     pub data_size: String,
@@ -52,6 +84,27 @@ where
     s.serialize_str(&base)
 }
 
+fn data_deserializer<'de, D>(d: D) -> Result<Vec<u8>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    use serde::de::Error;
+    let base = String::deserialize(d)?;
+    URL_SAFE_NO_PAD
+        .decode(base)
+        .map_err(|err| Error::custom(err.to_string()))
+}
+
+fn number_deserializer<'de, D>(d: D) -> Result<u128, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    use serde::de::Error;
+    String::deserialize(d)?
+        .parse()
+        .map_err(|err: std::num::ParseIntError| Error::custom(err.to_string()))
+}
+
 impl Transaction {
     pub fn builder(rpc: Rpc) -> TransactionBuilder {
         TransactionBuilder::new(rpc)
@@ -150,7 +203,7 @@ impl Transaction {
     }
 
     pub fn sign(&mut self, jwk: Arc<Signer>) -> Result<()> {
-        self.set_owner(jwk.address.clone());
+        self.set_owner(jwk.owner.clone());
 
         let data_to_sign = self.get_signature_data()?;
         let raw_signature: Vec<u8> = jwk.sign(&data_to_sign)?;
@@ -1,13 +1,49 @@
-use std::time::Duration;
+use std::{collections::BTreeSet, future::Future, sync::Arc, time::Duration};
 
-use crate::{Rpc, Transaction};
-use anyhow::Result;
+use crate::{
+    transaction::{TxConfirmation, TxStatus},
+    Rpc, Transaction,
+};
+use anyhow::{ensure, Context, Result};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinSet;
+
+const DEFAULT_CONCURRENCY: usize = 4;
+const DEFAULT_RETRIES: usize = 5;
+const DEFAULT_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Upload state that can be stored and passed to a new `Uploader` of the same
+/// signed transaction to continue an interrupted upload
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UploadCursor {
+    pub tx_id: String,
+    pub tx_posted: bool,
+    pub uploaded_chunks: BTreeSet<usize>,
+}
+
+#[derive(Debug)]
+pub enum UploadProgress {
+    TxPosted,
+    ChunkUploaded {
+        index: usize,
+        uploaded: usize,
+        total: usize,
+    },
+    Status(TxStatus),
+}
+
+pub type ProgressCallback = Arc<dyn Fn(&UploadProgress) + Send + Sync>;
 
 pub struct Uploader {
     rpc: Rpc,
     tx: Transaction,
     data: Vec<u8>,
-    last_chunk: usize,
+    cursor: UploadCursor,
+    concurrency: usize,
+    retries: usize,
+    backoff: Duration,
+    on_progress: Option<ProgressCallback>,
 }
 
 impl Uploader {
@@ -15,20 +51,171 @@ impl Uploader {
         Self {
             rpc,
             data: tx.data.clone(),
+            cursor: UploadCursor {
+                tx_id: tx.id.clone(),
+                ..Default::default()
+            },
             tx,
-            last_chunk: 1,
+            concurrency: DEFAULT_CONCURRENCY,
+            retries: DEFAULT_RETRIES,
+            backoff: DEFAULT_BACKOFF,
+            on_progress: None,
         }
     }
 
+    /// Continues upload from previously stored cursor
+    pub fn resume(mut self, cursor: UploadCursor) -> Result<Self> {
+        ensure!(
+            cursor.tx_id == self.tx.id,
+            "Cursor belongs to another transaction"
+        );
+        self.cursor = cursor;
+        Ok(self)
+    }
+
+    /// Maximal number of chunks uploaded at once
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Requests failed with 429, 5xx or transport error are retried `retries`
+    /// times, delay starts from `backoff` and doubles each attempt
+    pub fn retries(mut self, retries: usize, backoff: Duration) -> Self {
+        self.retries = retries;
+        self.backoff = backoff;
+        self
+    }
+
+    pub fn on_progress(
+        mut self,
+        callback: impl Fn(&UploadProgress) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_progress = Some(Arc::new(callback));
+        self
+    }
+
+    pub fn cursor(&self) -> &UploadCursor {
+        &self.cursor
+    }
+
+    pub fn tx_id(&self) -> &str {
+        &self.tx.id
+    }
+
+    fn report(&self, progress: UploadProgress) {
+        if let Some(callback) = &self.on_progress {
+            callback(&progress);
+        }
+    }
+
+    /// Posts transaction and its chunks. Can be called again after an error,
+    /// already uploaded chunks are skipped.
     pub async fn upload_chunks(&mut self) -> Result<()> {
-        if self.tx.chunks.as_ref().unwrap().chunks.len() > 2 {
+        let total = self
+            .tx
+            .chunks
+            .as_ref()
+            .context("Chunks have not been prepared")?
+            .chunks
+            .len();
+        // small data is posted within transaction
+        let chunked = total > 2;
+        if chunked {
             self.tx.data = Vec::new();
         }
-        self.rpc.post_tx(&self.tx).await?;
-        while let Ok(chunk) = self.tx.get_chunk(self.last_chunk, &self.data) {
-            self.rpc.chunk(chunk).await?;
-            tokio::time::sleep(Duration::from_secs(3)).await;
+
+        if !self.cursor.tx_posted {
+            let (rpc, tx) = (&self.rpc, &self.tx);
+            with_retries(self.retries, self.backoff, || rpc.post_tx(tx)).await?;
+            self.cursor.tx_posted = true;
+            self.report(UploadProgress::TxPosted);
+        }
+        if !chunked {
+            return Ok(());
+        }
+
+        let pending: Vec<usize> = (0..total)
+            .filter(|index| !self.cursor.uploaded_chunks.contains(index))
+            .collect();
+        let mut pending = pending.into_iter();
+        let mut tasks = JoinSet::new();
+        loop {
+            while tasks.len() < self.concurrency {
+                let Some(index) = pending.next() else {
+                    break;
+                };
+                let chunk = self.tx.get_chunk(index, &self.data)?;
+                let rpc = self.rpc.clone();
+                let (retries, backoff) = (self.retries, self.backoff);
+                tasks.spawn(async move {
+                    let result = with_retries(retries, backoff, || rpc.chunk(&chunk)).await;
+                    (index, result)
+                });
+            }
+            // dropped tasks are aborted if any chunk fails
+            let Some(joined) = tasks.join_next().await else {
+                break;
+            };
+            let (index, result) = joined?;
+            result?;
+            self.cursor.uploaded_chunks.insert(index);
+            self.report(UploadProgress::ChunkUploaded {
+                index,
+                uploaded: self.cursor.uploaded_chunks.len(),
+                total,
+            });
         }
         Ok(())
     }
+
+    /// Polls transaction status until it has at least `confirmations`
+    pub async fn wait_confirmation(
+        &self,
+        confirmations: u64,
+        poll_interval: Duration,
+    ) -> Result<TxConfirmation> {
+        loop {
+            let status = with_retries(self.retries, self.backoff, || {
+                self.rpc.get_tx_status(&self.tx.id)
+            })
+            .await?;
+            if let TxStatus::Confirmed(confirmation) = &status {
+                if confirmation.number_of_confirmations >= confirmations {
+                    let confirmation = confirmation.clone();
+                    self.report(UploadProgress::Status(status));
+                    return Ok(confirmation);
+                }
+            }
+            self.report(UploadProgress::Status(status));
+            tokio::time::sleep(poll_interval).await;
+        }
+    }
+}
+
+fn is_retryable(err: &anyhow::Error) -> bool {
+    match err.downcast_ref::<reqwest::Error>() {
+        Some(err) => match err.status() {
+            Some(status) => status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error(),
+            None => err.is_timeout() || err.is_connect(),
+        },
+        None => false,
+    }
+}
+
+async fn with_retries<T, F, Fut>(retries: usize, backoff: Duration, request: F) -> Result<T>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut attempt = 0;
+    loop {
+        match request().await {
+            Err(err) if attempt < retries && is_retryable(&err) => {
+                tokio::time::sleep(backoff * 2u32.pow(attempt as u32)).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
 }
@@ -1,15 +1,18 @@
 use anyhow::Result;
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use rsa::{
-    pss::BlindedSigningKey,
+    pss::{BlindedSigningKey, Signature, VerifyingKey},
     rand_core::OsRng,
-    sha2::Sha256,
-    signature::{RandomizedSigner, SignatureEncoding},
-    BigUint, RsaPrivateKey,
+    sha2::{Digest, Sha256},
+    signature::{RandomizedSigner, SignatureEncoding, Verifier},
+    traits::PublicKeyParts,
+    BigUint, RsaPrivateKey, RsaPublicKey,
 };
 use serde::Deserialize;
 use std::fs;
 
+use crate::utils::hash_256;
+
 #[derive(Deserialize)]
 pub struct Wallet {
     pub n: String,
@@ -24,14 +27,17 @@ pub struct Wallet {
 
 #[derive(Clone)]
 pub struct Signer {
+    /// Base64url SHA-256 of the public key modulus
     pub address: String,
+    /// Base64url public key modulus `n`
+    pub owner: String,
     key: BlindedSigningKey<Sha256>,
 }
 
 impl Signer {
     pub fn from_file(path: &str) -> Result<Self> {
-        let text = fs::read_to_string(path).unwrap();
-        let wallet: Wallet = serde_json::from_str(&text).unwrap();
+        let text = fs::read_to_string(path)?;
+        let wallet: Wallet = serde_json::from_str(&text)?;
         Signer::from_wallet(wallet)
     }
 
@@ -48,7 +54,8 @@ impl Signer {
         let key = BlindedSigningKey::<Sha256>::new(private_key);
         Ok(Self {
             key,
-            address: wallet.n,
+            address: owner_to_address(&wallet.n)?,
+            owner: wallet.n,
         })
     }
 
@@ -58,6 +65,29 @@ impl Signer {
     }
 }
 
+/// Wallet address is base64url encoded SHA-256 of the owner
+pub fn owner_to_address(owner: &str) -> Result<String> {
+    Ok(URL_SAFE_NO_PAD.encode(hash_256(&URL_SAFE_NO_PAD.decode(owner)?)))
+}
+
+/// Verifies RSA-PSS `signature` of `owner` modulus. `Signer` uses salt of digest
+/// length while arweave-js signs with the maximal one, both are accepted.
+pub fn verify(owner: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    let Ok(key) = RsaPublicKey::new(BigUint::from_bytes_be(owner), BigUint::from(65537u32)) else {
+        return false;
+    };
+    let Ok(signature) = Signature::try_from(signature) else {
+        return false;
+    };
+    let digest = <Sha256 as Digest>::output_size();
+    let max_salt = (key.n().bits() - 1).div_ceil(8) - digest - 2;
+    [digest, max_salt].into_iter().any(|salt| {
+        VerifyingKey::<Sha256>::new_with_salt_len(key.clone(), salt)
+            .verify(message, &signature)
+            .is_ok()
+    })
+}
+
 fn base64_url_to_biguint(b64: &str) -> Result<BigUint> {
     let bytes = URL_SAFE_NO_PAD.decode(b64)?;
     Ok(BigUint::from_bytes_be(&bytes))
@@ -0,0 +1,201 @@
+use std::sync::Arc;
+
+use arweave_client::{deserialize_tags, serialize_tags, verify, Bundle, DataItem, Signer, Tag};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::Deserialize;
+
+const GOLDEN: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden");
+
+fn tag(name: &str, value: &str) -> Tag {
+    Tag {
+        name: name.to_string(),
+        value: value.to_string(),
+    }
+}
+
+fn item(data: &[u8]) -> DataItem {
+    DataItem {
+        signature: vec![0xaa; 512],
+        owner: vec![0xbb; 512],
+        ..DataItem::new(data.to_vec())
+    }
+    .tags(vec![tag("a", "b")])
+}
+
+#[test]
+fn check_tags_encoding() {
+    assert_eq!(Vec::<u8>::new(), serialize_tags(&[]));
+
+    let tags = vec![tag("Content-Type", "MpData")];
+    let expected = [
+        &[0x02, 0x18][..],
+        b"Content-Type",
+        &[0x0c],
+        b"MpData",
+        &[0x00],
+    ]
+    .concat();
+    assert_eq!(expected, serialize_tags(&tags));
+    assert_eq!(tags, deserialize_tags(&expected).unwrap());
+
+    // length 64 takes two varint bytes
+    let long = "x".repeat(64);
+    let tags = vec![tag("Symbol", &long)];
+    let expected = [
+        &[0x02, 0x0c][..],
+        b"Symbol",
+        &[0x80, 0x01],
+        long.as_bytes(),
+        &[0x00],
+    ]
+    .concat();
+    assert_eq!(expected, serialize_tags(&tags));
+    assert_eq!(tags, deserialize_tags(&expected).unwrap());
+}
+
+#[test]
+fn check_data_item_layout() {
+    let item = item(b"data").target([0x11; 32]);
+    let expected = [
+        &[0x01, 0x00][..],
+        &[0xaa; 512],
+        &[0xbb; 512],
+        &[0x01],
+        &[0x11; 32],
+        &[0x00],
+        &[1, 0, 0, 0, 0, 0, 0, 0],
+        &[6, 0, 0, 0, 0, 0, 0, 0],
+        &[0x02, 0x02, b'a', 0x02, b'b', 0x00],
+        b"data",
+    ]
+    .concat();
+    let bytes = item.serialize();
+    assert_eq!(expected, bytes);
+    assert_eq!(item, DataItem::deserialize(&bytes).unwrap());
+    assert!(DataItem::deserialize(&bytes[..1000]).is_err());
+}
+
+#[test]
+fn check_bundle_layout() {
+    let first = item(b"first");
+    let mut second = item(b"second").anchor([0x22; 32]);
+    second.signature = vec![0xcc; 512];
+    let bundle = Bundle::new(vec![first.clone(), second.clone()]);
+
+    let (first_bytes, second_bytes) = (first.serialize(), second.serialize());
+    let number = |n: usize| {
+        let mut bytes = vec![0; 32];
+        bytes[..8].copy_from_slice(&(n as u64).to_le_bytes());
+        bytes
+    };
+    let expected = [
+        number(2),
+        number(first_bytes.len()),
+        first.raw_id(),
+        number(second_bytes.len()),
+        second.raw_id(),
+        first_bytes,
+        second_bytes,
+    ]
+    .concat();
+    let bytes = bundle.serialize();
+    assert_eq!(expected, bytes);
+    assert_eq!(bundle, Bundle::deserialize(&bytes).unwrap());
+
+    assert_eq!(
+        vec![
+            tag("Bundle-Format", "binary"),
+            tag("Bundle-Version", "2.0.0")
+        ],
+        Bundle::tags()
+    );
+}
+
+#[derive(Deserialize)]
+struct Signature {
+    message: String,
+    signature: String,
+}
+
+fn read<T: for<'de> Deserialize<'de>>(file: &str) -> T {
+    serde_json::from_str(&std::fs::read_to_string(format!("{GOLDEN}/{file}")).unwrap()).unwrap()
+}
+
+fn decode(b64: &str) -> Vec<u8> {
+    URL_SAFE_NO_PAD.decode(b64).unwrap()
+}
+
+#[test]
+fn check_signature_verification() {
+    let signer = Arc::new(Signer::from_file(&format!("{GOLDEN}/wallet.json")).unwrap());
+    let owner = decode(&signer.owner);
+
+    // signed by node crypto with maximal salt, see `tests/golden/signature.mjs`
+    let reference: Signature = read("signature.json");
+    let signature = decode(&reference.signature);
+    assert!(verify(&owner, reference.message.as_bytes(), &signature));
+    assert!(!verify(&owner, b"other message", &signature));
+
+    let mut item = item(b"data");
+    item.sign(signer).unwrap();
+    assert!(item.verify());
+    item.data = b"other data".to_vec();
+    assert!(!item.verify());
+}
+
+#[derive(Deserialize)]
+struct ReferenceItem {
+    data: String,
+    tags: Vec<Tag>,
+    target: Option<String>,
+    anchor: Option<String>,
+    id: String,
+    raw: String,
+}
+
+#[derive(Deserialize)]
+struct ReferenceBundle {
+    items: Vec<ReferenceItem>,
+    bundle: String,
+}
+
+// Items and bundle written by `tests/golden/bundle.mjs` with the arbundles
+// 0.11.2 signing and bundling code (`npm run bundle` in `tests/golden`)
+#[test]
+fn check_arbundles_reference() {
+    let reference: ReferenceBundle = read("bundle.json");
+    let owner = decode(
+        &Signer::from_file(&format!("{GOLDEN}/wallet.json"))
+            .unwrap()
+            .owner,
+    );
+
+    let mut items = Vec::new();
+    for expected in &reference.items {
+        let raw = decode(&expected.raw);
+        let parsed = DataItem::deserialize(&raw).unwrap();
+        assert!(parsed.verify());
+        assert_eq!(expected.id, parsed.id());
+
+        // the same item built here serializes to the same bytes
+        let mut built = DataItem {
+            signature: parsed.signature.clone(),
+            owner: owner.clone(),
+            ..DataItem::new(decode(&expected.data))
+        }
+        .tags(expected.tags.clone());
+        if let Some(target) = &expected.target {
+            built = built.target(decode(target).try_into().unwrap());
+        }
+        if let Some(anchor) = &expected.anchor {
+            built = built.anchor(decode(anchor).try_into().unwrap());
+        }
+        assert_eq!(parsed, built);
+        assert_eq!(raw, built.serialize());
+        items.push(built);
+    }
+
+    let raw = decode(&reference.bundle);
+    assert_eq!(raw, Bundle::new(items.clone()).serialize());
+    assert_eq!(Bundle::new(items), Bundle::deserialize(&raw).unwrap());
+}
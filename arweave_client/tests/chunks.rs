@@ -0,0 +1,145 @@
+use arweave_client::{generate_transaction_chunks, validate_path, ValidatedPath};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::Deserialize;
+
+const KIB: usize = 1024;
+const MAX_CHUNK_SIZE: usize = 256 * KIB;
+const MIN_CHUNK_SIZE: usize = 32 * KIB;
+
+fn data(size: usize) -> Vec<u8> {
+    (0..size).map(|i| (i % 251) as u8).collect()
+}
+
+// Chunk ranges of `data(size)` follow arweave-js chunking rules and every proof
+// resolves its whole chunk
+fn check_chunks(size: usize, ranges: &[(usize, usize)]) {
+    let chunks = generate_transaction_chunks(data(size));
+    assert_eq!(
+        ranges,
+        chunks
+            .chunks
+            .iter()
+            .map(|c| (c.min_byte_range, c.max_byte_range))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(ranges.len(), chunks.proofs.len());
+
+    for (proof, &(left_bound, right_bound)) in chunks.proofs.iter().zip(ranges) {
+        assert_eq!(right_bound - 1, proof.offset);
+        let expected = Some(ValidatedPath {
+            offset: right_bound - 1,
+            left_bound,
+            right_bound,
+            chunk_size: right_bound - left_bound,
+        });
+        // any byte of the chunk resolves to the same path
+        for offset in [left_bound, proof.offset] {
+            assert_eq!(
+                expected,
+                validate_path(&chunks.data_root, offset, 0, size, &proof.proof)
+            );
+        }
+    }
+}
+
+#[test]
+fn check_empty_data() {
+    check_chunks(0, &[]);
+}
+
+#[test]
+fn check_single_byte() {
+    check_chunks(1, &[(0, 1)]);
+}
+
+#[test]
+fn check_multiples_of_max_chunk() {
+    check_chunks(MAX_CHUNK_SIZE, &[(0, 262144)]);
+    check_chunks(2 * MAX_CHUNK_SIZE, &[(0, 262144), (262144, 524288)]);
+    check_chunks(
+        3 * MAX_CHUNK_SIZE,
+        &[(0, 262144), (262144, 524288), (524288, 786432)],
+    );
+}
+
+#[test]
+fn check_small_last_chunk() {
+    // last two chunks are split in halves, bigger half goes first
+    check_chunks(MAX_CHUNK_SIZE + 1000, &[(0, 131572), (131572, 263144)]);
+    check_chunks(
+        2 * MAX_CHUNK_SIZE + MIN_CHUNK_SIZE - 1,
+        &[(0, 262144), (262144, 409600), (409600, 557055)],
+    );
+    check_chunks(
+        2 * MAX_CHUNK_SIZE + MIN_CHUNK_SIZE,
+        &[(0, 262144), (262144, 524288), (524288, 557056)],
+    );
+}
+
+#[derive(Deserialize)]
+struct ReferenceProof {
+    offset: usize,
+    proof: String,
+}
+
+#[derive(Deserialize)]
+struct Reference {
+    size: usize,
+    data_root: String,
+    ranges: Vec<(usize, usize)>,
+    proofs: Vec<ReferenceProof>,
+}
+
+// Roots, ranges and proofs written by `tests/golden/chunks.mjs` with the
+// arweave-js 1.15.5 merkle module (`npm run chunks` in `tests/golden`)
+#[test]
+fn check_arweave_js_reference() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden/chunks.json");
+    let references: Vec<Reference> =
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    assert!(!references.is_empty());
+
+    for reference in references {
+        let chunks = generate_transaction_chunks(data(reference.size));
+        assert_eq!(
+            reference.data_root,
+            URL_SAFE_NO_PAD.encode(&chunks.data_root),
+            "size {}",
+            reference.size
+        );
+        assert_eq!(
+            reference.ranges,
+            chunks
+                .chunks
+                .iter()
+                .map(|c| (c.min_byte_range, c.max_byte_range))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(reference.proofs.len(), chunks.proofs.len());
+        for (expected, proof) in reference.proofs.iter().zip(&chunks.proofs) {
+            assert_eq!(expected.offset, proof.offset);
+            assert_eq!(expected.proof, URL_SAFE_NO_PAD.encode(&proof.proof));
+        }
+    }
+}
+
+#[test]
+fn check_invalid_path() {
+    let size = 3 * MAX_CHUNK_SIZE;
+    let chunks = generate_transaction_chunks(data(size));
+    let proof = &chunks.proofs[1].proof;
+
+    let mut tampered = proof.clone();
+    *tampered.last_mut().unwrap() ^= 1;
+    assert_eq!(
+        None,
+        validate_path(&chunks.data_root, MAX_CHUNK_SIZE, 0, size, &tampered)
+    );
+    assert_eq!(
+        None,
+        validate_path(&chunks.data_root, 0, 0, size, &proof[..proof.len() - 1])
+    );
+    assert_eq!(None, validate_path(&chunks.data_root, 0, 0, 0, proof));
+    // proof of the second chunk does not lead to the first one
+    assert_eq!(None, validate_path(&chunks.data_root, 0, 0, size, proof));
+}
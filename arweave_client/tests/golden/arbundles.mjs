@@ -0,0 +1,374 @@
+// Transcription of the parts of arbundles 0.11.2 (Apache-2.0) used by
+// `bundle.mjs`: `ArweaveSigner`, `createData`, `DataItem.sign`, `deepHash`,
+// `serializeTags` and `bundleAndSignData`, with node's crypto in place of the
+// arweave-js crypto driver, so that references can be written without
+// fetching the package. Layouts and hashing follow the original sources.
+import {
+  constants,
+  createHash,
+  createPrivateKey,
+  createSign,
+} from "node:crypto";
+
+const base64url = {
+  toBuffer: (b64) => Buffer.from(b64, "base64url"),
+  encode: (buffer) => Buffer.from(buffer).toString("base64url"),
+};
+
+// src/signing/chains/ArweaveSigner.ts, signing as arweave-js NodeCryptoDriver
+export class ArweaveSigner {
+  signatureType = 1;
+  ownerLength = 512;
+  signatureLength = 512;
+
+  constructor(jwk) {
+    this.jwk = jwk;
+    this.pk = jwk.n;
+  }
+
+  get publicKey() {
+    return base64url.toBuffer(this.pk);
+  }
+
+  sign(message) {
+    return createSign("sha256")
+      .update(message)
+      .sign({
+        key: createPrivateKey({ key: this.jwk, format: "jwk" }),
+        padding: constants.RSA_PKCS1_PSS_PADDING,
+      });
+  }
+}
+
+// src/utils.ts
+function longTo8ByteArray(long) {
+  const byteArray = [0, 0, 0, 0, 0, 0, 0, 0];
+  for (let index = 0; index < byteArray.length; index++) {
+    const byte = long & 0xff;
+    byteArray[index] = byte;
+    long = (long - byte) / 256;
+  }
+  return Uint8Array.from(byteArray);
+}
+
+function shortTo2ByteArray(long) {
+  const byteArray = [0, 0];
+  for (let index = 0; index < byteArray.length; index++) {
+    const byte = long & 0xff;
+    byteArray[index] = byte;
+    long = (long - byte) / 256;
+  }
+  return Uint8Array.from(byteArray);
+}
+
+function longTo32ByteArray(long) {
+  const byteArray = Array(32).fill(0);
+  for (let index = 0; index < byteArray.length; index++) {
+    const byte = long & 0xff;
+    byteArray[index] = byte;
+    long = (long - byte) / 256;
+  }
+  return Uint8Array.from(byteArray);
+}
+
+// src/tags.ts
+class AVSCTap {
+  constructor(buf = Buffer.alloc(4096), pos = 0) {
+    this.buf = buf;
+    this.pos = pos;
+  }
+
+  writeTags(tags) {
+    if (!Array.isArray(tags)) {
+      throw new Error("input must be array");
+    }
+
+    const n = tags.length;
+    let i;
+    if (n) {
+      this.writeLong(n);
+      for (i = 0; i < n; i++) {
+        const tag = tags[i];
+        if (tag?.name === undefined || tag?.value === undefined)
+          throw new Error(`Invalid tag format for ${tag}, expected {name:string, value: string}`);
+        this.writeString(tag.name);
+        this.writeString(tag.value);
+      }
+    }
+    this.writeLong(0);
+  }
+
+  toBuffer() {
+    const buffer = Buffer.alloc(this.pos);
+    if (this.pos > this.buf.length) throw new Error(`Too many tag bytes (${this.pos} > ${this.buf.length})`);
+    this.buf.copy(buffer, 0, 0, this.pos);
+    return buffer;
+  }
+
+  writeLong(n) {
+    const buf = this.buf;
+    let f, m;
+
+    if (n >= -1073741824 && n < 1073741824) {
+      // Won't overflow, we can use integer arithmetic.
+      m = n >= 0 ? n << 1 : (~n << 1) | 1;
+      do {
+        buf[this.pos] = m & 0x7f;
+        m >>= 7;
+      } while (m && (buf[this.pos++] |= 0x80));
+    } else {
+      // We have to use slower floating arithmetic.
+      f = n >= 0 ? n * 2 : -n * 2 - 1;
+      do {
+        buf[this.pos] = f & 0x7f;
+        f /= 128;
+      } while (f >= 1 && (buf[this.pos++] |= 0x80));
+    }
+    this.pos++;
+    this.buf = buf;
+  }
+
+  writeString(s) {
+    const len = Buffer.byteLength(s);
+    const buf = this.buf;
+    this.writeLong(len);
+    this.pos += len;
+    if (this.pos > buf.length) {
+      return;
+    }
+    this.buf.write(s, this.pos - len, len, "utf8");
+  }
+}
+
+export function serializeTags(tags) {
+  if (tags?.length == 0) {
+    return Buffer.allocUnsafe(0);
+  }
+  const tap = new AVSCTap();
+  tap.writeTags(tags);
+  return tap.toBuffer();
+}
+
+// src/deepHash.ts
+const sha384 = (data) => createHash("sha384").update(data).digest();
+
+export function deepHash(data) {
+  if (Array.isArray(data)) {
+    const tag = Buffer.concat([
+      Buffer.from("list"),
+      Buffer.from(data.length.toString()),
+    ]);
+    return deepHashChunks(data, sha384(tag));
+  }
+
+  const tag = Buffer.concat([
+    Buffer.from("blob"),
+    Buffer.from(data.byteLength.toString()),
+  ]);
+  const taggedHash = Buffer.concat([sha384(tag), sha384(data)]);
+  return sha384(taggedHash);
+}
+
+function deepHashChunks(chunks, acc) {
+  if (chunks.length < 1) {
+    return acc;
+  }
+  const hashPair = Buffer.concat([acc, deepHash(chunks[0])]);
+  const newAcc = sha384(hashPair);
+  return deepHashChunks(chunks.slice(1), newAcc);
+}
+
+// src/DataItem.ts, only getters read by signing and bundling
+export class DataItem {
+  constructor(binary) {
+    this.binary = binary;
+  }
+
+  get signatureType() {
+    return this.binary.readUInt16LE(0);
+  }
+
+  get signatureLength() {
+    return 512;
+  }
+
+  get ownerLength() {
+    return 512;
+  }
+
+  get rawSignature() {
+    return this.binary.subarray(2, 2 + this.signatureLength);
+  }
+
+  get rawOwner() {
+    return this.binary.subarray(2 + this.signatureLength, 2 + this.signatureLength + this.ownerLength);
+  }
+
+  get rawId() {
+    return createHash("sha256").update(this.rawSignature).digest();
+  }
+
+  get id() {
+    return base64url.encode(this.rawId);
+  }
+
+  get rawTarget() {
+    const targetStart = this.getTargetStart();
+    const isPresent = this.binary[targetStart] == 1;
+    return isPresent ? this.binary.subarray(targetStart + 1, targetStart + 33) : Buffer.alloc(0);
+  }
+
+  get rawAnchor() {
+    const anchorStart = this.getAnchorStart();
+    const isPresent = this.binary[anchorStart] == 1;
+    return isPresent ? this.binary.subarray(anchorStart + 1, anchorStart + 33) : Buffer.alloc(0);
+  }
+
+  get rawTags() {
+    const tagsStart = this.getTagsStart();
+    const tagsSize = this.binary.readUInt32LE(tagsStart + 8);
+    return this.binary.subarray(tagsStart + 16, tagsStart + 16 + tagsSize);
+  }
+
+  get rawData() {
+    const tagsStart = this.getTagsStart();
+    const numberOfTagBytes = this.binary.readUInt32LE(tagsStart + 8);
+    const dataStart = tagsStart + 16 + numberOfTagBytes;
+    return this.binary.subarray(dataStart, this.binary.length);
+  }
+
+  getRaw() {
+    return this.binary;
+  }
+
+  isSigned() {
+    return this.rawSignature.some((byte) => byte !== 0);
+  }
+
+  async sign(signer) {
+    const signatureData = deepHash([
+      Buffer.from("dataitem"),
+      Buffer.from("1"),
+      Buffer.from(this.signatureType.toString()),
+      this.rawOwner,
+      this.rawTarget,
+      this.rawAnchor,
+      this.rawTags,
+      this.rawData,
+    ]);
+    const signatureBytes = signer.sign(signatureData);
+    this.binary.set(signatureBytes, 2);
+    return this.rawId;
+  }
+
+  getTargetStart() {
+    return 2 + this.signatureLength + this.ownerLength;
+  }
+
+  getAnchorStart() {
+    let anchorStart = this.getTargetStart() + 1;
+    const targetPresent = this.binary[this.getTargetStart()] == 1;
+    anchorStart += targetPresent ? 32 : 0;
+    return anchorStart;
+  }
+
+  getTagsStart() {
+    const targetStart = this.getTargetStart();
+    const targetPresent = this.binary[targetStart] == 1;
+    let tagsStart = targetStart + (targetPresent ? 33 : 1);
+    const anchorPresent = this.binary[tagsStart] == 1;
+    tagsStart += anchorPresent ? 33 : 1;
+    return tagsStart;
+  }
+}
+
+// src/ar-data-create.ts
+export function createData(data, signer, opts) {
+  const _owner = signer.publicKey;
+
+  const _target = opts?.target ? base64url.toBuffer(opts.target) : null;
+  const target_length = 1 + (_target?.byteLength ?? 0);
+  const _anchor = opts?.anchor ? Buffer.from(opts.anchor) : null;
+  const anchor_length = 1 + (_anchor?.byteLength ?? 0);
+  const _tags = (opts?.tags?.length ?? 0) > 0 ? serializeTags(opts.tags) : null;
+  const tags_length = 16 + (_tags ? _tags.byteLength : 0);
+  const _data = typeof data === "string" ? Buffer.from(data) : Buffer.from(data);
+  const data_length = _data.byteLength;
+
+  const length =
+    2 +
+    signer.signatureLength +
+    signer.ownerLength +
+    target_length +
+    anchor_length +
+    tags_length +
+    data_length;
+  const bytes = Buffer.alloc(length);
+
+  bytes.set(shortTo2ByteArray(signer.signatureType), 0);
+  bytes.set(new Uint8Array(signer.signatureLength).fill(0), 2);
+
+  if (_owner.byteLength !== signer.ownerLength)
+    throw new Error(`Owner must be ${signer.ownerLength} bytes, but was incorrectly ${_owner.byteLength}`);
+  bytes.set(_owner, 2 + signer.signatureLength);
+
+  const position = 2 + signer.signatureLength + signer.ownerLength;
+  bytes[position] = _target ? 1 : 0;
+  if (_target) {
+    if (_target.byteLength !== 32)
+      throw new Error(`Target must be 32 bytes but was incorrectly ${_target.byteLength}`);
+    bytes.set(_target, position + 1);
+  }
+
+  const anchor_start = position + target_length;
+  let tags_start = anchor_start + 1;
+  bytes[anchor_start] = _anchor ? 1 : 0;
+  if (_anchor) {
+    tags_start += _anchor.byteLength;
+    if (_anchor.byteLength !== 32) throw new Error("Anchor must be 32 bytes");
+    bytes.set(_anchor, anchor_start + 1);
+  }
+
+  bytes.set(longTo8ByteArray(opts?.tags?.length ?? 0), tags_start);
+  const bytesCount = longTo8ByteArray(_tags?.byteLength ?? 0);
+  bytes.set(bytesCount, tags_start + 8);
+  if (_tags) {
+    bytes.set(_tags, tags_start + 16);
+  }
+
+  const data_start = tags_start + tags_length;
+
+  bytes.set(_data, data_start);
+
+  return new DataItem(bytes);
+}
+
+// src/ar-data-bundle.ts
+export async function bundleAndSignData(dataItems, signer) {
+  const headers = new Uint8Array(64 * dataItems.length);
+
+  const binaries = await Promise.all(
+    dataItems.map(async (d, index) => {
+      // Sign DataItem
+      const id = d.isSigned() ? d.rawId : await d.sign(signer);
+      // Create header array
+      const header = new Uint8Array(64);
+      // Set offset
+      header.set(longTo32ByteArray(d.getRaw().byteLength), 0);
+      // Set id
+      header.set(id, 32);
+      // Add header to array of headers
+      headers.set(header, 64 * index);
+      // Convert to array for flattening
+      return d.getRaw();
+    })
+  );
+
+  const buffer = Buffer.concat([
+    Buffer.from(longTo32ByteArray(dataItems.length)),
+    Buffer.from(headers),
+    ...binaries,
+  ]);
+
+  return { getRaw: () => buffer };
+}
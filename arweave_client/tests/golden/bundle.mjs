@@ -0,0 +1,52 @@
+// Writes data items and a bundle of them serialized and signed by arbundles
+// with `wallet.json`, see `tests/bundle.rs`.
+//
+//   npm run bundle
+import { readFileSync } from "node:fs";
+import { ArweaveSigner, bundleAndSignData, createData } from "./arbundles.mjs";
+
+const jwk = JSON.parse(readFileSync(new URL("wallet.json", import.meta.url)));
+const signer = new ArweaveSigner(jwk);
+
+const specs = [
+  { data: "first", tags: [{ name: "Content-Type", value: "MpData" }] },
+  {
+    data: "second",
+    tags: [
+      { name: "a", value: "b" },
+      { name: "Symbol", value: "x".repeat(64) },
+    ],
+    target: Buffer.alloc(32, 0x11).toString("base64url"),
+    anchor: "a".repeat(32),
+  },
+];
+
+const items = [];
+for (const spec of specs) {
+  const item = createData(spec.data, signer, {
+    tags: spec.tags,
+    target: spec.target,
+    anchor: spec.anchor,
+  });
+  await item.sign(signer);
+  items.push(item);
+}
+const bundle = await bundleAndSignData(items, signer);
+
+console.log(
+  JSON.stringify(
+    {
+      items: specs.map((spec, i) => ({
+        data: Buffer.from(spec.data).toString("base64url"),
+        tags: spec.tags,
+        target: spec.target ?? null,
+        anchor: spec.anchor ? Buffer.from(spec.anchor).toString("base64url") : null,
+        id: items[i].id,
+        raw: items[i].getRaw().toString("base64url"),
+      })),
+      bundle: bundle.getRaw().toString("base64url"),
+    },
+    null,
+    2,
+  ),
+);
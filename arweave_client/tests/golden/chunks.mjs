@@ -0,0 +1,42 @@
+// Writes data roots, chunk ranges and proofs of `data(size)` computed by
+// arweave-js `generateTransactionChunks`, see `tests/chunks.rs`.
+//
+//   npm run chunks
+import { generateTransactionChunks } from "./merkle.mjs";
+
+const bufferTob64Url = (buffer) => Buffer.from(buffer).toString("base64url");
+
+const KIB = 1024;
+const MAX_CHUNK_SIZE = 256 * KIB;
+const MIN_CHUNK_SIZE = 32 * KIB;
+
+const SIZES = [
+  0,
+  1,
+  MAX_CHUNK_SIZE,
+  2 * MAX_CHUNK_SIZE,
+  3 * MAX_CHUNK_SIZE,
+  MAX_CHUNK_SIZE + 1000,
+  2 * MAX_CHUNK_SIZE + MIN_CHUNK_SIZE - 1,
+  2 * MAX_CHUNK_SIZE + MIN_CHUNK_SIZE,
+];
+
+function data(size) {
+  return Uint8Array.from({ length: size }, (_, i) => i % 251);
+}
+
+const cases = [];
+for (const size of SIZES) {
+  const chunks = await generateTransactionChunks(data(size));
+  cases.push({
+    size,
+    data_root: bufferTob64Url(chunks.data_root),
+    ranges: chunks.chunks.map((c) => [c.minByteRange, c.maxByteRange]),
+    proofs: chunks.proofs.map((p) => ({
+      offset: p.offset,
+      proof: bufferTob64Url(p.proof),
+    })),
+  });
+}
+
+console.log(JSON.stringify(cases, null, 2));
@@ -0,0 +1,193 @@
+// Transcription of arweave-js 1.15.5 `src/common/lib/merkle.ts` (MIT) with
+// node's crypto in place of `Arweave.crypto`, so that references can be
+// written without fetching the package. Only functions used by
+// `generateTransactionChunks` are kept, their order and branches follow
+// the original.
+import { createHash } from "node:crypto";
+
+export const MAX_CHUNK_SIZE = 256 * 1024;
+export const MIN_CHUNK_SIZE = 32 * 1024;
+const NOTE_SIZE = 32;
+
+export async function chunkData(data) {
+  let chunks = [];
+
+  let rest = data;
+  let cursor = 0;
+
+  while (rest.byteLength >= MAX_CHUNK_SIZE) {
+    let chunkSize = MAX_CHUNK_SIZE;
+
+    // If the total bytes left will produce a chunk < MIN_CHUNK_SIZE,
+    // then adjust the amount we put in this 2nd last chunk.
+
+    let nextChunkSize = rest.byteLength - MAX_CHUNK_SIZE;
+    if (nextChunkSize > 0 && nextChunkSize < MIN_CHUNK_SIZE) {
+      chunkSize = Math.ceil(rest.byteLength / 2);
+    }
+
+    const chunk = rest.slice(0, chunkSize);
+    const dataHash = await hash(chunk);
+    cursor += chunk.byteLength;
+    chunks.push({
+      dataHash,
+      minByteRange: cursor - chunk.byteLength,
+      maxByteRange: cursor,
+    });
+    rest = rest.slice(chunkSize);
+  }
+
+  chunks.push({
+    dataHash: await hash(rest),
+    minByteRange: cursor,
+    maxByteRange: cursor + rest.byteLength,
+  });
+
+  return chunks;
+}
+
+export async function generateLeaves(chunks) {
+  return Promise.all(
+    chunks.map(async ({ dataHash, minByteRange, maxByteRange }) => {
+      return {
+        type: "leaf",
+        id: await hash(
+          await Promise.all([hash(dataHash), hash(intToBuffer(maxByteRange))])
+        ),
+        dataHash: dataHash,
+        minByteRange,
+        maxByteRange,
+      };
+    })
+  );
+}
+
+export async function buildLayers(nodes, level = 0) {
+  // If there is only 1 node left, this is going to be the root node
+  if (nodes.length < 2) {
+    const root = nodes[0];
+    return root;
+  }
+
+  const nextLayer = [];
+
+  for (let i = 0; i < nodes.length; i += 2) {
+    nextLayer.push(await hashBranch(nodes[i], nodes[i + 1]));
+  }
+
+  return buildLayers(nextLayer, level + 1);
+}
+
+export function generateProofs(root) {
+  const proofs = resolveBranchProofs(root);
+  if (!Array.isArray(proofs)) {
+    return [proofs];
+  }
+  return arrayFlatten(proofs);
+}
+
+function resolveBranchProofs(node, proof = new Uint8Array(), depth = 0) {
+  if (node.type == "leaf") {
+    return {
+      offset: node.maxByteRange - 1,
+      proof: concatBuffers([
+        proof,
+        node.dataHash,
+        intToBuffer(node.maxByteRange),
+      ]),
+    };
+  }
+
+  if (node.type == "branch") {
+    const partialProof = concatBuffers([
+      proof,
+      node.leftChild.id,
+      node.rightChild.id,
+      intToBuffer(node.byteRange),
+    ]);
+    return [
+      resolveBranchProofs(node.leftChild, partialProof, depth + 1),
+      resolveBranchProofs(node.rightChild, partialProof, depth + 1),
+    ];
+  }
+
+  throw new Error(`Unexpected node type`);
+}
+
+export function arrayFlatten(input) {
+  const flat = [];
+
+  input.forEach((item) => {
+    if (Array.isArray(item)) {
+      flat.push(...arrayFlatten(item));
+    } else {
+      flat.push(item);
+    }
+  });
+
+  return flat;
+}
+
+export async function generateTransactionChunks(data) {
+  const chunks = await chunkData(data);
+  const leaves = await generateLeaves(chunks);
+  const root = await buildLayers(leaves);
+  const proofs = await generateProofs(root);
+
+  // Discard the last chunk & proof if it's zero length.
+  const lastChunk = chunks.slice(-1)[0];
+  if (lastChunk.maxByteRange - lastChunk.minByteRange === 0) {
+    chunks.splice(chunks.length - 1, 1);
+    proofs.splice(proofs.length - 1, 1);
+  }
+
+  return {
+    data_root: root.id,
+    chunks,
+    proofs,
+  };
+}
+
+async function hashBranch(left, right) {
+  if (!right) {
+    return left;
+  }
+  let branch = {
+    type: "branch",
+    id: await hash([
+      await hash(left.id),
+      await hash(right.id),
+      await hash(intToBuffer(left.maxByteRange)),
+    ]),
+    byteRange: left.maxByteRange,
+    maxByteRange: right.maxByteRange,
+    leftChild: left,
+    rightChild: right,
+  };
+
+  return branch;
+}
+
+async function hash(data) {
+  if (Array.isArray(data)) {
+    data = concatBuffers(data);
+  }
+
+  return new Uint8Array(createHash("sha256").update(data).digest());
+}
+
+export function intToBuffer(note) {
+  const buffer = new Uint8Array(NOTE_SIZE);
+
+  for (var i = buffer.length - 1; i >= 0; i--) {
+    var byte = note % 256;
+    buffer[i] = byte;
+    note = (note - byte) / 256;
+  }
+
+  return buffer;
+}
+
+function concatBuffers(buffers) {
+  return new Uint8Array(Buffer.concat(buffers));
+}
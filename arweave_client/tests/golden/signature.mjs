@@ -0,0 +1,23 @@
+// Signs a fixed message with `wallet.json` the way arweave-js `NodeCryptoDriver.sign`
+// does: RSA-PSS over SHA-256 with default salt length, which is the maximal one.
+//
+//   node signature.mjs > signature.json
+import { createPrivateKey, createSign, constants } from "node:crypto";
+import { readFileSync } from "node:fs";
+
+const jwk = JSON.parse(readFileSync(new URL("wallet.json", import.meta.url)));
+const message = "arweave signature";
+const signature = createSign("sha256")
+  .update(message)
+  .sign({
+    key: createPrivateKey({ key: jwk, format: "jwk" }),
+    padding: constants.RSA_PKCS1_PSS_PADDING,
+  });
+
+console.log(
+  JSON.stringify(
+    { node: process.version, message, signature: signature.toString("base64url") },
+    null,
+    2,
+  ),
+);
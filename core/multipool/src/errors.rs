@@ -7,6 +7,10 @@ pub enum MultipoolErrors {
     ZeroCap,
     AssetMissing(Address),
     PriceMissing(Address),
+    PriceExpired(Address),
+    SameAssetSwap(Address),
+    InsufficientQuantity(Address),
+    DeviationExceedsLimit(Address),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -16,4 +20,5 @@ pub enum MultipoolOverflowErrors {
     TotalSharesOverflow(Address),
     PriceCapOverflow,
     TotalSupplyOverflow,
+    FeeOverflow,
 }
@@ -102,10 +102,24 @@ pub struct MultipoolAsset {
     )]
     pub collected_cashbacks: U112,
     pub share: u16,
+
+    /// Cumulative quantity of the asset swapped in and out
+    #[borsh(
+        deserialize_with = "deserialize::u256",
+        serialize_with = "serialize::u256"
+    )]
+    pub swap_volume: U256,
+    /// Cumulative fees charged in the asset
+    #[borsh(
+        deserialize_with = "deserialize::u256",
+        serialize_with = "serialize::u256"
+    )]
+    pub collected_fees: U256,
 }
 
 const X96: u64 = 96;
 const X32: u64 = 32;
+const X16: u64 = 16;
 
 impl MultipoolAsset {
     fn new(address: Address) -> Self {
@@ -116,6 +130,8 @@ impl MultipoolAsset {
             quantity: Default::default(),
             collected_cashbacks: Default::default(),
             share: Default::default(),
+            swap_volume: Default::default(),
+            collected_fees: Default::default(),
         }
     }
 
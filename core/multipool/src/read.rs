@@ -1,14 +1,99 @@
-use std::ops::Shl;
+use std::ops::{Shl, Shr};
 
 use alloy::primitives::{Address, I256, U256, U512};
+use serde::{Deserialize, Serialize};
 
-use multipool_types::expiry::{EmptyTimeExtractor, MayBeExpired};
+use multipool_types::expiry::{EmptyTimeExtractor, MayBeExpired, TimeExtractor};
 
 use super::{
     errors::MultipoolErrors, errors::MultipoolOverflowErrors, Merge, Multipool, MultipoolAsset,
-    X32, X96,
+    X16, X32, X96,
 };
 
+/// Which side of a swap is fixed by the requested amount
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SwapKind {
+    ExactIn,
+    ExactOut,
+}
+
+/// Fees and cashbacks caused by changing quantity of a single asset
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct AssetFees {
+    /// Fee for increasing asset deviation, in quote currency
+    pub deviation_fee: U256,
+    /// Part of deviation fee that is added to asset collected cashbacks
+    pub accrued_cashback: U256,
+    /// Cashback paid from collected cashbacks for decreasing asset deviation
+    pub cashback: U256,
+    /// Asset deviation after the change, x32 signed value
+    pub deviation: I256,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SwapQuote {
+    pub amount_in: U256,
+    pub amount_out: U256,
+    /// Base fee charged on incoming value, in quote currency
+    pub base_fee: U256,
+    pub asset_in_fees: AssetFees,
+    pub asset_out_fees: AssetFees,
+    /// Total fee to be sent with the swap, negative if cashbacks exceed fees
+    pub fee: I256,
+}
+
+/// Quote for minting or burning multipool shares for a single asset
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ShareQuote {
+    /// Asset amount deposited on mint or withdrawn on burn
+    pub amount: U256,
+    /// Shares received by the user on mint or taken from the user on burn
+    pub shares: U256,
+    /// Shares accrued to `management_fee_receiver`
+    pub management_shares: U256,
+    pub management_fee_receiver: Address,
+    /// Base fee charged on moved value, in quote currency
+    pub base_fee: U256,
+    pub asset_fees: AssetFees,
+    /// Total fee to be sent with the call, negative if cashback exceeds fees
+    pub fee: I256,
+}
+
+/// Fee params are stored as x16 fractions, shares and deviations are x32
+fn x16_to_x32(value: u16) -> U256 {
+    U256::from(value).shl(X32 - X16)
+}
+
+fn quoted(quantity: U256, price: U256) -> Result<U256, MultipoolErrors> {
+    quantity
+        .checked_mul(price)
+        .map(|v| v.shr(X96))
+        .ok_or(MultipoolErrors::Overflow(
+            MultipoolOverflowErrors::QuotedQuantityOverflow,
+        ))
+}
+
+fn div_ceil(value: U256, divisor: U256) -> Option<U256> {
+    let (quotient, remainder) = (value.checked_div(divisor)?, value % divisor);
+    Some(quotient + U256::from(!remainder.is_zero()))
+}
+
+fn net_fee(charged: U256, returned: U256) -> Result<I256, MultipoolErrors> {
+    I256::try_from(charged)
+        .ok()
+        .zip(I256::try_from(returned).ok())
+        .map(|(charged, returned)| charged - returned)
+        .ok_or(MultipoolErrors::Overflow(
+            MultipoolOverflowErrors::FeeOverflow,
+        ))
+}
+
+fn share_deviation(quoted: U256, cap: U256, target_share: U256) -> I256 {
+    let share = quoted.shl(X32).checked_div(cap).unwrap_or_default();
+    I256::from_raw(share) - I256::from_raw(target_share)
+}
+
 impl Multipool {
     pub fn asset(&self, asset_address: &Address) -> Result<MultipoolAsset, MultipoolErrors> {
         self.assets
@@ -170,4 +255,392 @@ impl Multipool {
         let amount = amount - I256::from_raw(U256::from(quantity));
         Ok(MayBeExpired::with_time(amount, 0))
     }
+
+    /// Quotes swap of `asset_in` to `asset_out` at current prices, fees are paid in quote currency
+    pub fn quote_swap(
+        &self,
+        asset_in: &Address,
+        asset_out: &Address,
+        amount: U256,
+        kind: SwapKind,
+    ) -> Result<MayBeExpired<SwapQuote, EmptyTimeExtractor>, MultipoolErrors> {
+        if asset_in.eq(asset_out) {
+            return Err(MultipoolErrors::SameAssetSwap(*asset_in));
+        }
+        let input = self.asset(asset_in)?;
+        let output = self.asset(asset_out)?;
+        let price_in = input
+            .price
+            .clone()
+            .ok_or(MultipoolErrors::PriceMissing(*asset_in))?;
+        let price_out = output
+            .price
+            .clone()
+            .ok_or(MultipoolErrors::PriceMissing(*asset_out))?;
+
+        (price_in, price_out, self.cap()?)
+            .merge(|prices| self.quote_swap_with_prices(&input, &output, prices, amount, kind))
+            .transpose()
+    }
+
+    fn quote_swap_with_prices(
+        &self,
+        input: &MultipoolAsset,
+        output: &MultipoolAsset,
+        (price_in, price_out, cap): (U256, U256, U256),
+        amount: U256,
+        kind: SwapKind,
+    ) -> Result<SwapQuote, MultipoolErrors> {
+        let overflow = MultipoolErrors::Overflow(MultipoolOverflowErrors::QuotedQuantityOverflow);
+        let (amount_in, amount_out) = match kind {
+            SwapKind::ExactIn => {
+                let amount_out = amount
+                    .checked_mul(price_in)
+                    .ok_or(overflow.clone())?
+                    .checked_div(price_out)
+                    .ok_or(MultipoolErrors::PriceMissing(output.address))?;
+                (amount, amount_out)
+            }
+            SwapKind::ExactOut => {
+                if price_in.is_zero() {
+                    return Err(MultipoolErrors::PriceMissing(input.address));
+                }
+                // rounded up so the pool never gives more than it receives
+                let amount_in = amount
+                    .checked_mul(price_out)
+                    .ok_or(overflow.clone())
+                    .map(|v| div_ceil(v, price_in))?
+                    .expect("price is checked to be non zero");
+                (amount_in, amount)
+            }
+        };
+
+        let quantity_in = U256::from(input.quantity);
+        let quantity_out = U256::from(output.quantity);
+        let new_quantity_out = quantity_out
+            .checked_sub(amount_out)
+            .ok_or(MultipoolErrors::InsufficientQuantity(output.address))?;
+        let new_quantity_in = quantity_in.checked_add(amount_in).ok_or(overflow)?;
+
+        let quoted_in = quoted(quantity_in, price_in)?;
+        let quoted_out = quoted(quantity_out, price_out)?;
+        let new_quoted_in = quoted(new_quantity_in, price_in)?;
+        let new_quoted_out = quoted(new_quantity_out, price_out)?;
+        let new_cap = cap
+            .checked_add(new_quoted_in)
+            .and_then(|v| v.checked_add(new_quoted_out))
+            .and_then(|v| v.checked_sub(quoted_in.checked_add(quoted_out)?))
+            .ok_or(MultipoolErrors::Overflow(
+                MultipoolOverflowErrors::PriceCapOverflow,
+            ))?;
+
+        let asset_in_fees = self.asset_fees(input, quoted_in, new_quoted_in, cap, new_cap)?;
+        let asset_out_fees = self.asset_fees(output, quoted_out, new_quoted_out, cap, new_cap)?;
+
+        let base_fee = (new_quoted_in - quoted_in)
+            .checked_mul(x16_to_x32(self.base_fee))
+            .ok_or(MultipoolErrors::Overflow(
+                MultipoolOverflowErrors::FeeOverflow,
+            ))?
+            .shr(X32);
+
+        let charged = base_fee + asset_in_fees.deviation_fee + asset_out_fees.deviation_fee;
+        let returned = asset_in_fees.cashback + asset_out_fees.cashback;
+        let fee = net_fee(charged, returned)?;
+
+        Ok(SwapQuote {
+            amount_in,
+            amount_out,
+            base_fee,
+            asset_in_fees,
+            asset_out_fees,
+            fee,
+        })
+    }
+
+    /// Calculates deviation fee and cashback for changing asset quoted value from
+    /// `quoted` to `new_quoted` while pool cap changes from `cap` to `new_cap`
+    pub(crate) fn asset_fees(
+        &self,
+        asset: &MultipoolAsset,
+        quoted: U256,
+        new_quoted: U256,
+        cap: U256,
+        new_cap: U256,
+    ) -> Result<AssetFees, MultipoolErrors> {
+        let target_share = self.target_share(&asset.address)?;
+        let old_deviation = share_deviation(quoted, cap, target_share).unsigned_abs();
+        let deviation = share_deviation(new_quoted, new_cap, target_share);
+        let new_deviation = deviation.unsigned_abs();
+
+        if new_deviation > old_deviation {
+            let deviation_limit = x16_to_x32(self.deviation_limit);
+            if new_deviation >= deviation_limit {
+                return Err(MultipoolErrors::DeviationExceedsLimit(asset.address));
+            }
+            let deviation_fee = x16_to_x32(self.deviation_increase_fee)
+                .checked_mul(new_deviation)
+                .map(|v| v / (deviation_limit - new_deviation))
+                .and_then(|v| v.checked_mul(new_quoted.abs_diff(quoted)))
+                .ok_or(MultipoolErrors::Overflow(
+                    MultipoolOverflowErrors::FeeOverflow,
+                ))?
+                .shr(X32);
+            let accrued_cashback = deviation_fee
+                .checked_mul(x16_to_x32(self.cashback_fee))
+                .ok_or(MultipoolErrors::Overflow(
+                    MultipoolOverflowErrors::FeeOverflow,
+                ))?
+                .shr(X32);
+            Ok(AssetFees {
+                deviation_fee,
+                accrued_cashback,
+                cashback: U256::ZERO,
+                deviation,
+            })
+        } else {
+            let cashback = U256::from(asset.collected_cashbacks)
+                .checked_mul(old_deviation - new_deviation)
+                .and_then(|v| v.checked_div(old_deviation))
+                .unwrap_or_default();
+            Ok(AssetFees {
+                deviation_fee: U256::ZERO,
+                accrued_cashback: U256::ZERO,
+                cashback,
+                deviation,
+            })
+        }
+    }
+
+    /// Returns error if any asset price is missing or older than `max_age` by `E` clock
+    pub fn check_prices<E: TimeExtractor<TimeMeasure = u64>>(
+        &self,
+        max_age: u64,
+    ) -> Result<(), MultipoolErrors> {
+        self.assets.iter().try_for_each(|asset| {
+            asset
+                .price
+                .clone()
+                .ok_or(MultipoolErrors::PriceMissing(asset.address))?
+                .not_older_than::<E>(max_age)
+                .map(|_| ())
+                .ok_or(MultipoolErrors::PriceExpired(asset.address))
+        })
+    }
+
+    /// Price of a single share in quote currency, x96 value
+    pub fn share_price(&self) -> Result<U256, MultipoolErrors> {
+        if self.total_supply.is_zero() {
+            return Ok(U256::from(self.initial_share_price));
+        }
+        self.share_price_with_cap(self.cap()?.any_age())
+    }
+
+    fn share_price_with_cap(&self, cap: U256) -> Result<U256, MultipoolErrors> {
+        if self.total_supply.is_zero() {
+            return Ok(U256::from(self.initial_share_price));
+        }
+        if cap.is_zero() {
+            return Err(MultipoolErrors::ZeroCap);
+        }
+        cap.checked_shl(X96 as usize)
+            .map(|c| c / self.total_supply)
+            .ok_or(MultipoolErrors::Overflow(
+                MultipoolOverflowErrors::PriceCapOverflow,
+            ))
+    }
+
+    /// Quotes deposit of `asset_address` into the pool. `ExactIn` fixes deposited
+    /// amount, `ExactOut` fixes shares received by the user
+    pub fn quote_mint(
+        &self,
+        asset_address: &Address,
+        amount: U256,
+        kind: SwapKind,
+    ) -> Result<MayBeExpired<ShareQuote, EmptyTimeExtractor>, MultipoolErrors> {
+        let asset = self.asset(asset_address)?;
+        let price = asset
+            .price
+            .clone()
+            .ok_or(MultipoolErrors::PriceMissing(*asset_address))?;
+
+        (price, self.cap()?)
+            .merge(|prices| self.quote_mint_with_prices(&asset, prices, amount, kind))
+            .transpose()
+    }
+
+    fn quote_mint_with_prices(
+        &self,
+        asset: &MultipoolAsset,
+        (price, cap): (U256, U256),
+        amount: U256,
+        kind: SwapKind,
+    ) -> Result<ShareQuote, MultipoolErrors> {
+        if price.is_zero() {
+            return Err(MultipoolErrors::PriceMissing(asset.address));
+        }
+        let share_price = self.share_price_with_cap(cap)?;
+        let management_fee = x16_to_x32(self.management_fee);
+        let overflow = MultipoolErrors::Overflow(MultipoolOverflowErrors::TotalSupplyOverflow);
+
+        let (amount, minted) = match kind {
+            SwapKind::ExactIn => {
+                let minted = amount
+                    .checked_mul(price)
+                    .and_then(|v| v.checked_div(share_price))
+                    .ok_or(overflow.clone())?;
+                (amount, minted)
+            }
+            SwapKind::ExactOut => {
+                let minted = amount
+                    .checked_shl(X32 as usize)
+                    .and_then(|v| div_ceil(v, U256::from(1).shl(X32) - management_fee))
+                    .ok_or(overflow.clone())?;
+                let deposit = minted
+                    .checked_mul(share_price)
+                    .and_then(|v| div_ceil(v, price))
+                    .ok_or(overflow.clone())?;
+                (deposit, minted)
+            }
+        };
+        let management_shares = minted
+            .checked_mul(management_fee)
+            .ok_or(overflow.clone())?
+            .shr(X32);
+
+        let quantity = U256::from(asset.quantity);
+        let quoted_value = quoted(quantity, price)?;
+        let new_quoted_value = quoted(quantity.checked_add(amount).ok_or(overflow)?, price)?;
+        // management part is minted to receiver out of the minted shares
+        self.share_quote(
+            asset,
+            amount,
+            minted - management_shares,
+            management_shares,
+            (quoted_value, new_quoted_value, cap),
+        )
+    }
+
+    /// Quotes withdrawal of `asset_address` from the pool. `ExactIn` fixes shares
+    /// taken from the user, `ExactOut` fixes withdrawn amount
+    pub fn quote_burn(
+        &self,
+        asset_address: &Address,
+        amount: U256,
+        kind: SwapKind,
+    ) -> Result<MayBeExpired<ShareQuote, EmptyTimeExtractor>, MultipoolErrors> {
+        let asset = self.asset(asset_address)?;
+        let price = asset
+            .price
+            .clone()
+            .ok_or(MultipoolErrors::PriceMissing(*asset_address))?;
+
+        (price, self.cap()?)
+            .merge(|prices| self.quote_burn_with_prices(&asset, prices, amount, kind))
+            .transpose()
+    }
+
+    fn quote_burn_with_prices(
+        &self,
+        asset: &MultipoolAsset,
+        (price, cap): (U256, U256),
+        amount: U256,
+        kind: SwapKind,
+    ) -> Result<ShareQuote, MultipoolErrors> {
+        if price.is_zero() {
+            return Err(MultipoolErrors::PriceMissing(asset.address));
+        }
+        let share_price = self.share_price_with_cap(cap)?;
+        let management_fee = x16_to_x32(self.management_fee);
+        let overflow = MultipoolErrors::Overflow(MultipoolOverflowErrors::TotalSupplyOverflow);
+
+        let (withdrawal, shares) = match kind {
+            SwapKind::ExactIn => {
+                let management_shares = amount
+                    .checked_mul(management_fee)
+                    .ok_or(overflow.clone())?
+                    .shr(X32);
+                let withdrawal = (amount - management_shares)
+                    .checked_mul(share_price)
+                    .and_then(|v| v.checked_div(price))
+                    .ok_or(overflow.clone())?;
+                (withdrawal, amount)
+            }
+            SwapKind::ExactOut => {
+                let burned = amount
+                    .checked_mul(price)
+                    .and_then(|v| div_ceil(v, share_price))
+                    .ok_or(overflow.clone())?;
+                let shares = burned
+                    .checked_shl(X32 as usize)
+                    .and_then(|v| div_ceil(v, U256::from(1).shl(X32) - management_fee))
+                    .ok_or(overflow.clone())?;
+                (amount, shares)
+            }
+        };
+        if shares > self.total_supply {
+            return Err(MultipoolErrors::InsufficientQuantity(self.contract_address));
+        }
+        let management_shares = shares.checked_mul(management_fee).ok_or(overflow)?.shr(X32);
+
+        let quantity = U256::from(asset.quantity);
+        let new_quantity = quantity
+            .checked_sub(withdrawal)
+            .ok_or(MultipoolErrors::InsufficientQuantity(asset.address))?;
+        self.share_quote(
+            asset,
+            withdrawal,
+            shares,
+            management_shares,
+            (quoted(quantity, price)?, quoted(new_quantity, price)?, cap),
+        )
+    }
+
+    fn share_quote(
+        &self,
+        asset: &MultipoolAsset,
+        amount: U256,
+        shares: U256,
+        management_shares: U256,
+        (quoted_value, new_quoted_value, cap): (U256, U256, U256),
+    ) -> Result<ShareQuote, MultipoolErrors> {
+        let new_cap = cap
+            .checked_add(new_quoted_value)
+            .and_then(|v| v.checked_sub(quoted_value))
+            .ok_or(MultipoolErrors::Overflow(
+                MultipoolOverflowErrors::PriceCapOverflow,
+            ))?;
+        let target_share = self.target_share(&asset.address)?;
+
+        // first mint defines the pool composition and is not charged for deviation
+        let asset_fees = if self.total_supply.is_zero() {
+            AssetFees {
+                deviation: share_deviation(new_quoted_value, new_cap, target_share),
+                ..Default::default()
+            }
+        } else {
+            self.asset_fees(asset, quoted_value, new_quoted_value, cap, new_cap)?
+        };
+
+        let fee_overflow = MultipoolErrors::Overflow(MultipoolOverflowErrors::FeeOverflow);
+        let base_fee = new_quoted_value
+            .abs_diff(quoted_value)
+            .checked_mul(x16_to_x32(self.base_fee))
+            .ok_or(fee_overflow.clone())?
+            .shr(X32);
+        let charged = base_fee
+            .checked_add(asset_fees.deviation_fee)
+            .ok_or(fee_overflow)?;
+        let fee = net_fee(charged, asset_fees.cashback)?;
+
+        Ok(ShareQuote {
+            amount,
+            shares,
+            management_shares,
+            management_fee_receiver: self.management_fee_receiver,
+            base_fee,
+            asset_fees,
+            fee,
+        })
+    }
 }
@@ -1,4 +1,4 @@
-use super::multipool_builder::MultipoolMockBuilder;
+use super::multipool_builder::{asset_change, target_share_change, MultipoolMockBuilder};
 use crate::errors::MultipoolErrors::*;
 use crate::errors::MultipoolOverflowErrors::*;
 use crate::tests::ADDRESSES;
@@ -9,7 +9,7 @@ use pretty_assertions::assert_eq;
 
 #[test]
 fn check_asset_missing() {
-    let contract_address = Address::from_slice(&[0x10; 20]);
+    let contract_address = Address::with_last_byte(0x10);
     let multipool = MultipoolMockBuilder::new(contract_address);
     let expected_error = AssetMissing(contract_address);
     assert_eq!(
@@ -20,26 +20,27 @@ fn check_asset_missing() {
 
 #[test]
 fn check_cap_quantity_slot_missing() {
-    let contract_address = Address::from_slice(0x10_u64.to_be_bytes().as_ref());
+    let contract_address = Address::with_last_byte(0x10);
 
+    // assets without AssetChange events hold nothing and add nothing to cap
     let multipool = MultipoolMockBuilder::new(contract_address)
         .insert_assets(ADDRESSES.to_vec())
+        .with_prices(ADDRESSES.iter().map(|a| (*a, U256::from(10))).collect(), 0)
         .build();
 
-    let expected_error = QuantitySlotMissing(ADDRESSES[0]);
-    assert_eq!(multipool.cap(), Err(expected_error),);
+    assert_eq!(Ok(U256::ZERO), multipool.cap().map(|cap| cap.any_age()));
 }
 
 #[test]
 fn check_cap_price_missing() {
-    let contract_address = Address::from_slice(10_u64.to_be_bytes().as_ref());
+    let contract_address = Address::with_last_byte(0x10);
     let multipool = MultipoolMockBuilder::new(contract_address)
         .insert_assets(ADDRESSES.to_vec())
-        .fill_updated_quantities_with_value(
-            ADDRESSES.to_vec(),
-            U256::from(10),
-            Some(contract_address),
-        )
+        .with_events(&[
+            asset_change(ADDRESSES[0], 10),
+            asset_change(ADDRESSES[1], 10),
+            asset_change(contract_address, 10),
+        ])
         .build();
     let expected_error = PriceMissing(ADDRESSES[0]);
     assert_eq!(Err(expected_error), multipool.cap());
@@ -47,29 +48,25 @@ fn check_cap_price_missing() {
 
 #[test]
 fn check_cap_overflow() {
-    let contract_address = Address::from_slice(0x10_u64.to_be_bytes().as_ref());
+    let contract_address = Address::with_last_byte(0x10);
     let multipool = MultipoolMockBuilder::new(contract_address)
-        .fill_updated_quantities_with_value(ADDRESSES.to_vec(), U256::MAX, None)
-        .set_quantity(contract_address, U256::MAX)
-        .fill_updated_prices_with_value(ADDRESSES.to_vec(), U256::from(1) << 96)
+        .insert_assets(ADDRESSES.to_vec())
+        .with_events(&[asset_change(ADDRESSES[2], u128::MAX)])
+        .with_prices(ADDRESSES.iter().map(|a| (*a, U256::MAX)).collect(), 0)
         .build();
     let expected_error = Overflow(QuotedQuantityOverflow);
     assert_eq!(Err(expected_error), multipool.cap());
 }
 
 #[test]
-fn check_target_share_share_missing() {
-    let contract_address = Address::from_slice(0x10_u64.to_be_bytes().as_ref());
+fn check_target_share_zero_total_shares() {
+    let contract_address = Address::with_last_byte(0x10);
     let multipool = MultipoolMockBuilder::new(contract_address)
         .insert_assets(ADDRESSES.to_vec())
-        .fill_updated_quantities_with_value(
-            ADDRESSES.to_vec(),
-            U256::from(10),
-            Some(contract_address),
-        )
+        .with_events(&[asset_change(ADDRESSES[1], 10)])
         .build();
 
-    let expected_error = ShareMissing(ADDRESSES[1]);
+    let expected_error = Overflow(TotalSupplyOverflow);
     assert_eq!(
         Err(expected_error),
         multipool.clone().target_share(&ADDRESSES[1].clone())
@@ -78,16 +75,12 @@ fn check_target_share_share_missing() {
 
 #[test]
 fn check_current_share_zero_division() {
-    let contract_address = Address::from_slice(0x10_u64.to_be_bytes().as_ref());
+    let contract_address = Address::with_last_byte(0x10);
 
     let multipool = MultipoolMockBuilder::new(contract_address)
         .insert_assets(ADDRESSES.to_vec())
-        .fill_updated_prices_with_value(ADDRESSES.to_vec(), U256::from(10))
-        .fill_updated_quantities_with_value(
-            ADDRESSES.to_vec(),
-            U256::from(10),
-            Some(contract_address),
-        )
+        .with_prices(ADDRESSES.iter().map(|a| (*a, U256::from(10))).collect(), 0)
+        .with_events(&ADDRESSES.map(|a| asset_change(a, 10)))
         .build();
     let expected_error = ZeroCap;
 
@@ -99,16 +92,12 @@ fn check_current_share_zero_division() {
 
 #[test]
 fn check_quantity_to_deviation_overflow() {
-    let contract_address = Address::from_slice(0x10_u64.to_be_bytes().as_ref());
+    let contract_address = Address::with_last_byte(0x10);
     let multipool = MultipoolMockBuilder::new(contract_address)
         .insert_assets(ADDRESSES.to_vec())
-        .fill_updated_shares_with_value(ADDRESSES.to_vec(), U256::from(10))
-        .fill_updated_quantities_with_value(
-            ADDRESSES.to_vec(),
-            U256::from(10),
-            Some(contract_address),
-        )
-        .fill_updated_prices_with_value(ADDRESSES.to_vec(), U256::from(10))
+        .with_events(&ADDRESSES.map(|a| target_share_change(a, 10, 50)))
+        .with_events(&ADDRESSES.map(|a| asset_change(a, 10)))
+        .with_prices(ADDRESSES.iter().map(|a| (*a, U256::from(10))).collect(), 0)
         .build();
 
     let expected_error = Overflow(TargetDeviationOverflow);
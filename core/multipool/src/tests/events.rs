@@ -0,0 +1,45 @@
+use super::multipool_builder::MultipoolMockBuilder;
+use super::*;
+use alloy::primitives::I256;
+use multipool_types::Multipool::{MultipoolEvents, Swap};
+use pretty_assertions::assert_eq;
+
+fn account(n: u8) -> Address {
+    Address::with_last_byte(n)
+}
+
+fn swap(asset: Address, quantity: i64, fee: u64) -> MultipoolEvents {
+    MultipoolEvents::Swap(Swap {
+        asset,
+        quantity: I256::try_from(quantity).unwrap(),
+        fee: U256::from(fee),
+    })
+}
+
+#[test]
+fn check_swaps_accumulate_volume_and_fees() {
+    let multipool = MultipoolMockBuilder::new(account(0x10))
+        .with_events(&[
+            swap(account(1), 100, 3),
+            swap(account(2), -50, 0),
+            swap(account(1), -40, 2),
+        ])
+        .build();
+
+    let asset = multipool.asset(&account(1)).unwrap();
+    assert_eq!(U256::from(140), asset.swap_volume);
+    assert_eq!(U256::from(5), asset.collected_fees);
+
+    let asset = multipool.asset(&account(2)).unwrap();
+    assert_eq!(U256::from(50), asset.swap_volume);
+    assert_eq!(U256::ZERO, asset.collected_fees);
+}
+
+#[test]
+fn check_share_swaps_are_ignored() {
+    let multipool = MultipoolMockBuilder::new(account(0x10))
+        .with_events(&[swap(account(0x10), 100, 1)])
+        .build();
+
+    assert!(multipool.assets.is_empty());
+}
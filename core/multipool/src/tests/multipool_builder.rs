@@ -1,7 +1,5 @@
-use alloy::primitives::{Address, U128, U256};
-use multipool_types::Multipool::MultipoolEvents;
-
-use multipool_types::expiry::StdTimeExtractor;
+use alloy::primitives::{aliases::U112, Address, U128, U256};
+use multipool_types::Multipool::{AssetChange, MultipoolEvents, TargetShareChange};
 
 use std::cmp::Ordering;
 
@@ -31,13 +29,43 @@ impl PartialEq for MultipoolAsset {
     }
 }
 
+fn compare_assets(a: &MultipoolAsset, b: &MultipoolAsset) -> Ordering {
+    a.address.cmp(&b.address)
+}
+
+pub fn asset_change(asset: Address, quantity: u128) -> MultipoolEvents {
+    MultipoolEvents::AssetChange(AssetChange {
+        asset,
+        quantity,
+        collectedCashbacks: U112::ZERO,
+    })
+}
+
+pub fn target_share_change(asset: Address, share: u16, total_shares: u16) -> MultipoolEvents {
+    MultipoolEvents::TargetShareChange(TargetShareChange {
+        asset,
+        newTargetShare: U256::from(share),
+        newTotalTargetShares: U256::from(total_shares),
+    })
+}
+
+pub fn prices(
+    values: &[(Address, U256)],
+    timestamp: u64,
+) -> Vec<(Address, MayBeExpired<U256, EmptyTimeExtractor>)> {
+    values
+        .iter()
+        .map(|(address, price)| (*address, MayBeExpired::with_time(*price, timestamp)))
+        .collect()
+}
+
 // TODO: create a mock extractor
 #[derive(Clone)]
 pub struct MultipoolMockBuilder(Multipool);
 
 impl MultipoolMockBuilder {
     pub fn new(contract_address: Address) -> Self {
-        Self(Multipool::new(contract_address))
+        Self(Multipool::new(contract_address, 1))
     }
 
     pub fn build(self) -> Multipool {
@@ -53,31 +81,45 @@ impl MultipoolMockBuilder {
     }
 
     pub fn with_prices(mut self, values: Vec<(Address, U256)>, timestamp: u64) -> Self {
-        self.0.update_prices(&values, timestamp);
+        self.0.update_prices(&prices(&values, timestamp));
         self
     }
 
     pub fn with_events(mut self, values: &[MultipoolEvents]) -> Self {
-        self.0.apply_events(&values);
+        self.0.apply_events(values);
         self
     }
 
     pub fn with_price(mut self, address: Address, value: U256, timestamp: u64) -> Self {
         let p = Some(MayBeExpired::with_time(value, timestamp));
-        if let Some(asset) = self
-            .0
-            .assets
-            .iter_mut()
-            .find(|asset| asset.address == address)
-        {
-            asset.price = p;
+        self.asset_mut(address).price = p;
+        self
+    }
+
+    pub fn with_quantity(mut self, address: Address, value: U128) -> Self {
+        if address == self.0.contract_address {
+            self.0.total_supply = U256::from(value);
         } else {
-            let mut asset = MultipoolAsset::new(address);
-            asset.price = p;
-            self.0.assets.push(asset);
+            self.asset_mut(address).quantity = value;
         }
         self
     }
+
+    pub fn with_share(mut self, address: Address, share: u16) -> Self {
+        self.asset_mut(address).share = share;
+        self.0.total_target_shares = self.0.assets.iter().map(|asset| asset.share).sum();
+        self
+    }
+
+    fn asset_mut(&mut self, address: Address) -> &mut MultipoolAsset {
+        match self.0.assets.iter().position(|a| a.address == address) {
+            Some(idx) => &mut self.0.assets[idx],
+            None => {
+                self.0.assets.push(MultipoolAsset::new(address));
+                self.0.assets.last_mut().expect("asset was just pushed")
+            }
+        }
+    }
 }
 
 //fill multipool with similar values, but other way
@@ -96,6 +138,8 @@ pub fn multipool_fixture(
             price_data: Default::default(),
             collected_cashbacks: Default::default(),
             share,
+            swap_volume: Default::default(),
+            collected_fees: Default::default(),
         };
         total_target_shares += share;
         assets.push(asset)
@@ -111,42 +155,23 @@ pub fn multipool_fixture(
 
 pub fn read_method_fixture(contract_address: Address) -> Multipool {
     //target_shares will be 20% 5% 5% 30% 40% for 5 tokens
-    let shares: Vec<U256> = vec![
-        U256::from(200) << 96,
-        U256::from(50) << 96,
-        U256::from(50) << 96,
-        U256::from(300) << 96,
-        U256::from(400) << 96,
-    ];
-    let prices: Vec<U256> = vec![
-        U256::from(15) << 96,
-        U256::from(21) << 96,
-        U256::from(54) << 96,
-        U256::from(11) << 96,
-        U256::from(191) << 96,
-    ];
-    let quantities: Vec<QuantityData> = vec![
-        QuantityData::new(U256::from(30) << 96, U256::default()),
-        QuantityData::new(U256::from(10) << 96, U256::default()),
-        QuantityData::new(U256::from(4) << 96, U256::default()),
-        QuantityData::new(U256::from(1441) << 96, U256::default()),
-        QuantityData::new(U256::from(440) << 96, U256::default()),
-    ];
-
-    let new_prices_info: Vec<(Address, U256)> = ADDRESSES.iter().copied().zip(prices).collect();
-    let new_shares_info: Vec<(Address, U256)> = ADDRESSES.iter().copied().zip(shares).collect();
-
-    let mut new_quantity_info: Vec<(Address, QuantityData)> =
-        ADDRESSES.iter().copied().zip(quantities).collect();
-    new_quantity_info.extend_from_slice(&[(
-        contract_address,
-        QuantityData::new(U256::from(1893) << 96, U256::default()),
-    )]);
-
-    MultipoolMockBuilder::new(contract_address)
-        .insert_assets(ADDRESSES.to_vec())
-        .fill_updated_prices(new_prices_info)
-        .fill_updated_shares(new_shares_info)
-        .fill_updated_quantities(new_quantity_info, None)
-        .build()
+    let shares: [u16; 5] = [200, 50, 50, 300, 400];
+    let prices: [u64; 5] = [15, 21, 54, 11, 191];
+    let quantities: [u64; 5] = [30, 10, 4, 1441, 440];
+
+    let assets = ADDRESSES
+        .iter()
+        .zip(shares)
+        .zip(prices)
+        .zip(quantities)
+        .map(|(((address, share), price), quantity)| {
+            (
+                *address,
+                U128::from(quantity) << 96,
+                U256::from(price) << 96,
+                share,
+            )
+        })
+        .collect();
+    multipool_fixture(contract_address, assets, U256::from(1893) << 96)
 }
@@ -0,0 +1,329 @@
+use super::multipool_builder::read_method_fixture;
+use super::*;
+use crate::read::{AssetFees, ShareQuote, SwapKind, SwapQuote};
+use alloy::primitives::{aliases::U112, I256};
+use multipool_types::expiry::TimeExtractor;
+use pretty_assertions::assert_eq;
+
+struct MockTimeExtractor;
+
+impl TimeExtractor for MockTimeExtractor {
+    type TimeMeasure = u64;
+    fn now() -> u64 {
+        100
+    }
+}
+
+// read_method_fixture with fees, asset 4 is overweight and asset 0 is underweight
+fn fee_fixture() -> Multipool {
+    let mut multipool = read_method_fixture(Address::with_last_byte(0x10));
+    multipool.deviation_limit = 1 << 15;
+    multipool.deviation_increase_fee = 1 << 12;
+    multipool.cashback_fee = 1 << 14;
+    multipool.base_fee = 1 << 6;
+    multipool
+}
+
+#[test]
+fn check_quote_swap_exact_in() {
+    let multipool = fee_fixture();
+    let quote = multipool
+        .quote_swap(
+            &ADDRESSES[4],
+            &ADDRESSES[0],
+            U256::from(1) << 96,
+            SwapKind::ExactIn,
+        )
+        .unwrap()
+        .any_age();
+
+    let expected = SwapQuote {
+        amount_in: U256::from(1) << 96,
+        amount_out: U256::from(1008838602681632565357792967611_u128),
+        base_fee: U256::from(14777909218969227031608295424_u128),
+        asset_in_fees: AssetFees {
+            deviation_fee: U256::from(6431486641882031498186826186752_u128),
+            accrued_cashback: U256::from(1607871660470507874546706546688_u128),
+            cashback: U256::ZERO,
+            deviation: I256::try_from(1872170477).unwrap(),
+        },
+        asset_out_fees: AssetFees {
+            deviation_fee: U256::from(617133624207846439429565579263_u128),
+            accrued_cashback: U256::from(154283406051961609857391394815_u128),
+            cashback: U256::ZERO,
+            deviation: I256::try_from(-847954166).unwrap(),
+        },
+        fee: I256::try_from(7063398175308847164648000061439_u128).unwrap(),
+    };
+    assert_eq!(expected, quote);
+}
+
+#[test]
+fn check_quote_swap_exact_out_rounds_up() {
+    let multipool = fee_fixture();
+    let quote = multipool
+        .quote_swap(
+            &ADDRESSES[2],
+            &ADDRESSES[1],
+            U256::from(3),
+            SwapKind::ExactOut,
+        )
+        .unwrap()
+        .any_age();
+
+    assert_eq!(U256::from(2), quote.amount_in);
+    assert_eq!(U256::from(3), quote.amount_out);
+    assert_eq!(I256::ZERO, quote.fee);
+}
+
+#[test]
+fn check_quote_swap_cashback() {
+    let mut multipool = fee_fixture();
+    multipool.assets[4].collected_cashbacks = U112::from(100) << 96;
+
+    let quote = multipool
+        .quote_swap(
+            &ADDRESSES[0],
+            &ADDRESSES[4],
+            U256::from(10) << 96,
+            SwapKind::ExactIn,
+        )
+        .unwrap()
+        .any_age();
+
+    assert_eq!(
+        U256::from(62221070037380369837861741101_u128),
+        quote.amount_out
+    );
+    assert_eq!(
+        U256::from(11605687868300440077179289600_u128),
+        quote.base_fee
+    );
+    assert_eq!(U256::ZERO, quote.asset_in_fees.cashback);
+    assert_eq!(
+        U256::from(27174378677425192341080469807_u128),
+        quote.asset_out_fees.cashback
+    );
+    assert_eq!(
+        I256::try_from(-15568690809124752263901180207_i128).unwrap(),
+        quote.fee
+    );
+}
+
+#[test]
+fn check_quote_swap_deviation_limit() {
+    let mut multipool = fee_fixture();
+    // 43.6% limit is above current 43.4% deviation of asset 4
+    multipool.deviation_limit = 28600;
+
+    assert_eq!(
+        Err(MultipoolErrors::DeviationExceedsLimit(ADDRESSES[4])),
+        multipool.quote_swap(
+            &ADDRESSES[4],
+            &ADDRESSES[0],
+            U256::from(2) << 96,
+            SwapKind::ExactIn
+        )
+    );
+}
+
+#[test]
+fn check_quote_swap_errors() {
+    let mut multipool = fee_fixture();
+
+    assert_eq!(
+        Err(MultipoolErrors::InsufficientQuantity(ADDRESSES[2])),
+        multipool.quote_swap(
+            &ADDRESSES[1],
+            &ADDRESSES[2],
+            U256::from(5) << 96,
+            SwapKind::ExactOut
+        )
+    );
+    assert_eq!(
+        Err(MultipoolErrors::SameAssetSwap(ADDRESSES[1])),
+        multipool.quote_swap(
+            &ADDRESSES[1],
+            &ADDRESSES[1],
+            U256::from(1),
+            SwapKind::ExactIn
+        )
+    );
+
+    multipool.assets[1].price = None;
+    assert_eq!(
+        Err(MultipoolErrors::PriceMissing(ADDRESSES[1])),
+        multipool.quote_swap(
+            &ADDRESSES[1],
+            &ADDRESSES[2],
+            U256::from(1),
+            SwapKind::ExactIn
+        )
+    );
+}
+
+#[test]
+fn check_quote_mint_exact_in() {
+    let mut multipool = fee_fixture();
+    multipool.management_fee = 1 << 12;
+    multipool.management_fee_receiver = Address::with_last_byte(0x20);
+
+    let quote = multipool
+        .quote_mint(&ADDRESSES[4], U256::from(1) << 96, SwapKind::ExactIn)
+        .unwrap()
+        .any_age();
+
+    let expected = ShareQuote {
+        amount: U256::from(1) << 96,
+        shares: U256::from(266511842820054153641580310103_u128),
+        management_shares: U256::from(17767456188003610242772020673_u128),
+        management_fee_receiver: Address::with_last_byte(0x20),
+        base_fee: U256::from(14777909218969227031608295424_u128),
+        asset_fees: AssetFees {
+            deviation_fee: U256::from(6253867113285694272531310051328_u128),
+            accrued_cashback: U256::from(1563466778321423568132827512832_u128),
+            cashback: U256::ZERO,
+            deviation: I256::try_from(1865378345).unwrap(),
+        },
+        fee: I256::try_from(6268645022504663499562918346752_u128).unwrap(),
+    };
+    assert_eq!(expected, quote);
+}
+
+#[test]
+fn check_quote_mint_exact_out() {
+    let mut multipool = fee_fixture();
+    multipool.management_fee = 1 << 12;
+
+    let quote = multipool
+        .quote_mint(&ADDRESSES[3], U256::from(15) << 96, SwapKind::ExactOut)
+        .unwrap()
+        .any_age();
+
+    assert_eq!(
+        U256::from(6134435385544733808798842236763_u128),
+        quote.amount
+    );
+    assert_eq!(U256::from(15) << 96, quote.shares);
+    assert_eq!(U256::from(1) << 96, quote.management_shares);
+    assert_eq!(U256::ZERO, quote.asset_fees.deviation_fee);
+}
+
+#[test]
+fn check_quote_first_mint() {
+    let mut multipool = fee_fixture();
+    multipool.total_supply = U256::ZERO;
+    multipool.initial_share_price = U96::from(1) << 95;
+    multipool
+        .assets
+        .iter_mut()
+        .for_each(|asset| asset.quantity = U128::ZERO);
+
+    let quote = multipool
+        .quote_mint(&ADDRESSES[1], U256::from(1) << 96, SwapKind::ExactIn)
+        .unwrap()
+        .any_age();
+
+    assert_eq!(U256::from(42) << 96, quote.shares);
+    assert_eq!(U256::ZERO, quote.asset_fees.deviation_fee);
+}
+
+#[test]
+fn check_quote_burn_exact_in() {
+    let mut multipool = fee_fixture();
+    multipool.management_fee = 1 << 12;
+    multipool.management_fee_receiver = Address::with_last_byte(0x20);
+    multipool.assets[4].collected_cashbacks = U112::from(100) << 96;
+
+    let quote = multipool
+        .quote_burn(&ADDRESSES[4], U256::from(16) << 96, SwapKind::ExactIn)
+        .unwrap()
+        .any_age();
+
+    let expected = ShareQuote {
+        amount: U256::from(331211334625288311011717594589_u128),
+        shares: U256::from(16) << 96,
+        management_shares: U256::from(1) << 96,
+        management_fee_receiver: Address::with_last_byte(0x20),
+        base_fee: U256::from(61778676673271550198474668521_u128),
+        asset_fees: AssetFees {
+            deviation_fee: U256::ZERO,
+            accrued_cashback: U256::ZERO,
+            cashback: U256::from(24203718182661571436718061899_u128),
+            deviation: I256::try_from(1858335035).unwrap(),
+        },
+        fee: I256::try_from(37574958490609978761756606622_u128).unwrap(),
+    };
+    assert_eq!(expected, quote);
+}
+
+#[test]
+fn check_quote_burn_exact_out() {
+    let mut multipool = fee_fixture();
+    multipool.management_fee = 1 << 12;
+
+    let quote = multipool
+        .quote_burn(&ADDRESSES[4], U256::from(3) << 96, SwapKind::ExactOut)
+        .unwrap()
+        .any_age();
+
+    assert_eq!(U256::from(3) << 96, quote.amount);
+    assert_eq!(
+        U256::from(909693756825784844429927458486_u128),
+        quote.shares
+    );
+    assert_eq!(
+        U256::from(56855859801611552776870466155_u128),
+        quote.management_shares
+    );
+}
+
+#[test]
+fn check_quote_time_is_oldest_price() {
+    let mut multipool = fee_fixture();
+    multipool.assets.iter_mut().for_each(|asset| {
+        asset.price = asset
+            .price
+            .clone()
+            .map(|p| MayBeExpired::with_time(p.any_age(), 90));
+    });
+    multipool.assets[3].price = Some(MayBeExpired::with_time(U256::from(11) << 96, 40));
+
+    let quote = multipool
+        .quote_mint(&ADDRESSES[1], U256::from(1) << 96, SwapKind::ExactIn)
+        .unwrap();
+    assert_eq!(40, quote.time());
+    assert!(quote.not_older_than::<MockTimeExtractor>(50).is_none());
+
+    assert_eq!(Ok(()), multipool.check_prices::<MockTimeExtractor>(60));
+    assert_eq!(
+        Err(MultipoolErrors::PriceExpired(ADDRESSES[3])),
+        multipool.check_prices::<MockTimeExtractor>(50)
+    );
+}
+
+#[test]
+fn check_quote_mint_burn_errors() {
+    let mut multipool = fee_fixture();
+
+    assert_eq!(
+        Err(MultipoolErrors::InsufficientQuantity(
+            multipool.contract_address
+        )),
+        multipool.quote_burn(&ADDRESSES[0], U256::from(1894) << 96, SwapKind::ExactIn)
+    );
+    assert_eq!(
+        Err(MultipoolErrors::InsufficientQuantity(ADDRESSES[2])),
+        multipool.quote_burn(&ADDRESSES[2], U256::from(5) << 96, SwapKind::ExactOut)
+    );
+
+    multipool.assets[2].price = None;
+    assert_eq!(
+        Err(MultipoolErrors::PriceMissing(ADDRESSES[2])),
+        multipool.quote_burn(&ADDRESSES[0], U256::from(1), SwapKind::ExactIn)
+    );
+    assert_eq!(
+        Err(MultipoolErrors::PriceMissing(ADDRESSES[2])),
+        multipool.quote_mint(&ADDRESSES[2], U256::from(1), SwapKind::ExactIn)
+    );
+}
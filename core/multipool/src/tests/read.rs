@@ -1,11 +1,11 @@
-use super::multipool_builder::{multipool_fixture, read_method_fixture};
+use super::multipool_builder::read_method_fixture;
 use super::*;
 use alloy::primitives::I256;
 use pretty_assertions::assert_eq;
 
 #[test]
 fn check_get_price() {
-    let contract_address = Address::from_slice(0x10_u64.to_be_bytes().as_ref());
+    let contract_address = Address::with_last_byte(0x10);
     let multipool = read_method_fixture(contract_address);
     let price = multipool.get_price(&ADDRESSES[2].clone());
     assert_eq!(
@@ -21,7 +21,7 @@ fn check_get_price() {
 
 #[test]
 fn check_deviation() {
-    let contract_address = Address::from_slice(0x10_u64.to_be_bytes().as_ref());
+    let contract_address = Address::with_last_byte(0x10);
     let multipool = read_method_fixture(contract_address);
     let deviation = multipool.deviation(&ADDRESSES[2].clone());
     assert_eq!(
@@ -32,7 +32,7 @@ fn check_deviation() {
 
 #[test]
 fn check_current_share() {
-    let contract_address = Address::from_slice(0x10_u64.to_be_bytes().as_ref());
+    let contract_address = Address::with_last_byte(0x10);
     let multipool = read_method_fixture(contract_address);
     let current_share = multipool.current_share(&ADDRESSES[4].clone());
     assert_eq!(
@@ -43,8 +43,8 @@ fn check_current_share() {
 
 #[test]
 fn check_target_share() {
-    let contract_address = Address::from_slice(0x10_u64.to_be_bytes().as_ref());
+    let contract_address = Address::with_last_byte(0x10);
     let multipool = read_method_fixture(contract_address);
     let target_share = multipool.target_share(&ADDRESSES[4].clone());
-    assert_eq!(target_share.unwrap().any_age(), U256::from(1717986918))
+    assert_eq!(target_share.unwrap(), U256::from(1717986918))
 }
@@ -1,19 +1,30 @@
-use super::multipool_builder::{multipool_fixture, MultipoolMockBuilder};
+use super::multipool_builder::{
+    asset_change, multipool_fixture, prices, target_share_change, MultipoolMockBuilder,
+};
 use super::*;
 use pretty_assertions::assert_eq;
 
 #[test]
 fn check_multipool_initialization() {
-    let contract_address = Address::from_slice(0x10_u64.to_be_bytes().as_ref());
-    let expected = multipool_fixture(contract_address, ADDRESSES.to_vec(), U256::from(10) << 96);
+    let contract_address = Address::with_last_byte(0x10);
+    let expected = multipool_fixture(
+        contract_address,
+        ADDRESSES
+            .iter()
+            .map(|address| (*address, U128::from(10) << 96, U256::from(10) << 96, 10))
+            .collect(),
+        U256::from(50) << 96,
+    );
     let multipool = MultipoolMockBuilder::new(contract_address)
-        .insert_assets(ADDRESSES.to_vec())
-        .fill_updated_prices_with_value(ADDRESSES.to_vec(), U256::from(10) << 96)
-        .fill_updated_shares_with_value(ADDRESSES.to_vec(), U256::from(10) << 96)
-        .fill_updated_quantities_with_value(
-            ADDRESSES.to_vec(),
-            U256::from(10) << 96,
-            Some(contract_address),
+        .with_events(&ADDRESSES.map(|address| asset_change(address, 10 << 96)))
+        .with_events(&ADDRESSES.map(|address| target_share_change(address, 10, 50)))
+        .with_events(&[asset_change(contract_address, 50 << 96)])
+        .with_prices(
+            ADDRESSES
+                .iter()
+                .map(|address| (*address, U256::from(10) << 96))
+                .collect(),
+            0,
         )
         .build();
     assert_eq!(expected, multipool)
@@ -24,35 +35,29 @@ fn check_multipool_initialization() {
 //UPDATE PRICE
 #[test]
 fn check_update_prices() {
-    let contract_address = Address::from_slice(0x10_u64.to_be_bytes().as_ref());
-    let mut multipool = Multipool::new(contract_address);
-
-    // for asset_initializing
-    let quantity = U256::default();
-    let inserted_data = [
-        (ADDRESSES[0], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[1], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[2], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[3], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[4], QuantityData::new(quantity, U256::default())),
-    ];
-    multipool.update_quantities(&inserted_data, false);
-
-    let inserted_data = [
-        (ADDRESSES[0], U256::from(10) << 96),
-        (ADDRESSES[1], U256::from(10) << 96),
-        (ADDRESSES[2], U256::from(10) << 96),
-        (ADDRESSES[3], U256::from(10) << 96),
-        (ADDRESSES[4], U256::from(10) << 96),
-    ];
-    multipool.update_prices(&inserted_data, false);
+    let contract_address = Address::with_last_byte(0x10);
+    let mut multipool = MultipoolMockBuilder::new(contract_address)
+        .insert_assets(ADDRESSES.to_vec())
+        .build();
+
+    let inserted_data = prices(
+        &[
+            (ADDRESSES[0], U256::from(10) << 96),
+            (ADDRESSES[1], U256::from(10) << 96),
+            (ADDRESSES[2], U256::from(10) << 96),
+            (ADDRESSES[3], U256::from(10) << 96),
+            (ADDRESSES[4], U256::from(10) << 96),
+        ],
+        1,
+    );
+    multipool.update_prices(&inserted_data);
 
     let expected = MultipoolMockBuilder::new(contract_address)
-        .set_price(ADDRESSES[0], U256::from(10) << 96)
-        .set_price(ADDRESSES[1], U256::from(10) << 96)
-        .set_price(ADDRESSES[2], U256::from(10) << 96)
-        .set_price(ADDRESSES[3], U256::from(10) << 96)
-        .set_price(ADDRESSES[4], U256::from(10) << 96)
+        .with_price(ADDRESSES[0], U256::from(10) << 96, 1)
+        .with_price(ADDRESSES[1], U256::from(10) << 96, 1)
+        .with_price(ADDRESSES[2], U256::from(10) << 96, 1)
+        .with_price(ADDRESSES[3], U256::from(10) << 96, 1)
+        .with_price(ADDRESSES[4], U256::from(10) << 96, 1)
         .build();
 
     assert_eq!(expected, multipool);
@@ -60,47 +65,32 @@ fn check_update_prices() {
 
 #[test]
 fn check_update_prices_with_not_existing_asset() {
-    let contract_address = Address::from_slice(0x10_u64.to_be_bytes().as_ref());
-    let mut multipool = Multipool::new(contract_address);
-
-    // for asset_initializing
-    let quantity = U256::default();
-    let inserted_data = [
-        (ADDRESSES[0], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[1], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[2], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[3], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[4], QuantityData::new(quantity, U256::default())),
-    ];
-    multipool.update_quantities(&inserted_data, false);
-
-    //insert base prices
-    let inserted_data = [
-        (ADDRESSES[0], U256::from(10) << 96),
-        (ADDRESSES[1], U256::from(10) << 96),
-        (ADDRESSES[2], U256::from(10) << 96),
-        (ADDRESSES[3], U256::from(10) << 96),
-        (ADDRESSES[4], U256::from(10) << 96),
-    ];
-    multipool.update_prices(&inserted_data, false);
-
-    //insert fake price
-    let inserted_data = [
-        (ADDRESSES[0], U256::from(10) << 96),
-        (ADDRESSES[1], U256::from(10) << 96),
-        (ADDRESSES[2], U256::from(10) << 96),
-        (ADDRESSES[3], U256::from(10) << 96),
-        (ADDRESSES[4], U256::from(10) << 96),
-        (Address::new([0u8; 20]), U256::from(10) << 96), // not exists
-    ];
-    multipool.update_prices(&inserted_data, false);
+    let contract_address = Address::with_last_byte(0x10);
+    let mut multipool = MultipoolMockBuilder::new(contract_address)
+        .insert_assets(ADDRESSES.to_vec())
+        .build();
+
+    //insert prices with fake asset, prices are sorted by address
+    let inserted_data = prices(
+        &[
+            (Address::ZERO, U256::from(10) << 96), // not exists
+            (ADDRESSES[0], U256::from(10) << 96),
+            (ADDRESSES[1], U256::from(10) << 96),
+            (ADDRESSES[2], U256::from(10) << 96),
+            (ADDRESSES[3], U256::from(10) << 96),
+            (ADDRESSES[4], U256::from(10) << 96),
+            (Address::with_last_byte(0x20), U256::from(10) << 96), // not exists
+        ],
+        1,
+    );
+    multipool.update_prices(&inserted_data);
 
     let expected = MultipoolMockBuilder::new(contract_address)
-        .set_price(ADDRESSES[0], U256::from(10) << 96)
-        .set_price(ADDRESSES[1], U256::from(10) << 96)
-        .set_price(ADDRESSES[2], U256::from(10) << 96)
-        .set_price(ADDRESSES[3], U256::from(10) << 96)
-        .set_price(ADDRESSES[4], U256::from(10) << 96)
+        .with_price(ADDRESSES[0], U256::from(10) << 96, 1)
+        .with_price(ADDRESSES[1], U256::from(10) << 96, 1)
+        .with_price(ADDRESSES[2], U256::from(10) << 96, 1)
+        .with_price(ADDRESSES[3], U256::from(10) << 96, 1)
+        .with_price(ADDRESSES[4], U256::from(10) << 96, 1)
         .build();
 
     assert_eq!(expected, multipool);
@@ -108,45 +98,41 @@ fn check_update_prices_with_not_existing_asset() {
 
 #[test]
 fn check_update_prices_with_custom_logic() {
-    let contract_address = Address::from_slice(0x10_u64.to_be_bytes().as_ref());
-    let mut multipool = Multipool::new(contract_address);
-
-    // for asset_initializing
-    let quantity = U256::default();
-    let inserted_data = [
-        (ADDRESSES[0], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[1], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[2], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[3], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[4], QuantityData::new(quantity, U256::default())),
-    ];
-    multipool.update_quantities(&inserted_data, false);
-
-    let inserted_data = [
-        (ADDRESSES[0], U256::from(10) << 96),
-        (ADDRESSES[1], U256::from(10) << 96),
-        (ADDRESSES[2], U256::from(10) << 96),
-        (ADDRESSES[3], U256::from(10) << 96),
-        (ADDRESSES[4], U256::from(10) << 96),
-    ];
-    multipool.update_prices(&inserted_data, false);
-
-    let inserted_data = [
-        (ADDRESSES[0], U256::default()),
-        //skipped second asset
-        (ADDRESSES[2], U256::from(15) << 96),
-        (ADDRESSES[3], U256::from(20) << 96),
-        (ADDRESSES[4], U256::from(25) << 96),
-        (Address::new([0u8; 20]), U256::from(10) << 96), // not exists
-    ];
-    multipool.update_prices(&inserted_data, false);
+    let contract_address = Address::with_last_byte(0x10);
+    let mut multipool = MultipoolMockBuilder::new(contract_address)
+        .insert_assets(ADDRESSES.to_vec())
+        .build();
+
+    let inserted_data = prices(
+        &[
+            (ADDRESSES[0], U256::from(10) << 96),
+            (ADDRESSES[1], U256::from(10) << 96),
+            (ADDRESSES[2], U256::from(10) << 96),
+            (ADDRESSES[3], U256::from(10) << 96),
+            (ADDRESSES[4], U256::from(10) << 96),
+        ],
+        1,
+    );
+    multipool.update_prices(&inserted_data);
+
+    let inserted_data = prices(
+        &[
+            (ADDRESSES[0], U256::default()),
+            //skipped second asset
+            (ADDRESSES[2], U256::from(15) << 96),
+            (ADDRESSES[3], U256::from(20) << 96),
+            (ADDRESSES[4], U256::from(25) << 96),
+        ],
+        2,
+    );
+    multipool.update_prices(&inserted_data);
 
     let expected = MultipoolMockBuilder::new(contract_address)
-        .set_price(ADDRESSES[0], U256::default())
-        .set_price(ADDRESSES[1], U256::from(10) << 96)
-        .set_price(ADDRESSES[2], U256::from(15) << 96)
-        .set_price(ADDRESSES[3], U256::from(20) << 96)
-        .set_price(ADDRESSES[4], U256::from(25) << 96)
+        .with_price(ADDRESSES[0], U256::default(), 2)
+        .with_price(ADDRESSES[1], U256::from(10) << 96, 1)
+        .with_price(ADDRESSES[2], U256::from(15) << 96, 2)
+        .with_price(ADDRESSES[3], U256::from(20) << 96, 2)
+        .with_price(ADDRESSES[4], U256::from(25) << 96, 2)
         .build();
 
     assert_eq!(expected, multipool);
@@ -155,150 +141,94 @@ fn check_update_prices_with_custom_logic() {
 //UPDATE SHARES
 #[test]
 fn check_update_shares() {
-    let contract_address = Address::from_slice(0x10_u64.to_be_bytes().as_ref());
-    let mut multipool = Multipool::new(contract_address);
-
-    // for asset_initializing, to set total_shares
-    let quantity = U256::default();
-    let inserted_data = [
-        (ADDRESSES[0], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[1], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[2], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[3], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[4], QuantityData::new(quantity, U256::default())),
-    ];
-    multipool.update_quantities(&inserted_data, false);
-
-    let inserted_data = [
-        (ADDRESSES[0], U256::from(10) << 96),
-        (ADDRESSES[1], U256::from(10) << 96),
-        (ADDRESSES[2], U256::from(10) << 96),
-        (ADDRESSES[3], U256::from(10) << 96),
-        (ADDRESSES[4], U256::from(10) << 96),
-    ];
-
-    multipool.update_shares(&inserted_data, false);
+    let contract_address = Address::with_last_byte(0x10);
+    let multipool = MultipoolMockBuilder::new(contract_address)
+        .with_events(&[
+            target_share_change(ADDRESSES[0], 10, 10),
+            target_share_change(ADDRESSES[1], 10, 20),
+            target_share_change(ADDRESSES[2], 10, 30),
+            target_share_change(ADDRESSES[3], 10, 40),
+            target_share_change(ADDRESSES[4], 10, 50),
+        ])
+        .build();
 
     let expected = MultipoolMockBuilder::new(contract_address)
-        .set_share(ADDRESSES[0], U256::from(10) << 96)
-        .set_share(ADDRESSES[1], U256::from(10) << 96)
-        .set_share(ADDRESSES[2], U256::from(10) << 96)
-        .set_share(ADDRESSES[3], U256::from(10) << 96)
-        .set_share(ADDRESSES[4], U256::from(10) << 96)
-        .set_total_shares(U256::from(50) << 96)
+        .with_share(ADDRESSES[0], 10)
+        .with_share(ADDRESSES[1], 10)
+        .with_share(ADDRESSES[2], 10)
+        .with_share(ADDRESSES[3], 10)
+        .with_share(ADDRESSES[4], 10)
         .build();
 
     assert_eq!(expected, multipool);
+    assert_eq!(50, multipool.total_target_shares);
 }
 
 #[test]
 fn check_update_shares_with_not_existing_asset() {
-    let contract_address = Address::from_slice(0x10_u64.to_be_bytes().as_ref());
-    let mut multipool = Multipool::new(contract_address);
-    let random_address = Address::new([0u8; 20]);
-
-    let inserted_data = [
-        (ADDRESSES[0], U256::from(10) << 96),
-        (ADDRESSES[1], U256::from(10) << 96),
-        (ADDRESSES[2], U256::from(10) << 96),
-        (ADDRESSES[3], U256::from(10) << 96),
-        (ADDRESSES[4], U256::from(10) << 96),
-    ];
-    multipool.update_shares(&inserted_data, false);
-
-    let inserted_data = [
-        (ADDRESSES[0], U256::from(20) << 96),
-        (ADDRESSES[1], U256::from(20) << 96),
-        (ADDRESSES[2], U256::from(20) << 96),
-        (ADDRESSES[3], U256::from(20) << 96),
-        (ADDRESSES[4], U256::from(20) << 96),
-        (random_address, U256::from(20) << 96), // not exists yet
-    ];
-
-    multipool.update_shares(&inserted_data, false);
+    let contract_address = Address::with_last_byte(0x10);
+    let random_address = Address::ZERO;
+    let multipool = MultipoolMockBuilder::new(contract_address)
+        .with_events(&ADDRESSES.map(|address| target_share_change(address, 10, 50)))
+        .with_events(&ADDRESSES.map(|address| target_share_change(address, 20, 100)))
+        // not exists yet
+        .with_events(&[target_share_change(random_address, 20, 120)])
+        .build();
 
     let expected = MultipoolMockBuilder::new(contract_address)
-        .set_share(ADDRESSES[0], U256::from(20) << 96)
-        .set_share(ADDRESSES[1], U256::from(20) << 96)
-        .set_share(ADDRESSES[2], U256::from(20) << 96)
-        .set_share(ADDRESSES[3], U256::from(20) << 96)
-        .set_share(ADDRESSES[4], U256::from(20) << 96)
-        .set_share(random_address, U256::from(20) << 96) // expect that value was created
-        .set_total_shares(U256::from(60) << 96)
+        .with_share(ADDRESSES[0], 20)
+        .with_share(ADDRESSES[1], 20)
+        .with_share(ADDRESSES[2], 20)
+        .with_share(ADDRESSES[3], 20)
+        .with_share(ADDRESSES[4], 20)
+        // expect that value was created
+        .with_share(random_address, 20)
         .build();
 
     assert_eq!(expected, multipool);
+    assert_eq!(120, multipool.total_target_shares);
 }
 
 #[test]
 fn check_update_shares_with_custom_logic() {
-    let random_address = Address::new([0u8; 20]);
-    let contract_address = Address::from_slice(0x10_u64.to_be_bytes().as_ref());
-    let mut multipool = Multipool::new(contract_address);
-
-    // for asset_initializing, to set total_shares
-    let quantity = U256::default();
-    let inserted_data = [
-        (ADDRESSES[0], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[1], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[2], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[3], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[4], QuantityData::new(quantity, U256::default())),
-    ];
-    multipool.update_quantities(&inserted_data, false);
-
-    let inserted_data = [
-        (ADDRESSES[0], U256::from(10) << 96),
-        (ADDRESSES[1], U256::from(10) << 96),
-        (ADDRESSES[2], U256::from(10) << 96),
-        (ADDRESSES[3], U256::from(10) << 96),
-        (ADDRESSES[4], U256::from(10) << 96),
-    ];
-
-    multipool.update_shares(&inserted_data, false);
-
-    let inserted_data = [
-        (ADDRESSES[0], U256::default()),
-        //skipped this asset ,
-        (ADDRESSES[2], U256::from(15) << 96),
-        (ADDRESSES[3], U256::from(20) << 96),
-        (ADDRESSES[4], U256::from(25) << 96),
-        (random_address, U256::from(20) << 96), // not exists
-    ];
-
-    multipool.update_shares(&inserted_data, false);
+    let contract_address = Address::with_last_byte(0x10);
+    let multipool = MultipoolMockBuilder::new(contract_address)
+        .with_events(&ADDRESSES.map(|address| target_share_change(address, 10, 50)))
+        .with_events(&[
+            target_share_change(ADDRESSES[0], 0, 40),
+            //skipped this asset
+            target_share_change(ADDRESSES[2], 15, 45),
+            target_share_change(ADDRESSES[3], 20, 55),
+            target_share_change(ADDRESSES[4], 25, 70),
+        ])
+        .build();
 
     let expected = MultipoolMockBuilder::new(contract_address)
-        .insert_assets(ADDRESSES[1..5].to_vec())
-        .set_share(ADDRESSES[1], U256::from(10) << 96)
-        .set_share(ADDRESSES[2], U256::from(15) << 96)
-        .set_share(ADDRESSES[3], U256::from(20) << 96)
-        .set_share(ADDRESSES[4], U256::from(25) << 96)
-        .set_share(random_address, U256::from(20) << 96)
-        .set_total_shares(U256::from(70) << 96)
+        .with_share(ADDRESSES[0], 0)
+        .with_share(ADDRESSES[1], 10)
+        .with_share(ADDRESSES[2], 15)
+        .with_share(ADDRESSES[3], 20)
+        .with_share(ADDRESSES[4], 25)
         .build();
 
     assert_eq!(expected, multipool);
+    assert_eq!(70, multipool.total_target_shares);
 }
 
 //UPDATE QUANTITY
 #[test]
 fn check_add_quantities() {
-    let contract_address = Address::from_slice(0x10_u64.to_be_bytes().as_ref());
-    let mut multipool = Multipool::new(contract_address);
-
-    let quantity = U256::from(10) << 96;
-    let inserted_data = [
-        (ADDRESSES[0], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[1], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[2], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[3], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[4], QuantityData::new(quantity, U256::default())),
-    ];
-    multipool.update_quantities(&inserted_data, false);
+    let contract_address = Address::with_last_byte(0x10);
+    let multipool = MultipoolMockBuilder::new(contract_address)
+        .with_events(&ADDRESSES.map(|address| asset_change(address, 10 << 96)))
+        .build();
 
     let expected = MultipoolMockBuilder::new(contract_address)
-        .fill_updated_quantities_with_value(ADDRESSES.to_vec(), U256::from(10) << 96, None)
+        .with_quantity(ADDRESSES[0], U128::from(10) << 96)
+        .with_quantity(ADDRESSES[1], U128::from(10) << 96)
+        .with_quantity(ADDRESSES[2], U128::from(10) << 96)
+        .with_quantity(ADDRESSES[3], U128::from(10) << 96)
+        .with_quantity(ADDRESSES[4], U128::from(10) << 96)
         .build();
 
     assert_eq!(expected, multipool);
@@ -306,29 +236,19 @@ fn check_add_quantities() {
 
 #[test]
 fn check_add_quantities_with_total_supply() {
-    let contract_address = Address::from_slice(0x10_u64.to_be_bytes().as_ref());
-    let mut multipool = MultipoolMockBuilder::new(contract_address).build();
-
-    let quantity = U256::from(10) << 96;
-    let inserted_data = [
-        (ADDRESSES[0], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[1], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[2], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[3], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[4], QuantityData::new(quantity, U256::default())),
-        (
-            contract_address,
-            QuantityData::new(U256::from(50) << 96, U256::default()),
-        ),
-    ];
-    multipool.update_quantities(&inserted_data, false);
+    let contract_address = Address::with_last_byte(0x10);
+    let multipool = MultipoolMockBuilder::new(contract_address)
+        .with_events(&ADDRESSES.map(|address| asset_change(address, 10 << 96)))
+        .with_events(&[asset_change(contract_address, 50 << 96)])
+        .build();
 
     let expected = MultipoolMockBuilder::new(contract_address)
-        .fill_updated_quantities_with_value(
-            ADDRESSES.to_vec(),
-            U256::from(10) << 96,
-            Some(contract_address),
-        )
+        .with_quantity(ADDRESSES[0], U128::from(10) << 96)
+        .with_quantity(ADDRESSES[1], U128::from(10) << 96)
+        .with_quantity(ADDRESSES[2], U128::from(10) << 96)
+        .with_quantity(ADDRESSES[3], U128::from(10) << 96)
+        .with_quantity(ADDRESSES[4], U128::from(10) << 96)
+        .with_quantity(contract_address, U128::from(50) << 96)
         .build();
 
     assert_eq!(expected, multipool);
@@ -336,34 +256,21 @@ fn check_add_quantities_with_total_supply() {
 
 #[test]
 fn check_delete_quantities() {
-    let contract_address = Address::from_slice(0x10_u64.to_be_bytes().as_ref());
-    let mut multipool = Multipool::new(contract_address);
-
-    let quantity = U256::from(10) << 96;
-    let inserted_data = [
-        (ADDRESSES[0], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[1], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[2], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[3], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[4], QuantityData::new(quantity, U256::default())),
-    ];
-    multipool.update_quantities(&inserted_data, false);
-
-    let quantity = U256::from(10) << 96;
-    let inserted_data = [
-        (ADDRESSES[0], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[1], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[2], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[3], QuantityData::new(quantity, U256::default())),
-        (
-            ADDRESSES[4],
-            QuantityData::new(U256::default(), U256::default()),
-        ),
-    ];
-    multipool.update_quantities(&inserted_data, false);
+    let contract_address = Address::with_last_byte(0x10);
+    let multipool = MultipoolMockBuilder::new(contract_address)
+        .with_events(&ADDRESSES.map(|address| target_share_change(address, 10, 50)))
+        .with_events(&ADDRESSES.map(|address| asset_change(address, 10 << 96)))
+        .with_events(&[asset_change(ADDRESSES[4], 0)])
+        .build();
 
+    // withdrawn asset is kept with zero quantity
     let expected = MultipoolMockBuilder::new(contract_address)
-        .fill_updated_quantities_with_value(ADDRESSES[0..4].to_vec(), U256::from(10) << 96, None)
+        .with_events(&ADDRESSES.map(|address| target_share_change(address, 10, 50)))
+        .with_quantity(ADDRESSES[0], U128::from(10) << 96)
+        .with_quantity(ADDRESSES[1], U128::from(10) << 96)
+        .with_quantity(ADDRESSES[2], U128::from(10) << 96)
+        .with_quantity(ADDRESSES[3], U128::from(10) << 96)
+        .with_quantity(ADDRESSES[4], U128::ZERO)
         .build();
 
     assert_eq!(expected, multipool);
@@ -371,60 +278,27 @@ fn check_delete_quantities() {
 
 #[test]
 fn check_delete_quantities_with_total_supply() {
-    let contract_address = Address::from_slice(0x10_u64.to_be_bytes().as_ref());
-    let mut multipool = Multipool::new(contract_address);
-
-    let quantity = U256::from(10) << 96;
-    let inserted_data = [
-        (ADDRESSES[0], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[1], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[2], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[3], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[4], QuantityData::new(quantity, U256::default())),
-        (
-            contract_address,
-            QuantityData::new(U256::from(50) << 96, U256::default()),
-        ),
-    ];
-    multipool.update_quantities(&inserted_data, false);
-
-    //if we pass multiple contract_address data, last would be applied
-    let total_supply = U256::from(40) << 96;
-    let quantity = U256::from(10) << 96;
-    let inserted_data = [
-        (ADDRESSES[0], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[1], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[2], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[3], QuantityData::new(quantity, U256::default())),
-        (
-            ADDRESSES[4],
-            QuantityData::new(U256::default(), U256::default()),
-        ),
-        (
-            ADDRESSES[4],
-            QuantityData::new(U256::default(), U256::default()),
-        ),
-        (
-            contract_address,
-            QuantityData::new(U256::from(70) << 96, U256::default()),
-        ),
-        (
-            contract_address,
-            QuantityData::new(U256::from(90) << 96, U256::default()),
-        ),
-        (
-            contract_address,
-            QuantityData::new(total_supply, U256::default()),
-        ),
-    ];
-    multipool.update_quantities(&inserted_data, false);
+    let contract_address = Address::with_last_byte(0x10);
+    let multipool = MultipoolMockBuilder::new(contract_address)
+        .with_events(&ADDRESSES.map(|address| asset_change(address, 10 << 96)))
+        .with_events(&[asset_change(contract_address, 50 << 96)])
+        //if we pass multiple events of the same asset, last would be applied
+        .with_events(&[
+            asset_change(ADDRESSES[4], 0),
+            asset_change(ADDRESSES[4], 0),
+            asset_change(contract_address, 70 << 96),
+            asset_change(contract_address, 90 << 96),
+            asset_change(contract_address, 40 << 96),
+        ])
+        .build();
 
     let expected = MultipoolMockBuilder::new(contract_address)
-        .set_quantity(ADDRESSES[0], quantity)
-        .set_quantity(ADDRESSES[1], quantity)
-        .set_quantity(ADDRESSES[2], quantity)
-        .set_quantity(ADDRESSES[3], quantity)
-        .set_quantity(contract_address, total_supply)
+        .with_quantity(ADDRESSES[0], U128::from(10) << 96)
+        .with_quantity(ADDRESSES[1], U128::from(10) << 96)
+        .with_quantity(ADDRESSES[2], U128::from(10) << 96)
+        .with_quantity(ADDRESSES[3], U128::from(10) << 96)
+        .with_quantity(ADDRESSES[4], U128::ZERO)
+        .with_quantity(contract_address, U128::from(40) << 96)
         .build();
 
     assert_eq!(multipool, expected);
@@ -432,27 +306,22 @@ fn check_delete_quantities_with_total_supply() {
 
 #[test]
 fn check_update_quantities_delete_with_zero_shares() {
-    let contract_address = Address::from_slice(0x10_u64.to_be_bytes().as_ref());
-    let mut multipool = MultipoolMockBuilder::new(contract_address).build();
-
-    let quantity = U256::from(10) << 96;
-    let inserted_data = [
-        (ADDRESSES[0], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[1], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[2], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[3], QuantityData::new(quantity, U256::default())),
-        (ADDRESSES[4], QuantityData::new(quantity, U256::default())),
-    ];
-
-    multipool.update_quantities(&inserted_data, true);
+    let contract_address = Address::with_last_byte(0x10);
+    let multipool = MultipoolMockBuilder::new(contract_address)
+        .with_events(&ADDRESSES.map(|address| target_share_change(address, 10, 50)))
+        .with_events(&ADDRESSES.map(|address| target_share_change(address, 0, 0)))
+        .with_events(&ADDRESSES.map(|address| asset_change(address, 10 << 96)))
+        .build();
 
+    // assets removed from targets still hold their quantity
     let expected = MultipoolMockBuilder::new(contract_address)
-        .set_quantity(ADDRESSES[0], quantity)
-        .set_quantity(ADDRESSES[1], quantity)
-        .set_quantity(ADDRESSES[2], quantity)
-        .set_quantity(ADDRESSES[3], quantity)
-        .set_quantity(ADDRESSES[4], quantity)
+        .with_quantity(ADDRESSES[0], U128::from(10) << 96)
+        .with_quantity(ADDRESSES[1], U128::from(10) << 96)
+        .with_quantity(ADDRESSES[2], U128::from(10) << 96)
+        .with_quantity(ADDRESSES[3], U128::from(10) << 96)
+        .with_quantity(ADDRESSES[4], U128::from(10) << 96)
         .build();
 
     assert_eq!(expected, multipool);
+    assert_eq!(0, multipool.total_target_shares);
 }
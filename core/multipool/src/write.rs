@@ -126,6 +126,22 @@ impl Multipool {
                     }
                 }
             }
+            MultipoolEvents::Swap(e) => {
+                // share side of mint and burn is tracked by ShareTransfer in storage
+                if self.contract_address == e.asset {
+                    return;
+                }
+                let idx = match self.assets.iter().position(|a| a.address.eq(&e.asset)) {
+                    Some(idx) => idx,
+                    None => {
+                        self.assets.push(MultipoolAsset::new(e.asset));
+                        self.assets.len() - 1
+                    }
+                };
+                let asset = &mut self.assets[idx];
+                asset.swap_volume = asset.swap_volume.saturating_add(e.quantity.unsigned_abs());
+                asset.collected_fees = asset.collected_fees.saturating_add(e.fee);
+            }
             _ => (),
         });
     }
@@ -1,13 +1,14 @@
 use crate::hook::HookInitializer;
 use crate::storage::MultipoolStorage;
+use crate::store::MultipoolStore;
 use anyhow::{anyhow, Context};
 use futures::StreamExt;
 use multipool_types::messages::{self, KafkaTopics, MsgPack, PriceData};
 use rdkafka::consumer::{CommitMode, Consumer, StreamConsumer};
 use rdkafka::Message;
 
-pub async fn into_fetching_task<HI: HookInitializer>(
-    storage: &mut MultipoolStorage<HI>,
+pub async fn into_fetching_task<HI: HookInitializer, S: MultipoolStore>(
+    storage: &mut MultipoolStorage<HI, S>,
     consumer: StreamConsumer,
 ) -> anyhow::Result<()> {
     loop {
@@ -21,10 +22,6 @@ pub async fn into_fetching_task<HI: HookInitializer>(
                         .context(anyhow!("Received message with no payload"))?;
                     let blocks = vec![messages::Block::unpack(bytes)];
 
-                    storage
-                        .create_multipools(&chain_id, blocks.as_slice().try_into()?)
-                        .await?;
-
                     storage
                         .apply_events(&chain_id, blocks.as_slice().try_into()?)
                         .await?;
@@ -3,3 +3,4 @@ pub mod kafka;
 pub mod pg;
 pub mod price_fetch;
 pub mod storage;
+pub mod store;
@@ -6,6 +6,7 @@ use sqlx::PgPool;
 
 use crate::hook::HookInitializer;
 use crate::storage::MultipoolStorage;
+use crate::store::MultipoolStore;
 use multipool_types::messages::Block;
 
 #[derive(FromRow)]
@@ -13,15 +14,19 @@ pub struct BlocksData {
     payload: Value,
 }
 
-pub async fn into_fetching_task<HI: HookInitializer>(
-    storage: &mut MultipoolStorage<HI>,
+pub async fn into_fetching_task<HI: HookInitializer, S: MultipoolStore>(
+    storage: &mut MultipoolStorage<HI, S>,
     pool: PgPool,
     interval: Duration,
     chain_ids: Vec<u64>,
 ) -> anyhow::Result<()> {
     loop {
         for chain_id in chain_ids.iter() {
-            let last_seen_block = storage.get_last_seen_block(chain_id)?.unwrap_or(0);
+            // re-read journaled blocks so storage can detect reorgs
+            let last_seen_block = storage
+                .get_last_seen_block(chain_id)?
+                .map(|b| b.saturating_sub(storage.journal_depth()))
+                .unwrap_or(0);
             let blocks: Vec<Block> = sqlx::query_as(
                 "
             SELECT 
@@ -40,9 +45,9 @@ pub async fn into_fetching_task<HI: HookInitializer>(
             .into_iter()
             .map(|v: BlocksData| from_value(v.payload).unwrap())
             .collect();
-            storage
-                .create_multipools(chain_id, blocks.as_slice().try_into()?)
-                .await?;
+            if blocks.is_empty() {
+                continue;
+            }
             storage
                 .apply_events(chain_id, blocks.as_slice().try_into()?)
                 .await?;
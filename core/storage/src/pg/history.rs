@@ -0,0 +1,169 @@
+use alloy::{primitives::Address, sol_types::SolEventInterface};
+use anyhow::Result;
+use multipool::Multipool;
+use multipool_types::{messages::Block, Multipool::MultipoolEvents};
+use serde_json::{from_value, Value};
+use sqlx::PgPool;
+
+use crate::store::encoding::{decode, encode};
+
+/// Checkpoints are saved at block heights that are multiples of this interval
+pub const CHECKPOINT_INTERVAL: u64 = 10_000;
+/// Number of block payloads fetched at once while replaying
+pub const REPLAY_PAGE_SIZE: i64 = 500;
+
+/// Applies events of `address` from `blocks` to the multipool, returns number of
+/// blocks that contained any of its events
+pub fn replay(multipool: &mut Multipool, blocks: &[Block]) -> usize {
+    let address = multipool.contract_address();
+    let mut touched = 0;
+    for block in blocks {
+        let events: Vec<MultipoolEvents> = block
+            .transactions
+            .iter()
+            .flat_map(|transaction| transaction.events.iter())
+            .filter(|event| event.log.address == address)
+            .filter_map(|event| MultipoolEvents::decode_log(&event.log).ok())
+            .map(|parsed_log| parsed_log.data)
+            .collect();
+        if !events.is_empty() {
+            multipool.apply_events(events.as_slice());
+            touched += 1;
+        }
+    }
+    touched
+}
+
+/// Highest checkpoint height at or below `block_number`
+pub fn checkpoint_height(block_number: u64) -> u64 {
+    block_number / CHECKPOINT_INTERVAL * CHECKPOINT_INTERVAL
+}
+
+async fn save_checkpoint(
+    pool: &PgPool,
+    chain_id: u64,
+    multipool: &Multipool,
+    block_number: u64,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO multipool_checkpoints(chain_id, multipool, block_number, data)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (chain_id, multipool, block_number) DO NOTHING",
+    )
+    .bind::<i64>(chain_id.try_into()?)
+    .bind::<[u8; 20]>(multipool.contract_address().into())
+    .bind::<i64>(block_number.try_into()?)
+    .bind(encode(multipool))
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Reconstructs multipool state right after `block_number` from the latest
+/// checkpoint and payloads of `blocks` table, which are replayed page by page.
+/// Returns `None` if multipool had no events up to this block.
+///
+/// Checkpoints are saved at every `CHECKPOINT_INTERVAL` height that is passed
+/// by the replay and is not above the last indexed block, blocks are indexed
+/// only once finalized. Rewritten blocks drop checkpoints above them, see
+/// `invalidate_checkpoints` in `create_tables.sql`.
+pub async fn multipool_at(
+    pool: &PgPool,
+    chain_id: u64,
+    address: Address,
+    block_number: u64,
+) -> Result<Option<Multipool>> {
+    let checkpoint: Option<(i64, Vec<u8>)> = sqlx::query_as(
+        "
+        SELECT
+            block_number, data
+        FROM
+            multipool_checkpoints
+        WHERE
+            chain_id = $1
+            and multipool = $2
+            and block_number <= $3
+        ORDER BY block_number DESC
+        LIMIT 1;",
+    )
+    .bind::<i64>(chain_id.try_into()?)
+    .bind::<[u8; 20]>(address.into())
+    .bind::<i64>(block_number.try_into()?)
+    .fetch_optional(pool)
+    .await?;
+
+    // checkpoints are a cache, ones written with other layout are replayed again
+    let checkpoint = checkpoint.and_then(|(block_number, data)| {
+        decode::<Multipool>(&data)
+            .ok()
+            .map(|multipool| (block_number, multipool))
+    });
+    let (mut cursor, mut multipool, mut created) = match checkpoint {
+        Some((checkpoint_block, multipool)) => (checkpoint_block, multipool, true),
+        None => (-1, Multipool::new(address, chain_id), false),
+    };
+
+    let indexed_block: Option<(i64,)> =
+        sqlx::query_as("SELECT block_number FROM indexed_blocks WHERE chain_id = $1")
+            .bind::<i64>(chain_id.try_into()?)
+            .fetch_optional(pool)
+            .await?;
+    // every block up to this height is final and present in `blocks` table
+    let checkpoint_limit = indexed_block
+        .and_then(|(indexed_block,)| u64::try_from(indexed_block).ok())
+        .map(|indexed_block| indexed_block.min(block_number));
+    let mut next_checkpoint = u64::try_from(cursor).map_or(CHECKPOINT_INTERVAL, |block| {
+        checkpoint_height(block) + CHECKPOINT_INTERVAL
+    });
+
+    loop {
+        let page: Vec<(i64, Value)> = sqlx::query_as(
+            "
+            SELECT
+                block_number, payload
+            FROM
+                blocks
+            WHERE
+                chain_id = $1
+                and block_number > $2
+                and block_number <= $3
+            ORDER BY block_number ASC
+            LIMIT $4;",
+        )
+        .bind::<i64>(chain_id.try_into()?)
+        .bind::<i64>(cursor)
+        .bind::<i64>(block_number.try_into()?)
+        .bind(REPLAY_PAGE_SIZE)
+        .fetch_all(pool)
+        .await?;
+        let exhausted = (page.len() as i64) < REPLAY_PAGE_SIZE;
+
+        for (number, payload) in page {
+            let block: Block = from_value(payload)?;
+            // state before this block is the state at every height below it
+            if let Some(limit) = checkpoint_limit {
+                let height = checkpoint_height(limit.min(block.number.saturating_sub(1)));
+                if created && height >= next_checkpoint {
+                    save_checkpoint(pool, chain_id, &multipool, height).await?;
+                    next_checkpoint = height + CHECKPOINT_INTERVAL;
+                }
+            }
+            created |= replay(&mut multipool, std::slice::from_ref(&block)) > 0;
+            cursor = number;
+        }
+        if exhausted {
+            break;
+        }
+    }
+
+    if !created {
+        return Ok(None);
+    }
+    if let Some(limit) = checkpoint_limit {
+        let height = checkpoint_height(limit);
+        if height >= next_checkpoint {
+            save_checkpoint(pool, chain_id, &multipool, height).await?;
+        }
+    }
+    Ok(Some(multipool))
+}
@@ -1,4 +1,5 @@
 pub mod event_fetching;
+pub mod history;
 pub mod price_push;
 
 pub use event_fetching::into_fetching_task;
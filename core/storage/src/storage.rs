@@ -11,13 +11,16 @@ use multipool::Multipool;
 use multipool_types::{
     expiry::{EmptyTimeExtractor, MayBeExpired},
     messages::Block,
-    Multipool::MultipoolEvents,
+    Multipool::{MultipoolEvents, ShareTransfer},
     MultipoolFactory::MultipoolFactoryEvents,
 };
-use sled::{transaction::ConflictableTransactionResult, Transactional};
-use tokio::task::JoinHandle;
+use tokio::{sync::Mutex, task::JoinHandle};
 
 use crate::hook::HookInitializer;
+use crate::store::{
+    encoding::{decode, encode},
+    MultipoolStore, SledStore, StoreBatch,
+};
 
 pub struct EmptyHookInitialiser;
 
@@ -30,12 +33,50 @@ impl HookInitializer for EmptyHookInitialiser {
     }
 }
 
-pub struct MultipoolStorage<HI: HookInitializer> {
-    multipools: sled::Tree,
-    index_data: sled::Tree,
+/// Number of latest blocks that can be rolled back, older blocks are considered final
+pub const DEFAULT_JOURNAL_DEPTH: u64 = 64;
+
+pub struct MultipoolStorage<HI: HookInitializer, S: MultipoolStore = SledStore> {
+    store: S,
+    journal_depth: u64,
     factory_address: Address,
-    hooks: Vec<JoinHandle<Result<()>>>,
-    hook_initializer: HI,
+    // serializes read-modify-write cycles over the store, hooks are started and
+    // stopped in the same cycle that creates or removes their multipool
+    hooks: Mutex<Hooks<HI>>,
+}
+
+struct Hooks<HI: HookInitializer> {
+    initializer: HI,
+    handles: BTreeMap<Address, Vec<JoinHandle<Result<()>>>>,
+}
+
+impl<HI: HookInitializer> Hooks<HI> {
+    async fn start<S: MultipoolStore>(&mut self, store: &S, address: Address) -> Result<()> {
+        // stored record is checked once here, it's only replaced by valid records later
+        store
+            .get_multipool(&address)?
+            .ok_or(anyhow!("Multipool {address} not found"))?;
+        let store = store.clone();
+        let getter = move || {
+            store
+                .get_multipool(&address)
+                .ok()
+                .flatten()
+                .expect("hooks are stopped before their multipool is removed")
+        };
+        let handles = self.initializer.initialize_hook(getter).await;
+        self.handles.insert(address, handles);
+        Ok(())
+    }
+
+    /// Aborts hooks of the multipool and waits until they are stopped, so none of
+    /// them reads the multipool after it is removed
+    async fn stop(&mut self, address: &Address) {
+        for handle in self.handles.remove(address).unwrap_or_default() {
+            handle.abort();
+            let _ = handle.await;
+        }
+    }
 }
 
 pub fn parse_log(log: Log) -> Option<MultipoolEvents> {
@@ -49,9 +90,28 @@ pub fn parse_log(log: Log) -> Option<MultipoolEvents> {
 pub struct MultipoolsUpdates {
     from_block_number: u64,
     to_block_number: u64,
+    pub blocks: Vec<BlockUpdates>,
+}
+
+pub struct BlockUpdates {
+    number: u64,
+    hash: [u8; 32],
+    pub creations: Vec<MultipoolCreation>,
     pub updates: Vec<MultipoolUpdates>,
 }
 
+/// Undo record of a single applied block
+#[derive(BorshSerialize, BorshDeserialize)]
+pub(crate) struct JournalEntry {
+    pub hash: [u8; 32],
+    pub previous_block: Option<u64>,
+    // encoded multipools as they were before the block was applied,
+    // `None` marks multipools created by the block
+    pub snapshots: Vec<([u8; 20], Option<Vec<u8>>)>,
+    // share balances as (multipool, holder, balance) before the block was applied
+    pub balances: Vec<([u8; 20], [u8; 20], [u8; 32])>,
+}
+
 pub struct MultipoolUpdates {
     address: Address,
     pub logs: Vec<MultipoolEvents>,
@@ -105,61 +165,72 @@ impl TryFrom<&[Block]> for MultipoolsUpdates {
             .map(|v| v.number)
             .ok_or(anyhow!("Empty batch"))?;
 
-        let mut updates = BTreeMap::<Address, Vec<MultipoolEvents>>::new();
+        let mut blocks = Vec::new();
 
         for block in value {
+            let mut updates = BTreeMap::<Address, Vec<MultipoolEvents>>::new();
             for transaction in block.transactions.iter() {
                 for event in transaction.events.iter() {
-                    let entry = updates.entry(event.log.address).or_default();
                     // filter out MultipoolCreation event and other decoding
                     if let Ok(parsed_log) = MultipoolEvents::decode_log(&event.log) {
-                        entry.push(parsed_log.data);
+                        updates
+                            .entry(event.log.address)
+                            .or_default()
+                            .push(parsed_log.data);
                     }
                 }
             }
+            blocks.push(BlockUpdates {
+                number: block.number,
+                hash: block.hash,
+                creations: MultipoolsCreation::try_from(std::slice::from_ref(block))?.0,
+                updates: updates
+                    .into_iter()
+                    .map(|(address, logs)| MultipoolUpdates { address, logs })
+                    .collect(),
+            });
         }
         Ok(MultipoolsUpdates {
             from_block_number,
             to_block_number,
-            updates: updates
-                .into_iter()
-                .map(|(address, logs)| MultipoolUpdates { address, logs })
-                .collect(),
+            blocks,
         })
     }
 }
 
-impl<HI: HookInitializer> MultipoolStorage<HI> {
+impl<HI: HookInitializer, S: MultipoolStore> MultipoolStorage<HI, S> {
     //TODO: store factory address in db
-    pub async fn init(
-        db: sled::Db,
-        mut hook_initializer: HI,
-        factory_address: Address,
-    ) -> Result<Self> {
-        let multipools = db.open_tree(b"multipools")?;
-        let index_data = db.open_tree(b"index_data")?;
-        let mut hooks = Vec::new();
-
-        for val in multipools.into_iter() {
-            let (address, _) = val?;
-            let tree = multipools.clone();
-            let getter = move || {
-                let mp = tree.get(&address).unwrap().unwrap();
-                Multipool::deserialize(&mut &mp[..]).unwrap()
-            };
-            let handles = hook_initializer.initialize_hook(getter).await;
-            hooks.extend(handles);
+    pub async fn init(store: S, hook_initializer: HI, factory_address: Address) -> Result<Self> {
+        let mut hooks = Hooks {
+            initializer: hook_initializer,
+            handles: BTreeMap::new(),
+        };
+
+        for address in store.multipool_addresses()? {
+            hooks.start(&store, address).await?;
         }
 
         Ok(Self {
-            multipools,
-            index_data,
-            hooks,
-            hook_initializer,
+            store,
+            journal_depth: DEFAULT_JOURNAL_DEPTH,
             factory_address,
+            hooks: Mutex::new(hooks),
         })
     }
 
+    pub fn with_journal_depth(mut self, journal_depth: u64) -> Self {
+        self.journal_depth = journal_depth;
+        self
+    }
+
+    pub fn journal_depth(&self) -> u64 {
+        self.journal_depth
+    }
+
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
     pub fn derive_multipool_address(
         factory_address: Address,
         factory_salt_nonce: U128,
@@ -174,114 +245,304 @@ impl<HI: HookInitializer> MultipoolStorage<HI> {
     }
 
     pub fn get_last_seen_block(&self, chain_id: &u64) -> anyhow::Result<Option<u64>> {
-        Ok(self
-            .index_data
-            .get(format!("current_block_{}", chain_id).as_bytes())?
-            .map(|value| u64::deserialize(&mut &value[..]).map(Into::into))
-            .transpose()?)
+        self.store.get_last_seen_block(*chain_id)
     }
 
-    pub async fn create_multipools(
-        &mut self,
+    pub fn get_multipool(&self, address: &Address) -> anyhow::Result<Option<Multipool>> {
+        self.store.get_multipool(address)
+    }
+
+    pub fn balance_of(&self, multipool: &Address, holder: &Address) -> anyhow::Result<U256> {
+        self.store.balance_of(multipool, holder)
+    }
+
+    fn journal_entries(
+        &self,
         chain_id: &u64,
-        creations: MultipoolsCreation,
-    ) -> anyhow::Result<()> {
-        for creation in creations.0 {
-            if creation.address != self.factory_address {
-                continue;
+        from_block: u64,
+    ) -> anyhow::Result<Vec<(u64, JournalEntry)>> {
+        self.store
+            .journal(*chain_id, from_block)?
+            .into_iter()
+            .map(|(block_number, record)| -> anyhow::Result<_> {
+                Ok((block_number, decode(&record)?))
+            })
+            .collect()
+    }
+
+    /// Reverts all blocks starting from `block_number` using the journal, returns new last seen block
+    pub async fn rollback(&self, chain_id: &u64, block_number: u64) -> anyhow::Result<Option<u64>> {
+        let mut hooks = self.hooks.lock().await;
+        self.rollback_locked(&mut hooks, chain_id, block_number)
+            .await
+    }
+
+    async fn rollback_locked(
+        &self,
+        hooks: &mut Hooks<HI>,
+        chain_id: &u64,
+        block_number: u64,
+    ) -> anyhow::Result<Option<u64>> {
+        let current_block = self.get_last_seen_block(chain_id)?;
+        let final_block = current_block.and_then(|b| b.checked_sub(self.journal_depth));
+        if final_block.is_some_and(|b| b >= block_number) {
+            return Err(anyhow!("Reorg is deeper than journal"));
+        }
+        let entries = self.journal_entries(chain_id, block_number)?;
+        let Some((_, first_entry)) = entries.first() else {
+            return Ok(current_block);
+        };
+        let previous_block = first_entry.previous_block;
+
+        // earliest snapshot of every multipool and balance wins
+        let mut snapshots = BTreeMap::new();
+        let mut balances = BTreeMap::new();
+        for (_, entry) in entries.iter().rev() {
+            for (address, snapshot) in entry.snapshots.iter() {
+                snapshots.insert(Address::from(*address), snapshot);
+            }
+            for (multipool, holder, balance) in entry.balances.iter() {
+                balances.insert(
+                    (Address::from(*multipool), Address::from(*holder)),
+                    U256::from_le_bytes(*balance),
+                );
             }
-            let multipool = Multipool::new(creation.multipool_address, *chain_id);
-
-            let mut w = Vec::new();
-            Multipool::serialize(&multipool, &mut w).unwrap();
-            self.multipools
-                .insert(creation.multipool_address.as_slice(), w)?;
-
-            let tree = self.multipools.clone();
-            let multipool_getter = move || {
-                let mp = tree
-                    .get(creation.multipool_address.as_slice())
-                    .unwrap()
-                    .unwrap();
-                Multipool::deserialize(&mut &mp[..]).unwrap()
-            };
-            let mut handles = self
-                .hook_initializer
-                .initialize_hook(multipool_getter)
-                .await;
-            self.hooks.append(&mut handles);
         }
-        self.multipools.flush()?;
-        Ok(())
+        let mut multipools = Vec::new();
+        let mut multipool_removals = Vec::new();
+        for (address, snapshot) in snapshots {
+            match snapshot {
+                Some(snapshot) => multipools.push(decode(snapshot)?),
+                None => {
+                    hooks.stop(&address).await;
+                    multipool_removals.push(address);
+                }
+            }
+        }
+        self.store
+            .commit(StoreBatch {
+                multipools,
+                multipool_removals,
+                balances: balances
+                    .into_iter()
+                    .map(|((multipool, holder), balance)| (multipool, holder, balance))
+                    .collect(),
+                last_seen_blocks: vec![(*chain_id, previous_block)],
+                journal_removals: entries
+                    .iter()
+                    .map(|(number, _)| (*chain_id, *number))
+                    .collect(),
+                ..Default::default()
+            })
+            .await?;
+        Ok(previous_block)
     }
 
+    /// Finds first block of the batch that differs from already applied blocks
+    fn find_fork(
+        &self,
+        chain_id: &u64,
+        updates: &MultipoolsUpdates,
+    ) -> anyhow::Result<Option<u64>> {
+        let Some(current_block) = self.get_last_seen_block(chain_id)? else {
+            return Ok(None);
+        };
+        // blocks below journal depth are final and never compared
+        let first_block = updates.from_block_number.max(
+            current_block
+                .checked_sub(self.journal_depth)
+                .map_or(0, |b| b + 1),
+        );
+        let mut journaled = self
+            .journal_entries(chain_id, first_block)?
+            .into_iter()
+            .take_while(|(number, _)| *number <= updates.to_block_number)
+            .peekable();
+        let mut blocks = updates
+            .blocks
+            .iter()
+            .filter(|block| block.number >= first_block)
+            .peekable();
+
+        loop {
+            match (journaled.peek(), blocks.peek()) {
+                (Some((number, entry)), Some(block))
+                    if *number == block.number && entry.hash == block.hash =>
+                {
+                    journaled.next();
+                    blocks.next();
+                }
+                (Some((number, _)), Some(block)) => return Ok(Some(block.number.min(*number))),
+                (Some((number, _)), None) => return Ok(Some(*number)),
+                (None, Some(block)) if block.number <= current_block => {
+                    return Ok(Some(block.number))
+                }
+                _ => return Ok(None),
+            }
+        }
+    }
+
+    /// Applies blocks on top of stored state, multipools created by the factory are
+    /// created in the same commit. Blocks that were already applied are skipped,
+    /// if hash of any of them differs state is rolled back to the fork
     pub async fn apply_events(
         &self,
         chain_id: &u64,
         updates: MultipoolsUpdates,
     ) -> anyhow::Result<()> {
-        let current_block = self
-            .index_data
-            .get(format!("current_block_{}", chain_id).as_bytes())?
-            .map(|value| u64::deserialize(&mut &value[..]).unwrap());
-
-        if current_block.is_some() && current_block.unwrap() >= updates.from_block_number {
-            return Err(anyhow!("Data invalid"));
+        let mut hooks = self.hooks.lock().await;
+        if let Some(fork_block) = self.find_fork(chain_id, &updates)? {
+            self.rollback_locked(&mut hooks, chain_id, fork_block)
+                .await?;
         }
+        let last_seen_block = self.get_last_seen_block(chain_id)?;
+        let blocks: Vec<&BlockUpdates> = updates
+            .blocks
+            .iter()
+            .filter(|block| last_seen_block.map_or(true, |last| block.number > last))
+            .collect();
+        let Some(new_last_seen_block) = blocks.last().map(|block| block.number) else {
+            return Ok(());
+        };
+        let final_block = new_last_seen_block.checked_sub(self.journal_depth);
 
-        (&self.multipools, &self.index_data)
-            .transaction(
-                |(multipools, index_data)| -> ConflictableTransactionResult<(), anyhow::Error> {
-                    for MultipoolUpdates { address, logs } in &updates.updates {
-                        if let Some(mp) = multipools.get(address)?
-                        // .ok_or(anyhow!("Multipool not found")) // should retrun error
-                        {
-                            let mut mp = Multipool::deserialize(&mut &mp[..]).unwrap();
-
-                            mp.apply_events(logs.as_slice());
-                            let mut w = Vec::new();
-                            Multipool::serialize(&mp, &mut w).unwrap();
-                            multipools.insert(address.as_slice(), w)?;
+        let mut multipools = BTreeMap::<Address, Multipool>::new();
+        let mut balances = BTreeMap::<(Address, Address), U256>::new();
+        let mut created = Vec::new();
+        let mut journal_inserts = Vec::new();
+        let mut previous_block = last_seen_block;
+        for block in blocks {
+            let mut snapshots = Vec::new();
+            let mut balance_snapshots = BTreeMap::new();
+            for creation in &block.creations {
+                let address = creation.multipool_address;
+                if creation.address != self.factory_address
+                    || multipools.contains_key(&address)
+                    || self.store.get_multipool(&address)?.is_some()
+                {
+                    continue;
+                }
+                snapshots.push((address.0 .0, None));
+                multipools.insert(address, Multipool::new(address, *chain_id));
+                created.push(address);
+            }
+            for MultipoolUpdates { address, logs } in &block.updates {
+                let mp = match multipools.remove(address) {
+                    Some(mp) => Some(mp),
+                    None => self.store.get_multipool(address)?,
+                };
+                // .ok_or(anyhow!("Multipool not found")) // should retrun error
+                if let Some(mut mp) = mp {
+                    snapshots.push((address.0 .0, Some(encode(&mp))));
+
+                    mp.apply_events(logs.as_slice());
+                    multipools.insert(*address, mp);
+                    for log in logs {
+                        if let MultipoolEvents::ShareTransfer(transfer) = log {
+                            self.transfer_shares(
+                                &mut balances,
+                                &mut balance_snapshots,
+                                *address,
+                                transfer,
+                            )?;
                         }
                     }
-                    let mut new_current_block = Vec::new();
-                    updates
-                        .to_block_number
-                        .serialize(&mut new_current_block)
-                        .unwrap();
-                    index_data.insert(
-                        format!("current_block_{}", chain_id).as_bytes(),
-                        new_current_block,
-                    )?;
-                    Ok(())
-                },
-            )
-            .unwrap();
-        self.index_data.flush()?;
-        self.multipools.flush()?;
+                }
+            }
+            if final_block.map_or(true, |final_block| block.number > final_block) {
+                let entry = JournalEntry {
+                    hash: block.hash,
+                    previous_block,
+                    snapshots,
+                    balances: balance_snapshots
+                        .into_iter()
+                        .map(|((multipool, holder), balance)| {
+                            (multipool.0 .0, holder.0 .0, balance.to_le_bytes())
+                        })
+                        .collect(),
+                };
+                journal_inserts.push((*chain_id, block.number, encode(&entry)));
+            }
+            previous_block = Some(block.number);
+        }
+        let journal_removals = match final_block {
+            Some(final_block) => self
+                .store
+                .journal(*chain_id, 0)?
+                .into_iter()
+                .take_while(|(number, _)| *number <= final_block)
+                .map(|(number, _)| (*chain_id, number))
+                .collect(),
+            None => vec![],
+        };
+
+        self.store
+            .commit(StoreBatch {
+                multipools: multipools.into_values().collect(),
+                balances: balances
+                    .into_iter()
+                    .map(|((multipool, holder), balance)| (multipool, holder, balance))
+                    .collect(),
+                last_seen_blocks: vec![(*chain_id, Some(new_last_seen_block))],
+                journal_inserts,
+                journal_removals,
+                ..Default::default()
+            })
+            .await?;
+
+        for address in created {
+            hooks.start(&self.store, address).await?;
+        }
+        Ok(())
+    }
+
+    /// Moves shares between holders in `balances`, balances of holders touched for
+    /// the first time in the block are saved to `snapshots`
+    fn transfer_shares(
+        &self,
+        balances: &mut BTreeMap<(Address, Address), U256>,
+        snapshots: &mut BTreeMap<(Address, Address), U256>,
+        multipool: Address,
+        transfer: &ShareTransfer,
+    ) -> Result<()> {
+        for (holder, received) in [(transfer.from, false), (transfer.to, true)] {
+            // zero address is the other side of mints and burns
+            if holder.is_zero() {
+                continue;
+            }
+            let key = (multipool, holder);
+            let balance = match balances.get(&key) {
+                Some(balance) => *balance,
+                None => self.store.balance_of(&multipool, &holder)?,
+            };
+            snapshots.entry(key).or_insert(balance);
+            let balance = if received {
+                balance.saturating_add(transfer.amount)
+            } else {
+                balance.saturating_sub(transfer.amount)
+            };
+            balances.insert(key, balance);
+        }
         Ok(())
     }
 
+    /// Merges prices into the latest state of the multipool. Holds the same lock as
+    /// `apply_events` so prices and events never overwrite each other
     pub async fn apply_prices(
         &self,
         address: Address,
         prices: Vec<(Address, MayBeExpired<U256, EmptyTimeExtractor>)>,
     ) -> Result<()> {
-        self.multipools
-            .transaction(
-                |multipools| -> ConflictableTransactionResult<(), anyhow::Error> {
-                    if let Some(mp) = multipools.get(address)? {
-                        let mut mp = Multipool::deserialize(&mut &mp[..]).unwrap();
-                        mp.update_prices(&prices);
-                        let mut w = Vec::new();
-                        Multipool::serialize(&mp, &mut w).unwrap();
-                        multipools.insert(address.as_slice(), w)?;
-                    }
-                    Ok(())
-                },
-            )
-            .unwrap();
-        self.multipools.flush()?;
+        let _hooks = self.hooks.lock().await;
+        if let Some(mut mp) = self.store.get_multipool(&address)? {
+            mp.update_prices(&prices);
+            self.store
+                .commit(StoreBatch {
+                    multipools: vec![mp],
+                    ..Default::default()
+                })
+                .await?;
+        }
         Ok(())
     }
 }
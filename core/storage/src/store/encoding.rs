@@ -0,0 +1,122 @@
+//! Versioned encoding of stored multipools and journal records. Every record starts
+//! with `STORAGE_VERSION`, records of older layouts are migrated by stores on open.
+//! Version 0 is the untagged multipool layout of sled stores written before
+//! records were versioned, journal and other stores always had a version.
+
+use anyhow::{anyhow, Result};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// Layout version of stored records, bumped on every change of `Multipool` or
+/// journal layout together with a migration from the previous one
+pub const STORAGE_VERSION: u8 = 1;
+
+pub fn encode<T: BorshSerialize>(value: &T) -> Vec<u8> {
+    let mut w = vec![STORAGE_VERSION];
+    value.serialize(&mut w).unwrap();
+    w
+}
+
+pub fn decode<T: BorshDeserialize>(record: &[u8]) -> Result<T> {
+    match record.split_first() {
+        Some((&STORAGE_VERSION, data)) => Ok(borsh::from_slice(data)?),
+        Some((version, _)) => Err(anyhow!("Unsupported record version {version}")),
+        None => Err(anyhow!("Empty record")),
+    }
+}
+
+/// Untagged multipool layout written by sled stores before records were versioned
+pub mod v0 {
+    use alloy::primitives::{
+        aliases::{U112, U128, U96},
+        Address, B256, U256,
+    };
+    use anyhow::Result;
+    use borsh::BorshDeserialize;
+    use multipool::{Multipool, MultipoolAsset};
+    use multipool_types::{
+        borsh_methods::deserialize,
+        expiry::{EmptyTimeExtractor, MayBeExpired},
+    };
+
+    use super::encode;
+
+    #[derive(BorshDeserialize)]
+    struct LegacyMultipool {
+        #[borsh(deserialize_with = "deserialize::address")]
+        contract_address: Address,
+        chain_id: u64,
+        assets: Vec<LegacyAsset>,
+        #[borsh(deserialize_with = "deserialize::u256")]
+        total_supply: U256,
+        #[borsh(deserialize_with = "deserialize::address")]
+        strategy_manager: Address,
+        #[borsh(deserialize_with = "deserialize::address")]
+        owner: Address,
+        deviation_increase_fee: u16,
+        deviation_limit: u16,
+        cashback_fee: u16,
+        base_fee: u16,
+        #[borsh(deserialize_with = "deserialize::address")]
+        management_fee_receiver: Address,
+        management_fee: u16,
+        total_target_shares: u16,
+        #[borsh(deserialize_with = "deserialize::address")]
+        oracle_address: Address,
+        #[borsh(deserialize_with = "deserialize::u96")]
+        initial_share_price: U96,
+    }
+
+    #[derive(BorshDeserialize)]
+    struct LegacyAsset {
+        #[borsh(deserialize_with = "deserialize::address")]
+        address: Address,
+        #[borsh(deserialize_with = "deserialize::b256")]
+        price_data: B256,
+        price: Option<MayBeExpired<U256, EmptyTimeExtractor>>,
+        #[borsh(deserialize_with = "deserialize::u128")]
+        quantity: U128,
+        #[borsh(deserialize_with = "deserialize::u112")]
+        collected_cashbacks: U112,
+        share: u16,
+    }
+
+    fn decode_multipool(data: &[u8]) -> Result<Multipool> {
+        let mp: LegacyMultipool = borsh::from_slice(data)?;
+        Ok(Multipool {
+            contract_address: mp.contract_address,
+            chain_id: mp.chain_id,
+            assets: mp
+                .assets
+                .into_iter()
+                .map(|asset| MultipoolAsset {
+                    address: asset.address,
+                    price_data: asset.price_data,
+                    price: asset.price,
+                    quantity: asset.quantity,
+                    collected_cashbacks: asset.collected_cashbacks,
+                    share: asset.share,
+                    // volume before the migration is unknown
+                    swap_volume: U256::ZERO,
+                    collected_fees: U256::ZERO,
+                })
+                .collect(),
+            total_supply: mp.total_supply,
+            strategy_manager: mp.strategy_manager,
+            owner: mp.owner,
+            deviation_increase_fee: mp.deviation_increase_fee,
+            deviation_limit: mp.deviation_limit,
+            cashback_fee: mp.cashback_fee,
+            base_fee: mp.base_fee,
+            management_fee_receiver: mp.management_fee_receiver,
+            management_fee: mp.management_fee,
+            total_target_shares: mp.total_target_shares,
+            oracle_address: mp.oracle_address,
+            initial_share_price: mp.initial_share_price,
+        })
+    }
+
+    /// Re-encodes untagged multipool with current layout
+    pub fn migrate_multipool(data: &[u8]) -> Result<Vec<u8>> {
+        Ok(encode(&decode_multipool(data)?))
+    }
+}
@@ -0,0 +1,117 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, RwLock},
+};
+
+use alloy::primitives::{Address, U256};
+use anyhow::{anyhow, Result};
+use multipool::Multipool;
+
+use super::{MultipoolStore, StoreBatch};
+
+#[derive(Default)]
+struct MemoryState {
+    multipools: BTreeMap<Address, Multipool>,
+    balances: BTreeMap<(Address, Address), U256>,
+    last_seen_blocks: BTreeMap<u64, u64>,
+    journal: BTreeMap<(u64, u64), Vec<u8>>,
+}
+
+/// Non persistent store, used in tests and as read cache of `PgStore`
+#[derive(Clone, Default)]
+pub struct MemoryStore(Arc<RwLock<MemoryState>>);
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn apply(&self, batch: StoreBatch) -> Result<()> {
+        let mut state = self
+            .0
+            .write()
+            .map_err(|_| anyhow!("Memory store poisoned"))?;
+        for multipool in batch.multipools {
+            state
+                .multipools
+                .insert(multipool.contract_address(), multipool);
+        }
+        for address in batch.multipool_removals {
+            state.multipools.remove(&address);
+        }
+        for (multipool, holder, balance) in batch.balances {
+            if balance.is_zero() {
+                state.balances.remove(&(multipool, holder));
+            } else {
+                state.balances.insert((multipool, holder), balance);
+            }
+        }
+        for (chain_id, block) in batch.last_seen_blocks {
+            match block {
+                Some(block) => state.last_seen_blocks.insert(chain_id, block),
+                None => state.last_seen_blocks.remove(&chain_id),
+            };
+        }
+        for (chain_id, block_number, record) in batch.journal_inserts {
+            state.journal.insert((chain_id, block_number), record);
+        }
+        for key in batch.journal_removals {
+            state.journal.remove(&key);
+        }
+        Ok(())
+    }
+}
+
+impl MultipoolStore for MemoryStore {
+    fn get_multipool(&self, address: &Address) -> Result<Option<Multipool>> {
+        let state = self
+            .0
+            .read()
+            .map_err(|_| anyhow!("Memory store poisoned"))?;
+        Ok(state.multipools.get(address).cloned())
+    }
+
+    fn multipool_addresses(&self) -> Result<Vec<Address>> {
+        let state = self
+            .0
+            .read()
+            .map_err(|_| anyhow!("Memory store poisoned"))?;
+        Ok(state.multipools.keys().copied().collect())
+    }
+
+    fn balance_of(&self, multipool: &Address, holder: &Address) -> Result<U256> {
+        let state = self
+            .0
+            .read()
+            .map_err(|_| anyhow!("Memory store poisoned"))?;
+        Ok(state
+            .balances
+            .get(&(*multipool, *holder))
+            .copied()
+            .unwrap_or_default())
+    }
+
+    fn get_last_seen_block(&self, chain_id: u64) -> Result<Option<u64>> {
+        let state = self
+            .0
+            .read()
+            .map_err(|_| anyhow!("Memory store poisoned"))?;
+        Ok(state.last_seen_blocks.get(&chain_id).copied())
+    }
+
+    fn journal(&self, chain_id: u64, from_block: u64) -> Result<Vec<(u64, Vec<u8>)>> {
+        let state = self
+            .0
+            .read()
+            .map_err(|_| anyhow!("Memory store poisoned"))?;
+        Ok(state
+            .journal
+            .range((chain_id, from_block)..=(chain_id, u64::MAX))
+            .map(|((_, block_number), record)| (*block_number, record.clone()))
+            .collect())
+    }
+
+    async fn commit(&self, batch: StoreBatch) -> Result<()> {
+        self.apply(batch)
+    }
+}
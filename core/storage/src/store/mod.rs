@@ -0,0 +1,47 @@
+use std::future::Future;
+
+use alloy::primitives::{Address, U256};
+use anyhow::Result;
+use multipool::Multipool;
+
+pub mod encoding;
+pub mod memory;
+pub mod postgres;
+pub mod sled_store;
+
+pub use memory::MemoryStore;
+pub use postgres::PgStore;
+pub use sled_store::SledStore;
+
+/// Set of changes that is committed atomically
+#[derive(Clone, Default)]
+pub struct StoreBatch {
+    pub multipools: Vec<Multipool>,
+    pub multipool_removals: Vec<Address>,
+    /// Share balances as (multipool, holder, balance), zero balance removes the record
+    pub balances: Vec<(Address, Address, U256)>,
+    /// Last seen block per chain, `None` removes the record
+    pub last_seen_blocks: Vec<(u64, Option<u64>)>,
+    /// Journal records as (chain_id, block_number, record)
+    pub journal_inserts: Vec<(u64, u64, Vec<u8>)>,
+    /// Journal records to remove as (chain_id, block_number)
+    pub journal_removals: Vec<(u64, u64)>,
+}
+
+/// Persistence layer of `MultipoolStorage`. Reads are synchronous because they are
+/// used by hook getters, writes go through `commit` only.
+pub trait MultipoolStore: Clone + Send + Sync + 'static {
+    fn get_multipool(&self, address: &Address) -> Result<Option<Multipool>>;
+
+    fn multipool_addresses(&self) -> Result<Vec<Address>>;
+
+    /// Share balance of the holder, zero for unknown holders
+    fn balance_of(&self, multipool: &Address, holder: &Address) -> Result<U256>;
+
+    fn get_last_seen_block(&self, chain_id: u64) -> Result<Option<u64>>;
+
+    /// Journal records of the chain starting from `from_block`, ordered by block number
+    fn journal(&self, chain_id: u64, from_block: u64) -> Result<Vec<(u64, Vec<u8>)>>;
+
+    fn commit(&self, batch: StoreBatch) -> impl Future<Output = Result<()>> + Send;
+}
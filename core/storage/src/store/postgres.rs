@@ -0,0 +1,219 @@
+use alloy::primitives::{Address, U256};
+use anyhow::{anyhow, Result};
+use multipool::Multipool;
+use sqlx::PgPool;
+
+use super::{
+    encoding::{decode, encode, STORAGE_VERSION},
+    MemoryStore, MultipoolStore, StoreBatch,
+};
+
+const CREATE_TABLES: [&str; 5] = [
+    "CREATE TABLE IF NOT EXISTS storage_multipools (
+        address     BYTEA   PRIMARY KEY,
+        chain_id    BIGINT  NOT NULL,
+        data        BYTEA   NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS storage_index_data (
+        chain_id        BIGINT  PRIMARY KEY,
+        current_block   BIGINT  NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS storage_journal (
+        chain_id        BIGINT  NOT NULL,
+        block_number    BIGINT  NOT NULL,
+        record          BYTEA   NOT NULL,
+
+        CONSTRAINT storage_journal_pkey PRIMARY KEY (chain_id, block_number)
+    )",
+    "CREATE TABLE IF NOT EXISTS storage_balances (
+        multipool   BYTEA   NOT NULL,
+        holder      BYTEA   NOT NULL,
+        balance     BYTEA   NOT NULL,
+
+        CONSTRAINT storage_balances_pkey PRIMARY KEY (multipool, holder)
+    )",
+    "CREATE TABLE IF NOT EXISTS storage_version (
+        version     SMALLINT    NOT NULL
+    )",
+];
+
+/// Postgres backed store. Whole state is loaded on start and kept in memory
+/// so hooks can read it synchronously, every commit is written through.
+#[derive(Clone)]
+pub struct PgStore {
+    pool: PgPool,
+    cache: MemoryStore,
+}
+
+impl PgStore {
+    pub async fn new(pool: PgPool) -> Result<Self> {
+        for query in CREATE_TABLES {
+            sqlx::query(query).execute(&pool).await?;
+        }
+        Self::check_version(&pool).await?;
+
+        let multipools = sqlx::query_as::<_, (Vec<u8>,)>("SELECT data FROM storage_multipools")
+            .fetch_all(&pool)
+            .await?
+            .into_iter()
+            .map(|(data,)| decode(&data))
+            .collect::<Result<Vec<Multipool>>>()?;
+        let balances = sqlx::query_as::<_, (Vec<u8>, Vec<u8>, Vec<u8>)>(
+            "SELECT multipool, holder, balance FROM storage_balances",
+        )
+        .fetch_all(&pool)
+        .await?
+        .into_iter()
+        .map(|(multipool, holder, balance)| {
+            Ok((
+                Address::try_from(multipool.as_slice())?,
+                Address::try_from(holder.as_slice())?,
+                U256::from_le_bytes::<32>(balance.as_slice().try_into()?),
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+        let last_seen_blocks = sqlx::query_as::<_, (i64, i64)>(
+            "SELECT chain_id, current_block FROM storage_index_data",
+        )
+        .fetch_all(&pool)
+        .await?
+        .into_iter()
+        .map(|(chain_id, block)| Ok((chain_id.try_into()?, Some(block.try_into()?))))
+        .collect::<Result<Vec<_>>>()?;
+        let journal_inserts = sqlx::query_as::<_, (i64, i64, Vec<u8>)>(
+            "SELECT chain_id, block_number, record FROM storage_journal",
+        )
+        .fetch_all(&pool)
+        .await?
+        .into_iter()
+        .map(|(chain_id, block, record)| Ok((chain_id.try_into()?, block.try_into()?, record)))
+        .collect::<Result<Vec<_>>>()?;
+
+        let cache = MemoryStore::new();
+        cache.apply(StoreBatch {
+            multipools,
+            balances,
+            last_seen_blocks,
+            journal_inserts,
+            ..Default::default()
+        })?;
+        Ok(Self { pool, cache })
+    }
+
+    /// Marks new store with `STORAGE_VERSION`, migrations of later layouts go here
+    async fn check_version(pool: &PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+        // blocks concurrent initialization of the same database
+        sqlx::query("LOCK TABLE storage_version")
+            .execute(&mut *tx)
+            .await?;
+        let version = sqlx::query_as::<_, (i16,)>("SELECT version FROM storage_version")
+            .fetch_optional(&mut *tx)
+            .await?;
+        match version {
+            Some((version,)) if version == i16::from(STORAGE_VERSION) => return Ok(()),
+            Some((version,)) => return Err(anyhow!("Unsupported storage version {version}")),
+            None => (),
+        }
+        sqlx::query("INSERT INTO storage_version (version) VALUES ($1)")
+            .bind::<i16>(STORAGE_VERSION.into())
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+impl MultipoolStore for PgStore {
+    fn get_multipool(&self, address: &Address) -> Result<Option<Multipool>> {
+        self.cache.get_multipool(address)
+    }
+
+    fn multipool_addresses(&self) -> Result<Vec<Address>> {
+        self.cache.multipool_addresses()
+    }
+
+    fn balance_of(&self, multipool: &Address, holder: &Address) -> Result<U256> {
+        self.cache.balance_of(multipool, holder)
+    }
+
+    fn get_last_seen_block(&self, chain_id: u64) -> Result<Option<u64>> {
+        self.cache.get_last_seen_block(chain_id)
+    }
+
+    fn journal(&self, chain_id: u64, from_block: u64) -> Result<Vec<(u64, Vec<u8>)>> {
+        self.cache.journal(chain_id, from_block)
+    }
+
+    async fn commit(&self, batch: StoreBatch) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        for multipool in batch.multipools.iter() {
+            sqlx::query(
+                "INSERT INTO storage_multipools (address, chain_id, data) VALUES ($1, $2, $3)
+                ON CONFLICT (address) DO UPDATE SET data = EXCLUDED.data",
+            )
+            .bind(multipool.contract_address().as_slice())
+            .bind::<i64>(multipool.chain_id().try_into()?)
+            .bind(encode(multipool))
+            .execute(&mut *tx)
+            .await?;
+        }
+        for address in batch.multipool_removals.iter() {
+            sqlx::query("DELETE FROM storage_multipools WHERE address = $1")
+                .bind(address.as_slice())
+                .execute(&mut *tx)
+                .await?;
+        }
+        for (multipool, holder, balance) in batch.balances.iter() {
+            if balance.is_zero() {
+                sqlx::query("DELETE FROM storage_balances WHERE multipool = $1 AND holder = $2")
+                    .bind(multipool.as_slice())
+                    .bind(holder.as_slice())
+            } else {
+                sqlx::query(
+                    "INSERT INTO storage_balances (multipool, holder, balance) VALUES ($1, $2, $3)
+                    ON CONFLICT (multipool, holder) DO UPDATE SET balance = EXCLUDED.balance",
+                )
+                .bind(multipool.as_slice())
+                .bind(holder.as_slice())
+                .bind(balance.to_le_bytes::<32>().to_vec())
+            }
+            .execute(&mut *tx)
+            .await?;
+        }
+        for (chain_id, block) in batch.last_seen_blocks.iter() {
+            match block {
+                Some(block) => sqlx::query(
+                    "INSERT INTO storage_index_data (chain_id, current_block) VALUES ($1, $2)
+                    ON CONFLICT (chain_id) DO UPDATE SET current_block = EXCLUDED.current_block",
+                )
+                .bind::<i64>((*chain_id).try_into()?)
+                .bind::<i64>((*block).try_into()?),
+                None => sqlx::query("DELETE FROM storage_index_data WHERE chain_id = $1")
+                    .bind::<i64>((*chain_id).try_into()?),
+            }
+            .execute(&mut *tx)
+            .await?;
+        }
+        for (chain_id, block_number, record) in batch.journal_inserts.iter() {
+            sqlx::query(
+                "INSERT INTO storage_journal (chain_id, block_number, record) VALUES ($1, $2, $3)
+                ON CONFLICT (chain_id, block_number) DO UPDATE SET record = EXCLUDED.record",
+            )
+            .bind::<i64>((*chain_id).try_into()?)
+            .bind::<i64>((*block_number).try_into()?)
+            .bind(record.as_slice())
+            .execute(&mut *tx)
+            .await?;
+        }
+        for (chain_id, block_number) in batch.journal_removals.iter() {
+            sqlx::query("DELETE FROM storage_journal WHERE chain_id = $1 AND block_number = $2")
+                .bind::<i64>((*chain_id).try_into()?)
+                .bind::<i64>((*block_number).try_into()?)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        self.cache.apply(batch)
+    }
+}
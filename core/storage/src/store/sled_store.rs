@@ -0,0 +1,179 @@
+use alloy::primitives::{Address, U256};
+use anyhow::{anyhow, Result};
+use borsh::{BorshDeserialize, BorshSerialize};
+use multipool::Multipool;
+use sled::{transaction::ConflictableTransactionResult, Transactional};
+
+use super::{
+    encoding::{decode, encode, v0, STORAGE_VERSION},
+    MultipoolStore, StoreBatch,
+};
+
+const STORAGE_VERSION_KEY: &str = "storage_version";
+
+fn journal_key(chain_id: u64, block_number: u64) -> [u8; 16] {
+    let mut key = [0u8; 16];
+    key[..8].copy_from_slice(&chain_id.to_be_bytes());
+    key[8..].copy_from_slice(&block_number.to_be_bytes());
+    key
+}
+
+fn balance_key(multipool: &Address, holder: &Address) -> [u8; 40] {
+    let mut key = [0u8; 40];
+    key[..20].copy_from_slice(multipool.as_slice());
+    key[20..].copy_from_slice(holder.as_slice());
+    key
+}
+
+fn current_block_key(chain_id: u64) -> String {
+    format!("current_block_{}", chain_id)
+}
+
+#[derive(Clone)]
+pub struct SledStore {
+    multipools: sled::Tree,
+    index_data: sled::Tree,
+    journal: sled::Tree,
+    balances: sled::Tree,
+}
+
+impl SledStore {
+    pub fn new(db: sled::Db) -> Result<Self> {
+        let store = Self {
+            multipools: db.open_tree(b"multipools")?,
+            index_data: db.open_tree(b"index_data")?,
+            journal: db.open_tree(b"journal")?,
+            balances: db.open_tree(b"balances")?,
+        };
+        store.migrate()?;
+        Ok(store)
+    }
+
+    /// Brings records written by previous versions to `STORAGE_VERSION`
+    fn migrate(&self) -> Result<()> {
+        match self.index_data.get(STORAGE_VERSION_KEY)? {
+            Some(version) if version.as_ref() == [STORAGE_VERSION] => return Ok(()),
+            Some(version) => return Err(anyhow!("Unsupported storage version {version:?}")),
+            None => (),
+        }
+        // multipools were untagged before the version key was introduced, journal
+        // was written with versions from the start
+        let multipool_records = self
+            .multipools
+            .iter()
+            .map(|entry| -> Result<_> {
+                let (key, value) = entry?;
+                Ok((key, v0::migrate_multipool(&value)?))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        (&self.multipools, &self.index_data)
+            .transaction(
+                |(multipools, index_data)| -> ConflictableTransactionResult<(), anyhow::Error> {
+                    for (key, value) in multipool_records.iter() {
+                        multipools.insert(key, value.as_slice())?;
+                    }
+                    index_data.insert(STORAGE_VERSION_KEY, &[STORAGE_VERSION])?;
+                    Ok(())
+                },
+            )
+            .map_err(|e| anyhow!("Sled transaction failed: {e:?}"))?;
+        self.index_data.flush()?;
+        self.multipools.flush()?;
+        Ok(())
+    }
+}
+
+impl MultipoolStore for SledStore {
+    fn get_multipool(&self, address: &Address) -> Result<Option<Multipool>> {
+        self.multipools
+            .get(address.as_slice())?
+            .map(|mp| decode(&mp))
+            .transpose()
+    }
+
+    fn multipool_addresses(&self) -> Result<Vec<Address>> {
+        self.multipools
+            .iter()
+            .keys()
+            .map(|key| Ok(Address::from_slice(&key?)))
+            .collect()
+    }
+
+    fn balance_of(&self, multipool: &Address, holder: &Address) -> Result<U256> {
+        Ok(self
+            .balances
+            .get(balance_key(multipool, holder))?
+            .map(|value| -> Result<_> { Ok(U256::from_le_bytes::<32>(value.as_ref().try_into()?)) })
+            .transpose()?
+            .unwrap_or_default())
+    }
+
+    fn get_last_seen_block(&self, chain_id: u64) -> Result<Option<u64>> {
+        Ok(self
+            .index_data
+            .get(current_block_key(chain_id).as_bytes())?
+            .map(|value| u64::deserialize(&mut &value[..]))
+            .transpose()?)
+    }
+
+    fn journal(&self, chain_id: u64, from_block: u64) -> Result<Vec<(u64, Vec<u8>)>> {
+        self.journal
+            .range(journal_key(chain_id, from_block)..=journal_key(chain_id, u64::MAX))
+            .map(|entry| -> Result<_> {
+                let (key, value) = entry?;
+                Ok((u64::from_be_bytes(key[8..].try_into()?), value.to_vec()))
+            })
+            .collect()
+    }
+
+    async fn commit(&self, batch: StoreBatch) -> Result<()> {
+        (&self.multipools, &self.index_data, &self.journal, &self.balances)
+            .transaction(
+                |(multipools, index_data, journal, balances)| -> ConflictableTransactionResult<(), anyhow::Error> {
+                    for multipool in batch.multipools.iter() {
+                        multipools.insert(multipool.contract_address().as_slice(), encode(multipool))?;
+                    }
+                    for address in batch.multipool_removals.iter() {
+                        multipools.remove(address.as_slice())?;
+                    }
+                    for (multipool, holder, balance) in batch.balances.iter() {
+                        let key = balance_key(multipool, holder);
+                        if balance.is_zero() {
+                            balances.remove(key.to_vec())?;
+                        } else {
+                            balances.insert(key.to_vec(), balance.to_le_bytes::<32>().to_vec())?;
+                        }
+                    }
+                    for (chain_id, block) in batch.last_seen_blocks.iter() {
+                        let key = current_block_key(*chain_id);
+                        match block {
+                            Some(block) => {
+                                let mut w = Vec::new();
+                                block.serialize(&mut w).unwrap();
+                                index_data.insert(key.as_bytes(), w)?;
+                            }
+                            None => {
+                                index_data.remove(key.as_bytes())?;
+                            }
+                        }
+                    }
+                    for (chain_id, block_number, record) in batch.journal_inserts.iter() {
+                        journal.insert(
+                            journal_key(*chain_id, *block_number).to_vec(),
+                            record.as_slice(),
+                        )?;
+                    }
+                    for (chain_id, block_number) in batch.journal_removals.iter() {
+                        journal.remove(journal_key(*chain_id, *block_number).to_vec())?;
+                    }
+                    Ok(())
+                },
+            )
+            .map_err(|e| anyhow!("Sled transaction failed: {e:?}"))?;
+        self.index_data.flush()?;
+        self.journal.flush()?;
+        self.multipools.flush()?;
+        self.balances.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,78 @@
+use alloy::{
+    primitives::{address, aliases::U112, Address, Log, U256},
+    sol_types::SolEvent,
+};
+use multipool_types::{
+    messages::{Block, Event, Transaction},
+    Multipool::{AssetChange, ShareTransfer},
+    MultipoolFactory::MultipoolCreated,
+};
+
+pub const CHAIN_ID: u64 = 1;
+pub const MULTIPOOL: Address = address!("1000000000000000000000000000000000000001");
+pub const ASSET: Address = address!("2000000000000000000000000000000000000002");
+
+pub fn block(number: u64, fork: u8, quantity: u128) -> Block {
+    let data = AssetChange {
+        asset: ASSET,
+        quantity,
+        collectedCashbacks: U112::ZERO,
+    }
+    .encode_log_data();
+    block_with_log(
+        number,
+        fork,
+        Log {
+            address: MULTIPOOL,
+            data,
+        },
+    )
+}
+
+pub fn transfer(number: u64, fork: u8, from: Address, to: Address, amount: u64) -> Block {
+    let data = ShareTransfer {
+        from,
+        to,
+        amount: U256::from(amount),
+    }
+    .encode_log_data();
+    block_with_log(
+        number,
+        fork,
+        Log {
+            address: MULTIPOOL,
+            data,
+        },
+    )
+}
+
+/// Block where the factory at zero address creates `MULTIPOOL`
+pub fn creation(number: u64, fork: u8) -> Block {
+    let data = MultipoolCreated {
+        multipoolAddress: MULTIPOOL,
+        name: "Test".into(),
+        symbol: "TST".into(),
+    }
+    .encode_log_data();
+    block_with_log(
+        number,
+        fork,
+        Log {
+            address: Address::ZERO,
+            data,
+        },
+    )
+}
+
+fn block_with_log(number: u64, fork: u8, log: Log) -> Block {
+    Block {
+        number,
+        hash: [fork; 32],
+        timestamp: number,
+        transactions: vec![Transaction {
+            hash: [fork; 32],
+            index: 0,
+            events: vec![Event { log, index: 0 }],
+        }],
+    }
+}
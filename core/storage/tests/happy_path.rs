@@ -1,7 +1,16 @@
-use alloy::primitives::{address, Address};
+use alloy::primitives::{Address, U128, U256};
 use anyhow::Result;
 use futures::future::ready;
-use multipool_storage::{hook::HookInitializer, storage::MultipoolStorage};
+use multipool_storage::{
+    hook::HookInitializer,
+    storage::MultipoolStorage,
+    store::{MemoryStore, MultipoolStore, PgStore, SledStore},
+};
+use multipool_types::expiry::MayBeExpired;
+
+#[allow(dead_code)]
+mod common;
+use common::{block, creation, ASSET, CHAIN_ID, MULTIPOOL};
 
 pub struct TestHookInitializer;
 
@@ -14,10 +23,83 @@ impl HookInitializer for TestHookInitializer {
     }
 }
 
+async fn happy_path<S: MultipoolStore>(store: S) -> Result<()> {
+    let storage = MultipoolStorage::init(store.clone(), TestHookInitializer, Address::ZERO).await?;
+    storage
+        .apply_events(
+            &CHAIN_ID,
+            [creation(0, 0xa), block(1, 0xa, 10), block(3, 0xa, 30)]
+                .as_slice()
+                .try_into()?,
+        )
+        .await?;
+
+    // state is visible through the store after storage is dropped
+    drop(storage);
+    let storage = MultipoolStorage::init(store, TestHookInitializer, Address::ZERO).await?;
+    assert_eq!(Some(3), storage.get_last_seen_block(&CHAIN_ID)?);
+    let multipool = storage.get_multipool(&MULTIPOOL)?.unwrap();
+    assert_eq!(U128::from(30), multipool.asset(&ASSET).unwrap().quantity);
+    Ok(())
+}
+
+async fn prices_with_events<S: MultipoolStore>(store: S) -> Result<()> {
+    let storage = MultipoolStorage::init(store, TestHookInitializer, Address::ZERO).await?;
+    storage
+        .apply_events(
+            &CHAIN_ID,
+            [creation(0, 0xa), block(1, 0xa, 10)]
+                .as_slice()
+                .try_into()?,
+        )
+        .await?;
+
+    // both updates read the stored multipool, neither may be lost
+    let price = MayBeExpired::with_time(U256::from(7), 1);
+    let (prices, events) = tokio::join!(
+        storage.apply_prices(MULTIPOOL, vec![(ASSET, price.clone())]),
+        storage.apply_events(&CHAIN_ID, [block(2, 0xa, 20)].as_slice().try_into()?),
+    );
+    prices?;
+    events?;
+
+    let asset = storage
+        .get_multipool(&MULTIPOOL)?
+        .unwrap()
+        .asset(&ASSET)
+        .unwrap();
+    assert_eq!(U128::from(20), asset.quantity);
+    assert_eq!(Some(price), asset.price);
+    Ok(())
+}
+
 #[tokio::test]
-async fn happy_path() -> Result<()> {
-    let db = sled::open("test")?;
-    let mut storage = MultipoolStorage::init(db, TestHookInitializer, Address::ZERO).await?;
-    storage.apply_events(vec![], 1, 3).await?;
+async fn happy_path_memory() -> Result<()> {
+    happy_path(MemoryStore::new()).await
+}
+
+#[tokio::test]
+async fn happy_path_sled() -> Result<()> {
+    let db = sled::Config::new().temporary(true).open()?;
+    happy_path(SledStore::new(db)?).await
+}
+
+#[tokio::test]
+async fn prices_with_events_memory() -> Result<()> {
+    prices_with_events(MemoryStore::new()).await
+}
+
+#[sqlx::test(migrations = false)]
+async fn happy_path_pg(pool: sqlx::PgPool) -> Result<()> {
+    happy_path(PgStore::new(pool.clone()).await?).await?;
+
+    // reloaded store reads committed state from postgres
+    let store = PgStore::new(pool).await?;
+    assert_eq!(Some(3), store.get_last_seen_block(CHAIN_ID)?);
     Ok(())
 }
+
+#[sqlx::test(migrations = false)]
+async fn prices_with_events_pg(pool: sqlx::PgPool) -> Result<()> {
+    prices_with_events(PgStore::new(pool).await?).await
+}
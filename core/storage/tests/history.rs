@@ -0,0 +1,103 @@
+use alloy::primitives::{Address, U128};
+use anyhow::Result;
+use multipool::Multipool;
+use multipool_storage::pg::history::{multipool_at, replay, REPLAY_PAGE_SIZE};
+use multipool_types::messages::Block;
+use sqlx::PgPool;
+
+#[allow(dead_code)]
+mod common;
+use common::{block, ASSET, CHAIN_ID, MULTIPOOL};
+
+#[test]
+fn check_replay_blocks() {
+    let mut multipool = Multipool::new(MULTIPOOL, CHAIN_ID);
+    let replayed = replay(&mut multipool, &[block(1, 0xa, 10), block(3, 0xa, 30)]);
+    assert_eq!(2, replayed);
+    assert_eq!(U128::from(30), multipool.asset(&ASSET).unwrap().quantity);
+
+    let mut other = Multipool::new(Address::ZERO, CHAIN_ID);
+    assert_eq!(0, replay(&mut other, &[block(1, 0xa, 10)]));
+}
+
+async fn insert_block(pool: &PgPool, chain_id: u64, block: &Block) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO blocks(chain_id, block_number, payload) VALUES ($1, $2, $3)
+        ON CONFLICT (chain_id, block_number) DO UPDATE SET payload = EXCLUDED.payload",
+    )
+    .bind(chain_id as i64)
+    .bind(block.number as i64)
+    .bind(serde_json::to_value(block)?)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn set_indexed_block(pool: &PgPool, chain_id: u64, block_number: u64) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO indexed_blocks(chain_id, block_number) VALUES ($1, $2)
+        ON CONFLICT (chain_id) DO UPDATE SET block_number = EXCLUDED.block_number",
+    )
+    .bind(chain_id as i64)
+    .bind(block_number as i64)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn checkpoints(pool: &PgPool, chain_id: u64) -> Result<Vec<i64>> {
+    Ok(sqlx::query_as::<_, (i64,)>(
+        "SELECT block_number FROM multipool_checkpoints WHERE chain_id = $1 ORDER BY block_number",
+    )
+    .bind(chain_id as i64)
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|(block_number,)| block_number)
+    .collect())
+}
+
+async fn quantity_at(pool: &PgPool, chain_id: u64, block_number: u64) -> Result<Option<u128>> {
+    Ok(multipool_at(pool, chain_id, MULTIPOOL, block_number)
+        .await?
+        .map(|multipool| multipool.asset(&ASSET).unwrap().quantity.to()))
+}
+
+#[sqlx::test(migrations = false)]
+async fn check_state_at_block(pool: PgPool) -> Result<()> {
+    sqlx::raw_sql(include_str!("../../../sql/create_tables.sql"))
+        .execute(&pool)
+        .await?;
+    for (number, quantity) in [(5, 10), (15_000, 20), (25_000, 30)] {
+        insert_block(&pool, CHAIN_ID, &block(number, 0xa, quantity)).await?;
+    }
+
+    // checkpoints are not written before blocks are indexed as final
+    assert_eq!(Some(30), quantity_at(&pool, CHAIN_ID, 26_000).await?);
+    assert!(checkpoints(&pool, CHAIN_ID).await?.is_empty());
+
+    set_indexed_block(&pool, CHAIN_ID, 22_000).await?;
+    assert_eq!(None, quantity_at(&pool, CHAIN_ID, 4).await?);
+    assert_eq!(Some(30), quantity_at(&pool, CHAIN_ID, 26_000).await?);
+    assert_eq!(vec![10_000, 20_000], checkpoints(&pool, CHAIN_ID).await?);
+    assert_eq!(Some(10), quantity_at(&pool, CHAIN_ID, 12_000).await?);
+    assert_eq!(Some(20), quantity_at(&pool, CHAIN_ID, 20_500).await?);
+
+    // rewritten block drops checkpoints above it, they are written again on replay
+    insert_block(&pool, CHAIN_ID, &block(15_000, 0xb, 25)).await?;
+    assert_eq!(vec![10_000], checkpoints(&pool, CHAIN_ID).await?);
+    assert_eq!(Some(25), quantity_at(&pool, CHAIN_ID, 20_500).await?);
+    assert_eq!(vec![10_000, 20_000], checkpoints(&pool, CHAIN_ID).await?);
+
+    // blocks are replayed across pages
+    let chain_id = CHAIN_ID + 1;
+    let blocks = 2 * REPLAY_PAGE_SIZE as u64 + 1;
+    for number in 1..=blocks {
+        insert_block(&pool, chain_id, &block(number, 0xa, number.into())).await?;
+    }
+    assert_eq!(
+        Some(blocks.into()),
+        quantity_at(&pool, chain_id, blocks).await?
+    );
+    Ok(())
+}
@@ -0,0 +1,102 @@
+use alloy::primitives::{Address, U256};
+use anyhow::Result;
+use multipool_storage::{
+    storage::{EmptyHookInitialiser, MultipoolStorage},
+    store::{MemoryStore, MultipoolStore, PgStore, SledStore},
+};
+use multipool_types::messages::Block;
+
+#[allow(dead_code)]
+mod common;
+use common::{creation, transfer, CHAIN_ID, MULTIPOOL};
+
+fn holder(n: u8) -> Address {
+    Address::with_last_byte(n)
+}
+
+async fn apply<S: MultipoolStore>(
+    storage: &MultipoolStorage<EmptyHookInitialiser, S>,
+    blocks: &[Block],
+) -> Result<()> {
+    storage.apply_events(&CHAIN_ID, blocks.try_into()?).await
+}
+
+fn balance<S: MultipoolStore>(
+    storage: &MultipoolStorage<EmptyHookInitialiser, S>,
+    n: u8,
+) -> Result<U256> {
+    storage.balance_of(&MULTIPOOL, &holder(n))
+}
+
+async fn transfers<S: MultipoolStore>(store: S) -> Result<()> {
+    let storage = MultipoolStorage::init(store, EmptyHookInitialiser, Address::ZERO).await?;
+    apply(
+        &storage,
+        &[
+            creation(0, 0xa),
+            transfer(1, 0xa, Address::ZERO, holder(1), 100),
+            transfer(2, 0xa, Address::ZERO, holder(2), 50),
+            transfer(3, 0xa, holder(1), holder(2), 30),
+            transfer(4, 0xa, holder(2), holder(2), 10),
+        ],
+    )
+    .await?;
+    assert_eq!(U256::from(70), balance(&storage, 1)?);
+    assert_eq!(U256::from(80), balance(&storage, 2)?);
+    assert_eq!(U256::ZERO, balance(&storage, 3)?);
+
+    apply(&storage, &[transfer(5, 0xa, holder(1), Address::ZERO, 70)]).await?;
+    assert_eq!(U256::ZERO, balance(&storage, 1)?);
+    Ok(())
+}
+
+#[tokio::test]
+async fn transfers_memory() -> Result<()> {
+    transfers(MemoryStore::new()).await
+}
+
+#[tokio::test]
+async fn transfers_sled() -> Result<()> {
+    let db = sled::Config::new().temporary(true).open()?;
+    transfers(SledStore::new(db)?).await
+}
+
+#[sqlx::test(migrations = false)]
+async fn transfers_pg(pool: sqlx::PgPool) -> Result<()> {
+    transfers(PgStore::new(pool.clone()).await?).await?;
+
+    // reloaded store reads balances from postgres
+    let store = PgStore::new(pool).await?;
+    assert_eq!(U256::from(80), store.balance_of(&MULTIPOOL, &holder(2))?);
+    assert_eq!(U256::ZERO, store.balance_of(&MULTIPOOL, &holder(1))?);
+    Ok(())
+}
+
+#[tokio::test]
+async fn check_reorged_transfers_are_reverted() -> Result<()> {
+    let db = sled::Config::new().temporary(true).open()?;
+    let storage =
+        MultipoolStorage::init(SledStore::new(db)?, EmptyHookInitialiser, Address::ZERO).await?;
+    apply(
+        &storage,
+        &[
+            creation(0, 0xa),
+            transfer(1, 0xa, Address::ZERO, holder(1), 100),
+            transfer(2, 0xa, holder(1), holder(2), 30),
+            transfer(3, 0xa, holder(2), holder(3), 30),
+        ],
+    )
+    .await?;
+    assert_eq!(U256::from(30), balance(&storage, 3)?);
+
+    apply(&storage, &[transfer(2, 0xb, holder(1), holder(3), 10)]).await?;
+    assert_eq!(U256::from(90), balance(&storage, 1)?);
+    assert_eq!(U256::ZERO, balance(&storage, 2)?);
+    assert_eq!(U256::from(10), balance(&storage, 3)?);
+
+    // balances of reorged creation are removed with the multipool
+    storage.rollback(&CHAIN_ID, 0).await?;
+    assert_eq!(U256::ZERO, balance(&storage, 1)?);
+    assert_eq!(U256::ZERO, balance(&storage, 3)?);
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use alloy::primitives::{Address, U128};
+use anyhow::Result;
+use multipool_storage::{
+    storage::{EmptyHookInitialiser, MultipoolStorage},
+    store::SledStore,
+};
+
+#[allow(dead_code)]
+mod common;
+use common::{block, ASSET, CHAIN_ID, MULTIPOOL};
+
+/// Multipool with a single asset in the layout stored before records were versioned
+fn legacy_multipool(quantity: u128) -> Vec<u8> {
+    let mut w = Vec::new();
+    w.extend(MULTIPOOL.as_slice());
+    w.extend(CHAIN_ID.to_le_bytes());
+    // assets
+    w.extend(1u32.to_le_bytes());
+    w.extend(ASSET.as_slice());
+    w.extend([0u8; 32]); // price_data
+    w.push(0); // price
+    w.extend(quantity.to_le_bytes());
+    w.extend([0u8; 14]); // collected_cashbacks
+    w.extend(0u16.to_le_bytes()); // share
+                                  // multipool parameters
+    w.extend([0u8; 32]); // total_supply
+    w.extend([0u8; 40]); // strategy_manager, owner
+    w.extend([0u8; 8]); // fees
+    w.extend([0u8; 20]); // management_fee_receiver
+    w.extend([0u8; 4]); // management_fee, total_target_shares
+    w.extend([0u8; 20]); // oracle_address
+    w.extend([0u8; 12]); // initial_share_price
+    w
+}
+
+#[tokio::test]
+async fn check_legacy_sled_records_are_migrated() -> Result<()> {
+    let db = sled::Config::new().temporary(true).open()?;
+    db.open_tree(b"multipools")?
+        .insert(MULTIPOOL.as_slice(), legacy_multipool(20))?;
+    db.open_tree(b"index_data")?
+        .insert("current_block_1", borsh::to_vec(&2u64)?)?;
+
+    let storage = MultipoolStorage::init(
+        SledStore::new(db.clone())?,
+        EmptyHookInitialiser,
+        Address::ZERO,
+    )
+    .await?;
+    let quantity = |storage: &MultipoolStorage<_, SledStore>| -> Result<U128> {
+        Ok(storage
+            .get_multipool(&MULTIPOOL)?
+            .unwrap()
+            .asset(&ASSET)
+            .unwrap()
+            .quantity)
+    };
+    assert_eq!(U128::from(20), quantity(&storage)?);
+
+    // migrated multipool is journaled and rolled back as any other
+    storage
+        .apply_events(&CHAIN_ID, [block(3, 0xb, 25)].as_slice().try_into()?)
+        .await?;
+    assert_eq!(U128::from(25), quantity(&storage)?);
+    assert_eq!(Some(2), storage.rollback(&CHAIN_ID, 3).await?);
+    assert_eq!(U128::from(20), quantity(&storage)?);
+
+    // migration runs once, reopened store reads current records
+    SledStore::new(db)?;
+    Ok(())
+}
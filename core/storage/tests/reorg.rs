@@ -0,0 +1,194 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use alloy::primitives::{Address, U128};
+use anyhow::Result;
+use multipool::Multipool;
+use multipool_storage::{
+    hook::HookInitializer,
+    storage::{EmptyHookInitialiser, MultipoolStorage},
+    store::SledStore,
+};
+use multipool_types::messages::Block;
+use tokio::task::JoinHandle;
+
+#[allow(dead_code)]
+mod common;
+use common::{block, creation, ASSET, CHAIN_ID, MULTIPOOL};
+
+/// Counts running hooks, hook's guard is dropped once its task is aborted
+#[derive(Clone, Default)]
+struct CountingHooks(Arc<AtomicUsize>);
+
+struct RunningHook(Arc<AtomicUsize>);
+
+impl Drop for RunningHook {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl CountingHooks {
+    fn running(&self) -> usize {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+impl HookInitializer for CountingHooks {
+    async fn initialize_hook<F: Fn() -> Multipool + Send + Sync + 'static>(
+        &mut self,
+        multipool: F,
+    ) -> Vec<JoinHandle<Result<()>>> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        let running = RunningHook(self.0.clone());
+        vec![tokio::spawn(async move {
+            let _running = running;
+            loop {
+                // panics if the multipool is removed before the hook is stopped
+                multipool();
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        })]
+    }
+}
+
+async fn storage() -> Result<MultipoolStorage<EmptyHookInitialiser>> {
+    let db = sled::Config::new().temporary(true).open()?;
+    let storage =
+        MultipoolStorage::init(SledStore::new(db)?, EmptyHookInitialiser, Address::ZERO).await?;
+    apply(&storage, &[creation(0, 0xa)]).await?;
+    Ok(storage)
+}
+
+async fn apply<HI: HookInitializer>(
+    storage: &MultipoolStorage<HI>,
+    blocks: &[Block],
+) -> Result<()> {
+    storage.apply_events(&CHAIN_ID, blocks.try_into()?).await
+}
+
+fn quantity<HI: HookInitializer>(storage: &MultipoolStorage<HI>) -> U128 {
+    storage
+        .get_multipool(&MULTIPOOL)
+        .unwrap()
+        .unwrap()
+        .asset(&ASSET)
+        .unwrap()
+        .quantity
+}
+
+#[tokio::test]
+async fn check_forked_block_replaces_branch() -> Result<()> {
+    let storage = storage().await?;
+    apply(
+        &storage,
+        &[block(1, 0xa, 10), block(2, 0xa, 20), block(3, 0xa, 30)],
+    )
+    .await?;
+    assert_eq!(U128::from(30), quantity(&storage));
+    assert_eq!(Some(3), storage.get_last_seen_block(&CHAIN_ID)?);
+
+    // block 2 is reorged, block 3 is gone
+    apply(&storage, &[block(2, 0xb, 25)]).await?;
+    assert_eq!(U128::from(25), quantity(&storage));
+    assert_eq!(Some(2), storage.get_last_seen_block(&CHAIN_ID)?);
+
+    apply(&storage, &[block(3, 0xb, 35)]).await?;
+    assert_eq!(U128::from(35), quantity(&storage));
+    assert_eq!(Some(3), storage.get_last_seen_block(&CHAIN_ID)?);
+    Ok(())
+}
+
+#[tokio::test]
+async fn check_missing_block_is_rolled_back() -> Result<()> {
+    let storage = storage().await?;
+    apply(
+        &storage,
+        &[block(1, 0xa, 10), block(2, 0xa, 20), block(3, 0xa, 30)],
+    )
+    .await?;
+
+    // new branch has no multipool events in block 2
+    apply(&storage, &[block(1, 0xa, 10), block(3, 0xb, 15)]).await?;
+    assert_eq!(U128::from(15), quantity(&storage));
+    assert_eq!(Some(3), storage.get_last_seen_block(&CHAIN_ID)?);
+    Ok(())
+}
+
+#[tokio::test]
+async fn check_redelivered_blocks_are_skipped() -> Result<()> {
+    let storage = storage().await?;
+    apply(&storage, &[block(1, 0xa, 10), block(2, 0xa, 20)]).await?;
+    apply(
+        &storage,
+        &[block(1, 0xa, 10), block(2, 0xa, 20), block(3, 0xa, 30)],
+    )
+    .await?;
+    apply(&storage, &[block(2, 0xa, 20)]).await?;
+    assert_eq!(U128::from(30), quantity(&storage));
+    assert_eq!(Some(3), storage.get_last_seen_block(&CHAIN_ID)?);
+    Ok(())
+}
+
+#[tokio::test]
+async fn check_rollback_to_empty_state() -> Result<()> {
+    let storage = storage().await?;
+    apply(&storage, &[block(1, 0xa, 10), block(2, 0xa, 20)]).await?;
+
+    assert_eq!(Some(0), storage.rollback(&CHAIN_ID, 1).await?);
+    assert!(storage
+        .get_multipool(&MULTIPOOL)?
+        .unwrap()
+        .asset(&ASSET)
+        .is_err());
+
+    // creation is rolled back as well
+    assert_eq!(None, storage.rollback(&CHAIN_ID, 0).await?);
+    assert_eq!(None, storage.get_last_seen_block(&CHAIN_ID)?);
+    assert!(storage.get_multipool(&MULTIPOOL)?.is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn check_reorged_creation_stops_hooks() -> Result<()> {
+    let hooks = CountingHooks::default();
+    let db = sled::Config::new().temporary(true).open()?;
+    let storage = MultipoolStorage::init(SledStore::new(db)?, hooks.clone(), Address::ZERO).await?;
+    apply(&storage, &[creation(1, 0xa), block(2, 0xa, 20)]).await?;
+    assert_eq!(U128::from(20), quantity(&storage));
+    assert_eq!(1, hooks.running());
+
+    // new branch has no creation, events of unknown multipool are ignored
+    apply(&storage, &[block(1, 0xb, 10)]).await?;
+    assert!(storage.get_multipool(&MULTIPOOL)?.is_none());
+    assert_eq!(0, hooks.running());
+
+    apply(&storage, &[creation(2, 0xb), block(3, 0xb, 30)]).await?;
+    assert_eq!(U128::from(30), quantity(&storage));
+    assert_eq!(1, hooks.running());
+    Ok(())
+}
+
+#[tokio::test]
+async fn check_reorg_deeper_than_journal() -> Result<()> {
+    let storage = storage().await?.with_journal_depth(1);
+    apply(
+        &storage,
+        &[block(1, 0xa, 10), block(2, 0xa, 20), block(3, 0xa, 30)],
+    )
+    .await?;
+
+    assert!(storage.rollback(&CHAIN_ID, 2).await.is_err());
+    // blocks below journal depth are final and ignored
+    apply(&storage, &[block(2, 0xb, 25)]).await?;
+    assert_eq!(U128::from(30), quantity(&storage));
+
+    apply(&storage, &[block(3, 0xb, 35)]).await?;
+    assert_eq!(U128::from(35), quantity(&storage));
+    Ok(())
+}
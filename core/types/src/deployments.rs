@@ -0,0 +1,225 @@
+use std::{collections::BTreeMap, fmt};
+
+use alloy::{primitives::Address, providers::Provider};
+use anyhow::Context;
+use serde::Deserialize;
+
+/// Default path of the registry shared by all services
+pub const DEFAULT_DEPLOYMENTS_PATH: &str = "deployments.yaml";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Contract {
+    MultipoolFactory,
+    Weth,
+    UniswapFactory,
+    UniswapQuoter,
+    Trader,
+    CashbackVault,
+    SiloLens,
+    SiloWrapper,
+}
+
+impl fmt::Display for Contract {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::MultipoolFactory => "multipool_factory",
+            Self::Weth => "weth",
+            Self::UniswapFactory => "uniswap_factory",
+            Self::UniswapQuoter => "uniswap_quoter",
+            Self::Trader => "trader",
+            Self::CashbackVault => "cashback_vault",
+            Self::SiloLens => "silo_lens",
+            Self::SiloWrapper => "silo_wrapper",
+        };
+        write!(f, "{name}")
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum DeploymentError {
+    UnknownChain(u64),
+    MissingContract(u64, Contract),
+    NoCode(u64, Contract, Address),
+}
+
+impl fmt::Display for DeploymentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownChain(chain_id) => write!(f, "no deployments on chain {chain_id}"),
+            Self::MissingContract(chain_id, contract) => {
+                write!(f, "{contract} is not deployed on chain {chain_id}")
+            }
+            Self::NoCode(chain_id, contract, address) => {
+                write!(f, "{contract} has no code at {address} on chain {chain_id}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DeploymentError {}
+
+/// Contract addresses of a single chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deployment {
+    chain_id: u64,
+    contracts: BTreeMap<Contract, Address>,
+}
+
+impl Deployment {
+    pub fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    pub fn get(&self, contract: Contract) -> Result<Address, DeploymentError> {
+        self.contracts
+            .get(&contract)
+            .copied()
+            .ok_or(DeploymentError::MissingContract(self.chain_id, contract))
+    }
+
+    /// Address of a contract that isn't deployed on every chain
+    pub fn optional(&self, contract: Contract) -> Option<Address> {
+        self.contracts.get(&contract).copied()
+    }
+
+    pub fn contracts(&self) -> impl Iterator<Item = (Contract, Address)> + '_ {
+        self.contracts
+            .iter()
+            .map(|(contract, address)| (*contract, *address))
+    }
+
+    /// Checks that the provider is connected to the chain and that there is
+    /// code at every address of the deployment
+    pub async fn validate<P: Provider>(&self, rpc: &P) -> anyhow::Result<()> {
+        let chain_id = rpc.get_chain_id().await?;
+        if chain_id != self.chain_id {
+            anyhow::bail!(
+                "provider is connected to chain {chain_id} instead of {}",
+                self.chain_id
+            );
+        }
+        for (contract, address) in self.contracts() {
+            let code = rpc
+                .get_code_at(address)
+                .await
+                .context(format!("Failed to get code of {contract}"))?;
+            if code.is_empty() {
+                return Err(DeploymentError::NoCode(self.chain_id, contract, address).into());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Registry of deployed contracts keyed by chain id, services resolve
+/// addresses from it instead of hardcoding them
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(from = "BTreeMap<u64, BTreeMap<Contract, Address>>")]
+pub struct Deployments(BTreeMap<u64, Deployment>);
+
+impl From<BTreeMap<u64, BTreeMap<Contract, Address>>> for Deployments {
+    fn from(value: BTreeMap<u64, BTreeMap<Contract, Address>>) -> Self {
+        Self(
+            value
+                .into_iter()
+                .map(|(chain_id, contracts)| {
+                    (
+                        chain_id,
+                        Deployment {
+                            chain_id,
+                            contracts,
+                        },
+                    )
+                })
+                .collect(),
+        )
+    }
+}
+
+impl Deployments {
+    pub fn from_yaml(yaml: &str) -> anyhow::Result<Self> {
+        serde_yaml::from_str(yaml).context("Failed to parse deployments")
+    }
+
+    pub fn from_file(path: &str) -> anyhow::Result<Self> {
+        let yaml = std::fs::read_to_string(path)
+            .context(format!("Failed to read deployments from {path}"))?;
+        Self::from_yaml(&yaml)
+    }
+
+    pub fn get(&self, chain_id: u64) -> Result<&Deployment, DeploymentError> {
+        self.0
+            .get(&chain_id)
+            .ok_or(DeploymentError::UnknownChain(chain_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        primitives::{address, Bytes, U64},
+        providers::ProviderBuilder,
+        transports::mock::Asserter,
+    };
+
+    use super::*;
+
+    const FACTORY: Address = address!("7eFe6656d08f2d6689Ed8ca8b5A3DEA0efaa769f");
+    const WETH: Address = address!("760AfE86e5de5fa0Ee542fc7B7B713e1c5425701");
+
+    fn deployments() -> Deployments {
+        Deployments::from_yaml(
+            "
+            10143:
+              multipool_factory: '0x7eFe6656d08f2d6689Ed8ca8b5A3DEA0efaa769f'
+              weth: '0x760AfE86e5de5fa0Ee542fc7B7B713e1c5425701'
+            ",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn get_test() {
+        let deployments = deployments();
+        let monad = deployments.get(10143).unwrap();
+        assert_eq!(monad.get(Contract::MultipoolFactory), Ok(FACTORY));
+        assert_eq!(monad.optional(Contract::Weth), Some(WETH));
+        assert_eq!(
+            monad.get(Contract::Trader),
+            Err(DeploymentError::MissingContract(10143, Contract::Trader))
+        );
+        assert_eq!(deployments.get(1), Err(DeploymentError::UnknownChain(1)));
+
+        assert!(Deployments::from_yaml(
+            "1:\n  unknown: '0x7eFe6656d08f2d6689Ed8ca8b5A3DEA0efaa769f'"
+        )
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn validate_test() {
+        let asserter = Asserter::new();
+        let rpc = ProviderBuilder::new().connect_mocked_client(asserter.clone());
+        let deployments = deployments();
+        let monad = deployments.get(10143).unwrap();
+
+        asserter.push_success(&U64::from(10143));
+        asserter.push_success(&Bytes::from_static(&[0x60, 0x80]));
+        asserter.push_success(&Bytes::from_static(&[0x60, 0x80]));
+        monad.validate(&rpc).await.unwrap();
+
+        // weth has no code
+        asserter.push_success(&U64::from(10143));
+        asserter.push_success(&Bytes::from_static(&[0x60, 0x80]));
+        asserter.push_success(&Bytes::new());
+        let error = monad.validate(&rpc).await.unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<DeploymentError>(),
+            Some(&DeploymentError::NoCode(10143, Contract::Weth, WETH))
+        );
+
+        asserter.push_success(&U64::from(1));
+        assert!(monad.validate(&rpc).await.is_err());
+    }
+}
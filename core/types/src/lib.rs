@@ -1,13 +1,10 @@
-use alloy::{
-    primitives::{address, Address},
-    sol,
-};
+use alloy::sol;
 
 pub mod borsh_methods;
+pub mod deployments;
 pub mod expiry;
 pub mod messages;
-
-pub const FACTORY_ADDRESS: Address = address!("7eFe6656d08f2d6689Ed8ca8b5A3DEA0efaa769f");
+pub mod resolutions;
 
 sol!(
     #[allow(clippy::too_many_arguments)]
@@ -0,0 +1,165 @@
+//! Candle resolutions configured by `resolutions` of gateway config. The gateway
+//! syncs them into the `resolutions` table read by `insert_price` and backfills
+//! added ones from 1m candles. The trader neither reads nor writes candles, so it
+//! takes no resolutions.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+pub const MINUTE: i32 = 60;
+pub const HOUR: i32 = 60 * MINUTE;
+pub const DAY: i32 = 24 * HOUR;
+pub const WEEK: i32 = 7 * DAY;
+
+/// Resolutions used when none are configured
+pub const DEFAULT_RESOLUTIONS: [i32; 4] = [MINUTE, 15 * MINUTE, HOUR, DAY];
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ResolutionError {
+    InvalidLabel(String),
+    NotMultipleOfMinute(i32),
+    MinuteMissing,
+}
+
+impl fmt::Display for ResolutionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidLabel(label) => write!(f, "invalid resolution {label}"),
+            Self::NotMultipleOfMinute(r) => write!(f, "resolution {r} is not a multiple of minute"),
+            Self::MinuteMissing => write!(f, "1m resolution is required"),
+        }
+    }
+}
+
+impl std::error::Error for ResolutionError {}
+
+/// Resolution in config, either seconds or label like `5m`, `4h`, `1d`, `1w`
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum ResolutionConfig {
+    Seconds(i32),
+    Label(String),
+}
+
+impl TryFrom<ResolutionConfig> for i32 {
+    type Error = ResolutionError;
+
+    fn try_from(value: ResolutionConfig) -> Result<Self, Self::Error> {
+        let label = match value {
+            ResolutionConfig::Seconds(seconds) => return Ok(seconds),
+            ResolutionConfig::Label(label) => label,
+        };
+        let invalid = || ResolutionError::InvalidLabel(label.clone());
+        let unit = match label.chars().last().ok_or_else(invalid)? {
+            's' => 1,
+            'm' => MINUTE,
+            'h' => HOUR,
+            'd' => DAY,
+            'w' => WEEK,
+            _ => return Err(invalid()),
+        };
+        label[..label.len() - 1]
+            .parse::<i32>()
+            .ok()
+            .and_then(|n| n.checked_mul(unit))
+            .filter(|r| *r > 0)
+            .ok_or_else(invalid)
+    }
+}
+
+/// Candle resolutions in seconds shared by all services, sorted ascending.
+/// Always contains 1m resolution, other candles are rebuilt from it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "Vec<ResolutionConfig>")]
+pub struct Resolutions(Vec<i32>);
+
+impl TryFrom<Vec<ResolutionConfig>> for Resolutions {
+    type Error = ResolutionError;
+
+    fn try_from(value: Vec<ResolutionConfig>) -> Result<Self, Self::Error> {
+        Self::new(
+            value
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+        )
+    }
+}
+
+impl Default for Resolutions {
+    fn default() -> Self {
+        Self(DEFAULT_RESOLUTIONS.to_vec())
+    }
+}
+
+impl Resolutions {
+    pub fn new(mut resolutions: Vec<i32>) -> Result<Self, ResolutionError> {
+        resolutions.sort_unstable();
+        resolutions.dedup();
+        if let Some(r) = resolutions.iter().find(|r| **r <= 0 || *r % MINUTE != 0) {
+            return Err(ResolutionError::NotMultipleOfMinute(*r));
+        }
+        if !resolutions.contains(&MINUTE) {
+            return Err(ResolutionError::MinuteMissing);
+        }
+        Ok(Self(resolutions))
+    }
+
+    pub fn index_of(&self, resolution: i32) -> Option<usize> {
+        self.0.binary_search(&resolution).ok()
+    }
+
+    pub fn get(&self, index: usize) -> Option<i32> {
+        self.0.get(index).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn as_slice(&self) -> &[i32] {
+        &self.0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = i32> + '_ {
+        self.0.iter().copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> Result<Resolutions, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    #[test]
+    fn labels_test() {
+        assert_eq!(
+            Resolutions::new(vec![60, 300, 900, 14400, 604800]).unwrap(),
+            parse(r#"["1w", "15m", 60, "4h", "5m", "1m"]"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn invalid_test() {
+        assert!(parse(r#"["1m", "5x"]"#).is_err());
+        assert!(parse(r#"["1m", "m"]"#).is_err());
+        assert!(parse(r#"["1m", "90s"]"#).is_err());
+        assert!(parse(r#"["5m"]"#).is_err());
+    }
+
+    #[test]
+    fn index_test() {
+        let resolutions = Resolutions::default();
+        assert_eq!(Some(1), resolutions.index_of(900));
+        assert_eq!(None, resolutions.index_of(300));
+        assert_eq!(Some(DAY), resolutions.get(3));
+    }
+}
@@ -2,13 +2,14 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use alloy::hex::ToHexExt;
-use alloy::primitives::address;
 use alloy::sol_types::SolEventInterface;
 use alloy::{primitives::Address, providers::ProviderBuilder};
 use backend_service::ServiceData;
 use multipool_storage::storage::{
     parse_log, MultipoolStorage, MultipoolUpdates, MultipoolsUpdates,
 };
+use multipool_storage::store::SledStore;
+use multipool_types::deployments::{Contract, Deployments, DEFAULT_DEPLOYMENTS_PATH};
 use multipool_types::Multipool::MultipoolEvents;
 use rdkafka::{
     consumer::{Consumer, StreamConsumer},
@@ -31,7 +32,9 @@ use crate::processors::kafka::Etl;
 
 mod processors;
 
-const FACTORY_ADDRESS: Address = address!("7eFe6656d08f2d6689Ed8ca8b5A3DEA0efaa769f");
+fn default_deployments() -> String {
+    DEFAULT_DEPLOYMENTS_PATH.into()
+}
 
 #[derive(Deserialize)]
 pub struct EtlService {
@@ -39,6 +42,9 @@ pub struct EtlService {
     kafka_group: String,
     chain_id: u64,
     database_url: String,
+    /// Registry of contract addresses
+    #[serde(default = "default_deployments")]
+    deployments: String,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -64,6 +70,10 @@ impl ServiceData for EtlService {
         //     .create()
         //     .expect("Cannot create kafka producer");
 
+        // etl has no rpc to validate deployments with, addresses are only resolved
+        let factory_address = Deployments::from_file(&self.deployments)?
+            .get(self.chain_id)?
+            .get(Contract::MultipoolFactory)?;
         let pool = PgPool::connect(&self.database_url).await?;
 
         let th = Etl {
@@ -74,7 +84,7 @@ impl ServiceData for EtlService {
         };
         let db = sled::open("etl_sled_db").unwrap();
         let storage = Arc::new(RwLock::new(
-            MultipoolStorage::init(db, th, FACTORY_ADDRESS)
+            MultipoolStorage::init(SledStore::new(db).unwrap(), th, factory_address)
                 .await
                 .unwrap(),
         ));
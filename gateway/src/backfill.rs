@@ -0,0 +1,78 @@
+use anyhow::Result;
+use backend_service::logging::LogTarget;
+use multipool_types::resolutions::{Resolutions, MINUTE};
+use serde_json::json;
+use sqlx::PgPool;
+
+use crate::{cache::TRW_RESOLUTION, service::log_target::GatewayTarget::PriceFetcher};
+
+/// Makes `resolutions` table match configured resolutions. Candles of removed
+/// resolutions are deleted and candles of added ones are rebuilt from 1m candles.
+/// 15m candles are always kept as they back 24h stats.
+pub async fn sync_resolutions(pool: &PgPool, resolutions: &Resolutions) -> Result<()> {
+    let mut stored: Vec<i32> = resolutions.iter().collect();
+    if !stored.contains(&TRW_RESOLUTION) {
+        stored.push(TRW_RESOLUTION);
+    }
+
+    let mut tx = pool.begin().await?;
+    let existing: Vec<i32> = sqlx::query_scalar("SELECT resolution FROM resolutions")
+        .fetch_all(&mut *tx)
+        .await?;
+
+    sqlx::query("DELETE FROM resolutions WHERE NOT (resolution = ANY($1))")
+        .bind(&stored)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("DELETE FROM candles WHERE NOT (resolution = ANY($1))")
+        .bind(&stored)
+        .execute(&mut *tx)
+        .await?;
+
+    for resolution in stored.into_iter().filter(|r| !existing.contains(r)) {
+        let backfilled = sqlx::query(
+            "
+            INSERT INTO candles(chain_id, multipool, resolution, ts, open, close, low, hight)
+            SELECT
+                chain_id,
+                multipool,
+                $1,
+                ts / $1 * $1 as bucket,
+                (array_agg(open ORDER BY ts ASC))[1],
+                (array_agg(close ORDER BY ts DESC))[1],
+                min(low),
+                max(hight)
+            FROM
+                candles
+            WHERE
+                resolution = $2
+            GROUP BY chain_id, multipool, bucket
+            ON CONFLICT (chain_id, multipool, resolution, ts) DO UPDATE SET
+                open = EXCLUDED.open,
+                close = EXCLUDED.close,
+                low = EXCLUDED.low,
+                hight = EXCLUDED.hight;",
+        )
+        .bind(resolution)
+        .bind(MINUTE)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        sqlx::query("INSERT INTO resolutions(resolution) VALUES ($1) ON CONFLICT DO NOTHING")
+            .bind(resolution)
+            .execute(&mut *tx)
+            .await?;
+
+        PriceFetcher
+            .info(json!({
+                "m": "resolution backfilled",
+                "r": resolution,
+                "c": backfilled,
+            }))
+            .log();
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
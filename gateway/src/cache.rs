@@ -1,3 +1,4 @@
+use std::collections::{HashMap, VecDeque};
 use std::sync::RwLock;
 
 use alloy::primitives::U256;
@@ -6,24 +7,35 @@ use anyhow::Result;
 use arweave_client::{Rpc, Signer};
 use bigdecimal::BigDecimal;
 use dashmap::DashMap;
+use multipool_types::resolutions::{Resolutions, DAY, MINUTE};
 use serde::Serialize;
 use serde::Serializer;
 use std::sync::Arc;
+use std::time::Instant;
 
 use sqlx::{Executor, PgPool, Postgres};
+use tokio::sync::{broadcast, Notify};
 
+use crate::error::{AppError, AppResult};
 use crate::ArweaveConfig;
 
-pub struct AppState<P: Provider> {
-    pub stats_cache: DashMap<Address, MultipoolCache>,
+pub struct ChainState<P: Provider> {
     pub multipools: Arc<RwLock<Vec<Address>>>,
-    pub connection: PgPool,
-    pub arwave: Option<ArwaveState>,
     pub provider: P,
-    pub chain_id: u64,
     pub factory: Address,
 }
 
+pub struct AppState<P: Provider> {
+    pub stats_cache: DashMap<(u64, Address), MultipoolCache>,
+    pub chains: HashMap<u64, ChainState<P>>,
+    pub connection: PgPool,
+    pub arwave: Option<ArwaveState>,
+    pub candle_updates: broadcast::Sender<CandleUpdate>,
+    pub resolutions: Resolutions,
+    /// Last metadata update of every multipool, see `routes::portfolio::create`
+    pub metadata_updates: DashMap<(u64, Address), Instant>,
+}
+
 #[derive(sqlx::FromRow, Debug, PartialEq, Eq)]
 struct DbMultipool {
     multipool: [u8; 20],
@@ -74,20 +86,32 @@ impl From<DbCandleSmall> for Candle {
             close: value.close.to_string().parse().unwrap(),
             low: value.low.to_string().parse().unwrap(),
             hight: value.hight.to_string().parse().unwrap(),
+            open_ts: value.ts as u64,
+            close_ts: value.ts as u64,
         }
     }
 }
 
 impl DbCandle {
-    async fn get_latest_day<E>(executor: &mut E) -> Result<Vec<Self>>
+    async fn get_latest_day<E>(
+        executor: &mut E,
+        chain_id: u64,
+        resolutions: &Resolutions,
+    ) -> Result<Vec<Self>>
     where
         for<'b> &'b mut E: Executor<'b, Database = Postgres>,
     {
         let mut candles = Vec::default();
-        for resolution in RESOLUTIONS {
+        let mut resolutions = resolutions.as_slice().to_vec();
+        if !resolutions.contains(&TRW_RESOLUTION) {
+            resolutions.push(TRW_RESOLUTION);
+        }
+        for resolution in resolutions {
             let part = sqlx::query_as(
-                "select * from candles WHERE resolution = $1 ORDER BY ts DESC LIMIT $2",
+                "select * from candles WHERE chain_id = $1 and resolution = $2
+                ORDER BY ts DESC LIMIT $3",
             )
+            .bind(chain_id as i64)
             .bind(resolution as i32)
             .bind(MAX_BUFFER_SIZE as i64)
             .fetch_all(&mut *executor)
@@ -101,44 +125,67 @@ impl DbCandle {
 pub struct ArwaveState {
     pub rpc: Rpc,
     pub signer: Arc<Signer>,
+    // wakes up metadata uploader
+    pub queued: Notify,
 }
 
 impl<P: Provider> AppState<P> {
+    /// `chains` contains (chain_id, provider, factory) of every served chain
     pub async fn initialize(
         connection: PgPool,
-        provider: P,
-        factory: Address,
+        chains: Vec<(u64, P, Address)>,
+        resolutions: Resolutions,
         arwave: Option<ArweaveConfig>,
     ) -> Result<Self> {
-        let chain_id = provider.get_chain_id().await?;
-        let stats_cache = DashMap::<Address, MultipoolCache>::default();
+        let stats_cache = DashMap::<(u64, Address), MultipoolCache>::default();
+        let mut chain_states = HashMap::new();
         let mut conn = connection.acquire().await?;
 
-        let multipools = DbMultipool::get_with_chain_id(&mut *conn, chain_id).await?;
-        let candles = DbCandle::get_latest_day(&mut *conn).await?;
-
-        for multipool in multipools.iter() {
-            let mut e = stats_cache
-                .entry(Address::new(multipool.multipool))
-                .or_insert(MultipoolCache::new(
-                    multipool.name.clone(),
-                    multipool.symbol.clone(),
-                ));
-            e.insert_total_supply(multipool.total_supply.to_string().parse().unwrap());
-            for candle in candles
-                .iter()
-                .filter(|c| c.multipool == multipool.multipool)
-            {
-                e.insert_price(candle.open.to_string().parse().unwrap(), candle.ts as u64);
-                e.insert_price(candle.low.to_string().parse().unwrap(), candle.ts as u64);
-                e.insert_price(candle.hight.to_string().parse().unwrap(), candle.ts as u64);
-                e.insert_price(candle.close.to_string().parse().unwrap(), candle.ts as u64);
+        for (chain_id, provider, factory) in chains {
+            let multipools = DbMultipool::get_with_chain_id(&mut *conn, chain_id).await?;
+            let candles = DbCandle::get_latest_day(&mut *conn, chain_id, &resolutions).await?;
+
+            for multipool in multipools.iter() {
+                let mut e = stats_cache
+                    .entry((chain_id, Address::new(multipool.multipool)))
+                    .or_insert(MultipoolCache::new(
+                        multipool.name.clone(),
+                        multipool.symbol.clone(),
+                        &resolutions,
+                    ));
+                e.insert_total_supply(multipool.total_supply.to_string().parse().unwrap());
+                for candle in candles
+                    .iter()
+                    .filter(|c| c.multipool == multipool.multipool)
+                {
+                    e.insert_candle(
+                        candle.resolution,
+                        Candle {
+                            ts: candle.ts as u64,
+                            open: candle.open.to_string().parse().unwrap(),
+                            close: candle.close.to_string().parse().unwrap(),
+                            low: candle.low.to_string().parse().unwrap(),
+                            hight: candle.hight.to_string().parse().unwrap(),
+                            open_ts: candle.ts as u64,
+                            close_ts: candle.ts as u64,
+                        },
+                    );
+                }
+                e.update_stats();
             }
-        }
 
-        let multipools = Arc::new(RwLock::new(
-            multipools.into_iter().map(|m| m.multipool.into()).collect(),
-        ));
+            let multipools = Arc::new(RwLock::new(
+                multipools.into_iter().map(|m| m.multipool.into()).collect(),
+            ));
+            let chain = ChainState {
+                multipools,
+                provider,
+                factory,
+            };
+            if chain_states.insert(chain_id, chain).is_some() {
+                anyhow::bail!("Chain {chain_id} is configured twice");
+            }
+        }
 
         Ok(Self {
             arwave: arwave
@@ -149,20 +196,25 @@ impl<P: Provider> AppState<P> {
                             ..Default::default()
                         },
                         signer: Arc::new(Signer::from_file(&a.wallet_path)?),
+                        queued: Notify::new(),
                     })
                 })
                 .transpose()?,
-            factory,
             stats_cache,
+            chains: chain_states,
             connection,
-            provider,
-            chain_id,
-            multipools,
+            candle_updates: broadcast::channel(CANDLE_UPDATES_CAPACITY).0,
+            resolutions,
+            metadata_updates: DashMap::default(),
         })
     }
+
+    pub fn chain(&self, chain_id: u64) -> AppResult<&ChainState<P>> {
+        self.chains.get(&chain_id).ok_or(AppError::InvalidChainId)
+    }
 }
 
-#[derive(Serialize, Clone, Default)]
+#[derive(Serialize, Clone, Default, Debug, PartialEq, Eq)]
 pub struct Candle {
     #[serde(rename(serialize = "t"))]
     #[serde(serialize_with = "serialize_u64")]
@@ -179,33 +231,66 @@ pub struct Candle {
     #[serde(rename(serialize = "h"))]
     #[serde(serialize_with = "serialize_u256")]
     pub hight: U256,
+    // timestamps of prices that set open and close, used to order late prices
+    #[serde(skip)]
+    pub open_ts: u64,
+    #[serde(skip)]
+    pub close_ts: u64,
+}
+
+impl Candle {
+    fn merge(&mut self, other: &Candle) {
+        self.hight = self.hight.max(other.hight);
+        self.low = self.low.min(other.low);
+        if other.open_ts < self.open_ts {
+            self.open = other.open;
+            self.open_ts = other.open_ts;
+        }
+        if other.close_ts >= self.close_ts {
+            self.close = other.close;
+            self.close_ts = other.close_ts;
+        }
+    }
 }
 
-#[derive(Serialize, Clone, Default)]
+/// Candle change pushed to stream subscribers
+#[derive(Serialize, Clone)]
+pub struct CandleUpdate {
+    #[serde(rename(serialize = "ch"))]
+    pub chain_id: u64,
+    #[serde(rename(serialize = "m"))]
+    pub multipool: Address,
+    #[serde(rename(serialize = "r"))]
+    pub resolution: i32,
+    #[serde(rename(serialize = "c"))]
+    pub candle: Candle,
+}
+
+#[derive(Serialize, Clone, Default, Debug, PartialEq, Eq)]
 pub struct Stats {
     #[serde(rename(serialize = "n"))]
-    name: String,
+    pub name: String,
     #[serde(rename(serialize = "s"))]
-    symbol: String,
+    pub symbol: String,
     #[serde(rename(serialize = "l"))]
     #[serde(serialize_with = "serialize_u256")]
-    low_24h: U256,
+    pub low_24h: U256,
     #[serde(rename(serialize = "h"))]
     #[serde(serialize_with = "serialize_u256")]
-    hight_24h: U256,
+    pub hight_24h: U256,
     #[serde(rename(serialize = "c"))]
     #[serde(serialize_with = "serialize_u256")]
-    current_price: U256,
+    pub current_price: U256,
     #[serde(rename(serialize = "o"))]
     #[serde(serialize_with = "serialize_u256")]
-    open_price: U256,
+    pub open_price: U256,
     #[serde(rename(serialize = "t"))]
     #[serde(serialize_with = "serialize_u128")]
-    total_supply: u128,
+    pub total_supply: u128,
     #[serde(rename(serialize = "cc"))]
-    current_candle: Option<Candle>,
+    pub current_candle: Option<Candle>,
     #[serde(rename(serialize = "pc"))]
-    previous_candle: Option<Candle>,
+    pub previous_candle: Option<Candle>,
 }
 
 pub fn serialize_u256<S>(number: &U256, serializer: S) -> Result<S::Ok, S::Error>
@@ -236,40 +321,104 @@ where
 }
 
 pub const MAX_BUFFER_SIZE: usize = 96;
-pub const TRW_RESOLUTION: usize = 1;
-pub const DAY: i32 = 86400;
-pub const MINUTE: i32 = 60;
+pub const CANDLE_UPDATES_CAPACITY: usize = 1024;
+// 96 candles of 15 minutes cover 24h window
+pub const TRW_RESOLUTION: i32 = 15 * MINUTE;
+
+/// Latest `MAX_BUFFER_SIZE` candles of a single resolution ordered by timestamp
+#[derive(Clone, Debug)]
+pub struct CandleBuffer {
+    resolution: u64,
+    candles: VecDeque<Candle>,
+}
 
-pub const RESOLUTIONS: [i32; 4] = [MINUTE, 900, 3600, DAY];
+impl CandleBuffer {
+    pub fn new(resolution: i32) -> Self {
+        Self {
+            resolution: resolution as u64,
+            candles: VecDeque::with_capacity(MAX_BUFFER_SIZE),
+        }
+    }
 
-pub fn resolution_to_index(resolution: i32) -> usize {
-    try_resolution_to_index(resolution).unwrap()
-}
+    pub fn resolution(&self) -> i32 {
+        self.resolution as i32
+    }
 
-pub fn try_resolution_to_index(resolution: i32) -> Option<usize> {
-    RESOLUTIONS
-        .into_iter()
-        .position(|r| r.eq(&resolution))
-        .map(TryInto::try_into)
-        .transpose()
-        .unwrap()
-}
+    fn align(&self, ts: u64) -> u64 {
+        ts / self.resolution * self.resolution
+    }
+
+    /// Adds price to the candle containing `ts`. Returns updated candle or `None`
+    /// if the candle is older than the whole buffer.
+    pub fn insert(&mut self, price: U256, ts: u64) -> Option<Candle> {
+        self.insert_candle(Candle {
+            ts: self.align(ts),
+            open: price,
+            close: price,
+            low: price,
+            hight: price,
+            open_ts: ts,
+            close_ts: ts,
+        })
+    }
+
+    /// Merges candle of the same resolution into the buffer
+    pub fn insert_candle(&mut self, candle: Candle) -> Option<Candle> {
+        match self.candles.binary_search_by_key(&candle.ts, |c| c.ts) {
+            Ok(index) => {
+                self.candles[index].merge(&candle);
+                Some(self.candles[index].clone())
+            }
+            Err(0) if self.candles.len() == MAX_BUFFER_SIZE => None,
+            Err(index) => {
+                self.candles.insert(index, candle.clone());
+                if self.candles.len() > MAX_BUFFER_SIZE {
+                    self.candles.pop_front();
+                }
+                Some(candle)
+            }
+        }
+    }
+
+    /// Candle containing `ts`
+    pub fn get(&self, ts: u64) -> Option<&Candle> {
+        let ts = self.align(ts);
+        self.candles
+            .binary_search_by_key(&ts, |c| c.ts)
+            .ok()
+            .map(|index| &self.candles[index])
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &Candle> + Clone {
+        self.candles.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.candles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.candles.is_empty()
+    }
 
-pub fn index_to_resolution(index: usize) -> i32 {
-    RESOLUTIONS[index]
+    pub fn to_vec(&self) -> Vec<Candle> {
+        self.candles.iter().cloned().collect()
+    }
 }
 
 pub struct MultipoolCache {
-    pub candles: [Vec<Candle>; 4],
-    pub trw_start_index: usize,
+    /// Candles of every configured resolution, 1m candles go first
+    pub candles: Vec<CandleBuffer>,
+    // source of 24h stats regardless of configured resolutions
+    trw: CandleBuffer,
     pub stats: Stats,
 }
 
 impl MultipoolCache {
-    pub fn new(name: String, symbol: String) -> Self {
+    pub fn new(name: String, symbol: String, resolutions: &Resolutions) -> Self {
         Self {
-            candles: Default::default(),
-            trw_start_index: 0,
+            candles: resolutions.iter().map(CandleBuffer::new).collect(),
+            trw: CandleBuffer::new(TRW_RESOLUTION),
             stats: Stats {
                 name,
                 symbol,
@@ -282,82 +431,56 @@ impl MultipoolCache {
         self.stats.total_supply = total_supply;
     }
 
-    fn align_by(ts: u64, resolution: i32) -> u64 {
-        ts / resolution as u64 * resolution as u64
+    fn minutes(&self) -> &CandleBuffer {
+        // 1m is the smallest possible resolution
+        &self.candles[0]
     }
 
     pub fn get_price(&self, ts: u64) -> Option<U256> {
-        self.get_candle_index(ts, MINUTE)
-            .map(|i| self.candles[resolution_to_index(MINUTE)][i].close)
+        self.minutes().get(ts).map(|c| c.close)
     }
 
-    fn get_candle_index(&self, ts: u64, resolution: i32) -> Option<usize> {
-        self.candles[resolution_to_index(resolution)]
-            .iter()
-            .rev()
-            .position(|c| c.ts == Self::align_by(ts, resolution))
+    /// Returns (resolution, candle) of every candle changed by the price
+    pub fn insert_price(&mut self, price: U256, ts: u64) -> Vec<(i32, Candle)> {
+        let updated = self
+            .candles
+            .iter_mut()
+            .filter_map(|buffer| Some((buffer.resolution(), buffer.insert(price, ts)?)))
+            .collect();
+        self.trw.insert(price, ts);
+        self.update_stats();
+        updated
     }
 
-    //TODO: tests (dis shit is crazy)
-    pub fn insert_price(&mut self, price: U256, ts: u64) {
-        for resolution in RESOLUTIONS {
-            let resolution_index = resolution_to_index(resolution);
-
-            let candle = match self.get_candle_index(ts, resolution) {
-                Some(i) => {
-                    let mut candle = self.candles[resolution_index][i].clone();
-                    candle.hight = candle.hight.max(price);
-                    candle.low = candle.low.min(price);
-                    candle.close = price;
-                    self.candles[resolution_index][i] = candle.clone();
-                    candle
-                }
-                None => {
-                    let candle = Candle {
-                        ts: Self::align_by(ts, resolution),
-                        open: price,
-                        close: price,
-                        low: price,
-                        hight: price,
-                    };
-                    self.candles[resolution_index].push(candle.clone());
-                    candle
-                }
-            };
-
-            self.candles[resolution_index].push(candle.clone());
-
-            if resolution_index == TRW_RESOLUTION {
-                self.trw_start_index = self.candles[resolution_index][self.trw_start_index..]
-                    .iter()
-                    .position(|c| candle.ts > c.ts + DAY as u64)
-                    .map(|n| self.trw_start_index + n)
-                    .unwrap_or(self.candles[resolution_index].len() - 1);
-
-                let trw_iter = self.candles[TRW_RESOLUTION][self.trw_start_index..].iter();
-                self.stats.hight_24h = trw_iter.clone().map(|c| c.hight).max().unwrap_or_default();
-                self.stats.low_24h = trw_iter.map(|c| c.low).min().unwrap_or_default();
-
-                self.stats.open_price = self.candles[TRW_RESOLUTION][self.trw_start_index].open;
-                self.stats.current_price = candle.close;
-            }
-
-            if resolution == MINUTE {
-                match self.stats.current_candle {
-                    Some(ref c) if c.ts < candle.ts => {
-                        self.stats.previous_candle = self.stats.current_candle.replace(candle);
-                    }
-                    None => {
-                        self.stats.current_candle = Some(candle);
-                    }
-                    _ => (),
-                }
-            }
+    /// Merges stored candle, candles of unknown resolutions are ignored
+    pub fn insert_candle(&mut self, resolution: i32, candle: Candle) {
+        if resolution == TRW_RESOLUTION {
+            self.trw.insert_candle(candle.clone());
+        }
+        if let Some(buffer) = self
+            .candles
+            .iter_mut()
+            .find(|buffer| buffer.resolution() == resolution)
+        {
+            buffer.insert_candle(candle);
+        }
+    }
 
-            let buf_len = self.candles[resolution_index].len();
-            self.candles[resolution_index]
-                .rotate_left(buf_len.checked_sub(MAX_BUFFER_SIZE).unwrap_or_default());
-            self.candles[resolution_index].truncate(MAX_BUFFER_SIZE);
+    /// Recalculates 24h window stats from the latest candles
+    pub fn update_stats(&mut self) {
+        if let Some(latest) = self.trw.iter().next_back() {
+            let window = self
+                .trw
+                .iter()
+                .skip_while(|c| c.ts + (DAY as u64) <= latest.ts);
+            self.stats.hight_24h = window.clone().map(|c| c.hight).max().unwrap_or_default();
+            self.stats.low_24h = window.clone().map(|c| c.low).min().unwrap_or_default();
+            self.stats.open_price = window.clone().next().map(|c| c.open).unwrap_or_default();
+            self.stats.current_price = latest.close;
         }
+
+        let mut minutes = self.minutes().iter().rev();
+        self.stats.current_candle = minutes.next().cloned();
+        self.stats.previous_candle = minutes.next().cloned();
     }
 }
@@ -11,6 +11,8 @@ pub enum AppError {
     InvalidResolution,
     FailedToGetCode,
     MultipoolNotCreated,
+    InvalidChainId,
+    TooManyRequests,
     Unknown(String),
 }
 
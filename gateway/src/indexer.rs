@@ -47,10 +47,16 @@ impl<'a, P: Provider + Clone> Processor<Transaction<'a, Postgres>> for PgEventPr
         chain_id: u64,
     ) -> anyhow::Result<()> {
         let commitement_timer = Instant::now();
-        INDEXER_HEIGHT.record(new_saved_block, &[]);
-        INDEXED_LOGS_COUNT.record(logs.len().try_into()?, &[]);
+        let chain_tag = [KeyValue::new("chain_id", chain_id as i64)];
+        INDEXER_HEIGHT.record(new_saved_block, &chain_tag);
+        INDEXED_LOGS_COUNT.record(logs.len().try_into()?, &chain_tag);
 
-        let blocks = Blocks::parse_logs(logs, self.app_state.provider.clone())
+        let chain = self
+            .app_state
+            .chains
+            .get(&chain_id)
+            .ok_or(anyhow!("Chain {chain_id} is not configured"))?;
+        let blocks = Blocks::parse_logs(logs, chain.provider.clone())
             .await
             .map_err(|_e| anyhow!("ParseLogsErrror"))?;
 
@@ -84,7 +90,7 @@ impl<'a, P: Provider + Clone> Processor<Transaction<'a, Postgres>> for PgEventPr
                                         "a": e.multipoolAddress,
                                     }))
                                     .log();
-                                if event.log.address != self.app_state.factory {
+                                if event.log.address != chain.factory {
                                     Indexer
                                         .info(json!({
                                             "m": "multipool created not by factory",
@@ -103,16 +109,25 @@ impl<'a, P: Provider + Clone> Processor<Transaction<'a, Postgres>> for PgEventPr
                                 .await?;
 
                                 self.app_state.stats_cache.insert(
-                                    e.multipoolAddress,
-                                    MultipoolCache::new(e.name, e.symbol),
+                                    (chain_id, e.multipoolAddress),
+                                    MultipoolCache::new(
+                                        e.name,
+                                        e.symbol,
+                                        &self.app_state.resolutions,
+                                    ),
                                 );
-                                let mut multipools = self.app_state.multipools.write().unwrap();
+                                let mut multipools = chain.multipools.write().unwrap();
                                 multipools.push(e.multipoolAddress);
                             }
                         }
 
                         let multipool_address = event.log.address;
-                        if self.app_state.stats_cache.get(&multipool_address).is_none() {
+                        if self
+                            .app_state
+                            .stats_cache
+                            .get(&(chain_id, multipool_address))
+                            .is_none()
+                        {
                             Indexer
                                 .info(json!({
                                     "m": "multipool event is orphan, skipping",
@@ -127,14 +142,14 @@ impl<'a, P: Provider + Clone> Processor<Transaction<'a, Postgres>> for PgEventPr
                                     let price = match self
                                         .app_state
                                         .stats_cache
-                                        .get(&multipool_address)
+                                        .get(&(chain_id, multipool_address))
                                         .expect("Multipool should present when having events")
                                         .get_price(block.timestamp)
                                     {
                                         Some(p) => p,
                                         None => crate::price_fetcher::get_mps_prices(
                                             &[multipool_address],
-                                            &self.app_state.provider,
+                                            &chain.provider,
                                             block.number,
                                         )
                                         .await?
@@ -169,18 +184,22 @@ impl<'a, P: Provider + Clone> Processor<Transaction<'a, Postgres>> for PgEventPr
                                     }
                                 }
                                 MultipoolEvents::MultipoolOwnerChange(e) => {
-                                    OwnerChange::new(e.newOwner, multipool_address)
+                                    OwnerChange::new(e.newOwner, chain_id, multipool_address)
                                         .apply_on_storage(&mut **db_tx)
                                         .await?
                                 }
                                 MultipoolEvents::AssetChange(e) => {
                                     if e.asset == multipool_address {
-                                        AssetChange::new(e.quantity.to(), multipool_address)
-                                            .apply_on_storage(&mut **db_tx)
-                                            .await?;
+                                        AssetChange::new(
+                                            e.quantity.to(),
+                                            chain_id,
+                                            multipool_address,
+                                        )
+                                        .apply_on_storage(&mut **db_tx)
+                                        .await?;
                                         self.app_state
                                             .stats_cache
-                                            .get_mut(&e.asset)
+                                            .get_mut(&(chain_id, e.asset))
                                             .expect("Multipool should present when having events")
                                             .insert_total_supply(e.quantity.to());
                                     }
@@ -192,6 +211,16 @@ impl<'a, P: Provider + Clone> Processor<Transaction<'a, Postgres>> for PgEventPr
                 }
             }
         }
+        // indexer only reads finalized blocks, checkpoints of state history are
+        // written up to this block
+        sqlx::query(
+            "INSERT INTO indexed_blocks(chain_id, block_number) VALUES ($1, $2)
+            ON CONFLICT (chain_id) DO UPDATE SET block_number = EXCLUDED.block_number",
+        )
+        .bind::<i64>(chain_id as i64)
+        .bind::<i64>(new_saved_block as i64)
+        .execute(&mut **db_tx)
+        .await?;
         LOGS_COMMITEMENT_DURATION_MS.record(commitement_timer.elapsed().as_millis() as u64, &[]);
         Ok(())
     }
@@ -227,7 +256,7 @@ impl MultipoolCreated {
                 symbol,
                 owner
             ) VALUES ($1,$2,$3,$4,$5)
-            ON CONFLICT (multipool) DO UPDATE
+            ON CONFLICT (chain_id, multipool) DO UPDATE
             SET
                 name = $3,
                 symbol = $4;
@@ -252,13 +281,15 @@ impl MultipoolCreated {
 
 pub struct OwnerChange {
     new_owner: Address,
+    chain_id: u64,
     multipool: Address,
 }
 
 impl OwnerChange {
-    fn new(new_owner: Address, multipool: Address) -> Self {
+    fn new(new_owner: Address, chain_id: u64, multipool: Address) -> Self {
         Self {
             new_owner,
+            chain_id,
             multipool,
         }
     }
@@ -272,10 +303,11 @@ impl OwnerChange {
             "
             UPDATE multipools
             SET owner = $1
-            WHERE multipool = $2;
+            WHERE chain_id = $2 and multipool = $3;
         ",
         )
         .bind::<[u8; 20]>(self.new_owner.into())
+        .bind::<i64>(self.chain_id.try_into()?)
         .bind::<[u8; 20]>(self.multipool.into())
         .execute(executor)
         .await
@@ -291,13 +323,15 @@ impl OwnerChange {
 
 pub struct AssetChange {
     total_supply: u128,
+    chain_id: u64,
     multipool: Address,
 }
 
 impl AssetChange {
-    fn new(total_supply: u128, multipool: Address) -> Self {
+    fn new(total_supply: u128, chain_id: u64, multipool: Address) -> Self {
         Self {
             total_supply,
+            chain_id,
             multipool,
         }
     }
@@ -311,10 +345,11 @@ impl AssetChange {
             "
             UPDATE multipools
             SET total_supply = $1::NUMERIC
-            WHERE multipool = $2;
+            WHERE chain_id = $2 and multipool = $3;
         ",
         )
         .bind::<String>(self.total_supply.to_string())
+        .bind::<i64>(self.chain_id.try_into()?)
         .bind::<[u8; 20]>(self.multipool.into())
         .execute(executor)
         .await
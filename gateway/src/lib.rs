@@ -2,19 +2,20 @@ use std::env;
 use std::sync::Arc;
 use std::time::Duration;
 
-use alloy::providers::ProviderBuilder;
+use alloy::providers::{Provider, ProviderBuilder};
+use alloy::rpc::client::ClientBuilder;
 use alloy::transports::http::reqwest::Url;
-use alloy::{primitives::Address, rpc::client::ClientBuilder};
 use backend_service::ServiceData;
 use cache::AppState;
 use indexer1::Indexer;
 use multipool::Multipool;
+use multipool_types::deployments::{Contract, Deployments, DEFAULT_DEPLOYMENTS_PATH};
+use multipool_types::resolutions::Resolutions;
 use price_fetcher::PriceFetcherConfig;
-use routes::{charts, portfolio};
+use routes::{charts, portfolio, stream};
 use serde::Deserialize;
 use tower_http::cors::CorsLayer;
 
-//TODO: add arwave mathcnism
 //TOOD: add oracle
 
 use anyhow::Context;
@@ -22,9 +23,12 @@ use axum::{
     routing::{get, post},
     Router,
 };
+use futures::future::{select_all, FutureExt, LocalBoxFuture, TryFutureExt};
+use std::future::IntoFuture;
 
 use crate::layers::api_metrics::OtelMetricsLayer;
 
+pub mod backfill;
 pub mod cache;
 pub mod error;
 pub mod indexer;
@@ -32,6 +36,7 @@ pub mod layers;
 pub mod price_fetcher;
 pub mod routes;
 pub mod service;
+pub mod uploader;
 
 #[derive(Deserialize)]
 pub struct IndexerConfig {
@@ -59,15 +64,27 @@ pub struct ArweaveConfig {
 }
 
 #[derive(Deserialize)]
-pub struct GatewayService {
+pub struct ChainConfig {
     price_fetcher: PriceFetcherConfig,
     indexer: IndexerConfig,
+    rpc: RpcConfig,
+}
+
+fn default_deployments() -> String {
+    DEFAULT_DEPLOYMENTS_PATH.into()
+}
+
+#[derive(Deserialize)]
+pub struct GatewayService {
+    chains: Vec<ChainConfig>,
     database: Option<DbConfig>,
-    // TODO: make optional when use create2 to deploy factory
-    factory: Address,
     bind_to: Option<String>,
-    rpc: RpcConfig,
     arweave: Option<ArweaveConfig>,
+    #[serde(default)]
+    resolutions: Resolutions,
+    /// Registry of contract addresses of all served chains
+    #[serde(default = "default_deployments")]
+    deployments: String,
 }
 
 impl ServiceData for GatewayService {
@@ -80,37 +97,57 @@ impl ServiceData for GatewayService {
             env::var(&database_env_key).context(format!("{} must be set", database_env_key))?;
         let pool = sqlx::PgPool::connect(&database_url).await?;
 
-        let retry_layer =
-            layers::backoff::RetryBackoffLayer::new(self.rpc.max_retry, self.rpc.backoff_ms);
-
-        let http_client = ClientBuilder::default()
-            .layer(retry_layer)
-            .http(Url::parse(&self.rpc.http_url).context("Failed to parse http rpc url")?);
+        let deployments = Deployments::from_file(&self.deployments)?;
+        let mut chain_ids = Vec::new();
+        let mut providers = Vec::new();
+        for chain in self.chains.iter() {
+            let retry_layer =
+                layers::backoff::RetryBackoffLayer::new(chain.rpc.max_retry, chain.rpc.backoff_ms);
+
+            let http_client = ClientBuilder::default()
+                .layer(retry_layer)
+                .http(Url::parse(&chain.rpc.http_url).context("Failed to parse http rpc url")?);
+
+            // Create a new provider with the client.
+            let provider_http = ProviderBuilder::new().on_client(http_client);
+            let chain_id = provider_http.get_chain_id().await?;
+            let deployment = deployments.get(chain_id)?;
+            deployment.validate(&provider_http).await?;
+            let factory = deployment.get(Contract::MultipoolFactory)?;
+
+            providers.push((chain_id, provider_http, factory));
+            chain_ids.push(chain_id);
+        }
 
-        // Create a new provider with the client.
-        let provider_http = ProviderBuilder::new().on_client(http_client);
+        backfill::sync_resolutions(&pool, &self.resolutions).await?;
 
         let app_state = Arc::new(
-            AppState::initialize(pool.clone(), provider_http, self.factory, self.arweave)
+            AppState::initialize(pool.clone(), providers, self.resolutions, self.arweave)
                 .await
                 .unwrap(),
         );
 
-        let price_fetcher_handle = price_fetcher::run(app_state.clone(), self.price_fetcher);
+        let mut tasks: Vec<LocalBoxFuture<anyhow::Result<()>>> = Vec::new();
+        if app_state.arwave.is_some() {
+            tasks.push(uploader::run(app_state.clone()).boxed_local());
+        }
+        for (chain, chain_id) in self.chains.into_iter().zip(chain_ids) {
+            tasks.push(
+                price_fetcher::run(app_state.clone(), chain_id, chain.price_fetcher).boxed_local(),
+            );
 
-        let indexer_handle = {
             let processor = indexer::PgEventProcessor {
                 app_state: app_state.clone(),
             };
             let pool = pool.clone();
 
-            Indexer::builder()
+            let indexer = Indexer::builder()
                 .pg_storage(pool)
-                .http_rpc_url(self.rpc.http_url.parse()?)
-                .ws_rpc_url_opt(self.rpc.ws_url.map(|url| url.parse()).transpose()?)
+                .http_rpc_url(chain.rpc.http_url.parse()?)
+                .ws_rpc_url_opt(chain.rpc.ws_url.map(|url| url.parse()).transpose()?)
                 .block_range_limit(999)
-                .fetch_interval(Duration::from_millis(self.indexer.fetch_interval_ms))
-                .filter(Multipool::filter().from_block(self.indexer.from_block))
+                .fetch_interval(Duration::from_millis(chain.indexer.fetch_interval_ms))
+                .filter(Multipool::filter().from_block(chain.indexer.from_block))
                 .set_processor(processor)
                 .build()
                 .await
@@ -118,9 +155,9 @@ impl ServiceData for GatewayService {
                     println!("indexer err {e:?}");
                     e
                 })
-                .context("Failed to build indexer")?
-                .run()
-        };
+                .context("Failed to build indexer")?;
+            tasks.push(indexer.run().boxed_local());
+        }
 
         let app = Router::new()
             .route("/portfolio/candles", get(charts::candles))
@@ -128,22 +165,25 @@ impl ServiceData for GatewayService {
             .route("/portfolio/list", get(portfolio::list))
             .route("/portfolio/create", post(portfolio::create))
             .route("/portfolio/metadata", get(portfolio::metadata))
+            .route("/portfolio/state", get(portfolio::state))
             .route(
                 "/account/positions_history",
                 get(portfolio::positions_history),
             )
             .route("/account/positions", get(portfolio::positions))
+            .route("/ws", get(stream::ws))
             .layer(OtelMetricsLayer)
             .layer(CorsLayer::permissive())
             .with_state(app_state);
 
         let listener =
             tokio::net::TcpListener::bind(self.bind_to.unwrap_or("0.0.0.0:8080".into())).await?;
-        let axum = axum::serve(listener, app);
-        tokio::select! {
-            axum = axum => axum.map_err(Into::into),
-            i = indexer_handle => i,
-            p = price_fetcher_handle => p,
-        }
+        tasks.push(
+            axum::serve(listener, app)
+                .into_future()
+                .map_err(Into::into)
+                .boxed_local(),
+        );
+        select_all(tasks).await.0
     }
 }
@@ -5,6 +5,7 @@ use alloy::eips::BlockNumberOrTag;
 use alloy::primitives::{Address, U256};
 use alloy::providers::{Provider, MULTICALL3_ADDRESS};
 use alloy::sol_types::SolCall;
+use anyhow::anyhow;
 use backend_service::logging::LogTarget;
 use backend_service::KeyValue;
 use bigdecimal::{BigDecimal, Num};
@@ -17,7 +18,7 @@ use sqlx::Row;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-use crate::cache::AppState;
+use crate::cache::{AppState, CandleUpdate};
 
 #[derive(Deserialize)]
 pub struct PriceFetcherConfig {
@@ -28,11 +29,16 @@ pub struct PriceFetcherConfig {
 
 pub async fn run<P: Provider>(
     app_state: Arc<AppState<P>>,
+    chain_id: u64,
     config: PriceFetcherConfig,
 ) -> anyhow::Result<()> {
     let pool = &app_state.connection;
-    let provider = &app_state.provider;
-    let chain_id = provider.get_chain_id().await? as i64;
+    let chain = app_state
+        .chains
+        .get(&chain_id)
+        .ok_or(anyhow!("Chain {chain_id} is not configured"))?;
+    let provider = &chain.provider;
+    let chain_id = chain_id as i64;
 
     //TODO: do something with block competition
     let mut latest_block = provider
@@ -55,14 +61,15 @@ pub async fn run<P: Provider>(
     loop {
         if indexing_block <= latest_block {
             let mut transaction = connection.begin().await?;
-            let multipools = app_state.multipools.read().unwrap().clone();
+            let multipools = chain.multipools.read().unwrap().clone();
 
             for chunk in multipools.chunks(config.multipools_in_chunk as usize) {
                 let (prices, ts) = get_mps_prices(chunk, &provider, indexing_block).await?;
                 for (p, mp) in prices.into_iter().zip(chunk) {
                     if let Some(price) = p {
                         let timer = Instant::now();
-                        sqlx::query("call insert_price($1,$2,$3)")
+                        sqlx::query("call insert_price($1,$2,$3,$4)")
+                            .bind(chain_id)
                             .bind::<[u8; 20]>(*mp.0)
                             .bind::<i64>(ts as i64)
                             .bind::<BigDecimal>(BigDecimal::from_str_radix(
@@ -76,15 +83,24 @@ pub async fn run<P: Provider>(
                             &[KeyValue::new("query_name", "insert_price")],
                         );
 
-                        app_state
+                        let candles = app_state
                             .stats_cache
-                            .get_mut(mp)
+                            .get_mut(&(chain_id as u64, *mp))
                             .unwrap()
                             .insert_price(price, ts);
+                        for (resolution, candle) in candles {
+                            // fails only when nobody is subscribed
+                            let _ = app_state.candle_updates.send(CandleUpdate {
+                                chain_id: chain_id as u64,
+                                multipool: *mp,
+                                resolution,
+                                candle,
+                            });
+                        }
                     }
                 }
             }
-            PRICE_FETCHER_HEIGHT.record(indexing_block, &[]);
+            PRICE_FETCHER_HEIGHT.record(indexing_block, &[KeyValue::new("chain_id", chain_id)]);
 
             sqlx::query(
                 "INSERT INTO price_indexes(chain_id, block_number) VALUES ($1, $2) ON CONFLICT (chain_id) DO UPDATE SET block_number = $2"
@@ -170,4 +186,4 @@ pub async fn get_mps_prices<P: Provider>(
         .collect();
 
     Ok((prices, ts.to()))
-}
\ No newline at end of file
+}
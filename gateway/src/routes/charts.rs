@@ -6,12 +6,13 @@ use serde::Deserialize;
 use std::sync::Arc;
 
 use crate::{
-    cache::{try_resolution_to_index, Candle, DbCandleSmall, Stats},
+    cache::{Candle, DbCandleSmall, Stats},
     error::{AppError, AppResult},
 };
 
 #[derive(Deserialize)]
 pub struct HistoryRequest {
+    chain_id: u64,
     t: Option<u64>,
     c: Option<usize>,
     r: i32,
@@ -22,15 +23,18 @@ pub async fn candles<P: Provider>(
     Query(query): Query<HistoryRequest>,
     State(state): State<Arc<crate::AppState<P>>>,
 ) -> AppResult<MsgPack<Vec<Candle>>> {
-    let resolution_index = try_resolution_to_index(query.r).ok_or(AppError::InvalidResolution)?;
+    let resolution_index = state
+        .resolutions
+        .index_of(query.r)
+        .ok_or(AppError::InvalidResolution)?;
 
     let candles = state
         .stats_cache
-        .get(&query.m)
+        .get(&(query.chain_id, query.m))
         .ok_or(AppError::InvalidMpAddress)?
         .value()
         .candles[resolution_index]
-        .clone();
+        .to_vec();
 
     let (ts, countback) = match (query.t, query.c) {
         (Some(ts), Some(countback)) => (ts, countback),
@@ -51,6 +55,7 @@ pub async fn candles<P: Provider>(
               ts <= $1
               AND resolution = $2
               AND multipool = $3
+              AND chain_id = $5
           ORDER BY
               ts DESC
           LIMIT $4;",
@@ -59,6 +64,7 @@ pub async fn candles<P: Provider>(
     .bind(query.r)
     .bind::<&[u8]>(query.m.as_slice())
     .bind(countback as i64)
+    .bind(query.chain_id as i64)
     .fetch_all(
         &mut *state
             .connection
@@ -77,6 +83,7 @@ pub async fn candles<P: Provider>(
 
 #[derive(Deserialize)]
 pub struct StatsRequest {
+    chain_id: u64,
     m: Address,
 }
 
@@ -86,7 +93,7 @@ pub async fn stats<P: Provider>(
 ) -> AppResult<MsgPack<Stats>> {
     Ok(state
         .stats_cache
-        .get(&query.m)
+        .get(&(query.chain_id, query.m))
         .ok_or(AppError::InvalidMpAddress)?
         .value()
         .stats
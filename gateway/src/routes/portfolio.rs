@@ -1,39 +1,55 @@
 use crate::{
     error::{AppError, AppResult},
     service::metrics::DATABASE_REQUEST_DURATION_MS,
+    uploader::MAX_UPLOAD_ATTEMPTS,
 };
 use alloy::{
     primitives::{Address, B256},
     providers::Provider,
-    rpc::types::Filter,
-    sol_types::{SolEvent, SolEventInterface},
 };
-//use arweave_client::{Rpc, Tag, Transaction, Uploader};
 use axum::extract::{Query, State};
 use axum_msgpack::MsgPack;
 use backend_service::KeyValue;
 use bigdecimal::BigDecimal;
-use multipool_types::MultipoolFactory::{self, MultipoolFactoryEvents};
+use dashmap::Entry;
+use multipool::Multipool;
+use multipool_storage::pg::history;
 use serde::Serializer;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
-use std::{sync::Arc, time::Instant};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// Metadata of a multipool can be changed once per this interval
+pub const METADATA_UPDATE_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Deserialize)]
+pub struct ChainRequest {
+    chain_id: u64,
+}
 
-pub async fn list<P: Provider>(State(state): State<Arc<crate::AppState<P>>>) -> MsgPack<Value> {
-    serde_json::to_value(
+pub async fn list<P: Provider>(
+    Query(query): Query<ChainRequest>,
+    State(state): State<Arc<crate::AppState<P>>>,
+) -> AppResult<MsgPack<Value>> {
+    state.chain(query.chain_id)?;
+    Ok(serde_json::to_value(
         state
             .stats_cache
             .iter()
+            .filter(|r| r.key().0 == query.chain_id)
             .map(|r| {
                 json!({
-                "a": r.key(),
+                "a": r.key().1,
                 "s": r.value().stats,})
             })
             .collect::<Vec<Value>>(),
     )
     .unwrap()
-    .into()
+    .into())
 }
 
 #[derive(Deserialize)]
@@ -54,75 +70,72 @@ pub struct CreateRequest {
 }
 
 pub async fn create<P: Provider>(
+    Query(query): Query<ChainRequest>,
     State(state): State<Arc<crate::AppState<P>>>,
     MsgPack(form): MsgPack<CreateRequest>,
 ) -> AppResult<MsgPack<()>> {
-    let multipool = state.factory.create2(form.salt, form.init_code_hash);
+    let chain = state.chain(query.chain_id)?;
+    let multipool = chain.factory.create2(form.salt, form.init_code_hash);
+
+    if form.description.len() > 500
+        || form.name.len() > 25
+        || form.symbol.len() > 10
+        || form.logo_bytes.len() > 1024 * 100
+    {
+        Err(AppError::InvalidPayloadSize)?;
+    }
 
-    //TODO: maybe use indexer <-> this route method that
-    //is going to be sending data to arwave
-    // also need to check fees so probably transaction hash is neserarry
-    // THIS ONE TO BE REMOVED AND LOGS TO BE USED
-    let code = state
+    let code = chain
         .provider
         .get_code_at(multipool)
         .latest()
         .await
         .map_err(|_| AppError::FailedToGetCode)?;
+    if code.is_empty() {
+        Err(AppError::MultipoolNotCreated)?;
+    }
 
-    //TODO: only insert if there is no logo set before
-    //TODO: same for arwave
-
-    // let current_block = state.provider.get_block_number().await?;
-    // let block_countback = 1000;
-
-    // let filter = Filter::new()
-    //     .event(MultipoolFactory::ProtocolFeeSent::SIGNATURE)
-    //     .topic1(multipool)
-    //     .from_block(current_block - block_countback)
-    //     .address(state.factory);
-    // let events = state.provider.get_logs(&filter).await?;
-
-    // let (fee_receiver, fee_amount) = events
-    //     .into_iter()
-    //     .find_map(
-    //         |i| match MultipoolFactoryEvents::decode_log(&i.into()).ok()?.data {
-    //             MultipoolFactoryEvents::ProtocolFeeSent(e) => Some((e.feeReceiver, e.amount)),
-    //             _ => None,
-    //         },
-    //     )
-    //     .ok_or(AppError::MultipoolNotCreated)?;
-
-    if code.is_empty()
-        && form.description.len() > 500
-        && form.name.len() > 25
-        && form.symbol.len() > 10
-        && form.logo_bytes.len() > 1024 * 100
-    {
-        Err(AppError::InvalidPayloadSize)?;
+    // every change of metadata is uploaded to arweave again
+    let now = Instant::now();
+    match state.metadata_updates.entry((query.chain_id, multipool)) {
+        Entry::Occupied(updated)
+            if now.duration_since(*updated.get()) < METADATA_UPDATE_INTERVAL =>
+        {
+            Err(AppError::TooManyRequests)?
+        }
+        entry => {
+            entry.insert(now);
+        }
     }
 
     let timer = Instant::now();
-    sqlx::query(
+    let result = sqlx::query(
         "INSERT INTO
             multipools(multipool, chain_id, owner, name, symbol, description, logo)
         VALUES
             ($1,$2,$3,$4,$5,$6,$7)
         ON CONFLICT
-            (multipool)
+            (chain_id, multipool)
         DO UPDATE SET
             logo = $7,
-            description = $6;
+            description = $6,
+            arweave_tx = NULL,
+            arweave_signed_tx = NULL,
+            arweave_cursor = NULL,
+            arweave_attempts = 0
+        WHERE
+            multipools.logo IS DISTINCT FROM EXCLUDED.logo
+            or multipools.description IS DISTINCT FROM EXCLUDED.description;
         ",
     )
     .bind::<[u8; 20]>(multipool.into())
-    .bind(state.chain_id as i64)
+    .bind(query.chain_id as i64)
     .bind::<[u8; 20]>(Address::ZERO.into())
     .bind(form.name)
     .bind(form.symbol)
     .bind(form.description)
     .bind(form.logo_bytes)
-    .fetch_all(
+    .execute(
         &mut *state
             .connection
             .acquire()
@@ -135,60 +148,12 @@ pub async fn create<P: Provider>(
         &[KeyValue::new("query_name", "mp_dnl")],
     );
 
-    Ok(().into())
-    //let name_bytes = name.into_bytes();
-    //let desc_offset = name_bytes.len();
-    //let desc_bytes = description.into_bytes();
-    //let logo_offset = desc_offset + desc_bytes.len();
-    // let data = name_bytes
-    //     .into_iter()
-    //     .chain(desc_bytes)
-    //     .chain(logo)
-    //     .collect();
-
-    // let mut tx = Transaction::builder(state.arweave_rpc.clone())
-    //     .tags(vec![
-    //         Tag {
-    //             name: "Content-Type".to_string(),
-    //             value: "MpData".to_string(),
-    //         },
-    //         Tag {
-    //             name: "Address".to_string(),
-    //             value: multipool_address.to_string(),
-    //         },
-    //         Tag {
-    //             name: "ChainId".to_string(),
-    //             value: chain_id.to_string(),
-    //         },
-    //         Tag {
-    //             name: "Symbol".to_string(),
-    //             value: symbol.to_owned(),
-    //         },
-    //         Tag {
-    //             name: "DescriptionOffset".to_string(),
-    //             value: desc_offset.to_string(),
-    //         },
-    //         Tag {
-    //             name: "LogoOffset".to_string(),
-    //             value: logo_offset.to_string(),
-    //         },
-    //     ])
-    //     .data(data)
-    //     .build()
-    //     .await
-    //     .map_err(stringify)?;
-    // tx.sign(state.arweave_signer.clone()).map_err(stringify)?;
-    // let mut uploader = Uploader::new(state.arweave_rpc.clone(), tx);
-    // uploader.upload_chunks().await.unwrap();
+    // unchanged metadata keeps its upload
+    if let Some(arwave) = state.arwave.as_ref().filter(|_| result.rows_affected() > 0) {
+        arwave.queued.notify_one();
+    }
 
-    //TODO: how to not fkn ddos
-    // sqlx::query("INSERT INTO multipools(logo, description) ")
-    //     .bind::<[u8; 20]>(multipool.into())
-    //     .fetch_all(&mut *state.connection.acquire().await.unwrap())
-    //     .await
-    //     .unwrap()
-    //     .into()
-    //TODO: add limits on name, symbol, description + logo size
+    Ok(().into())
 }
 
 //#[derive(Deserialize)]
@@ -206,22 +171,78 @@ pub struct DbMetadata {
     logo: Vec<u8>,
     #[serde(rename = "d")]
     description: String,
+    #[serde(rename = "t")]
+    arweave_tx: Option<String>,
+    #[serde(rename = "as")]
+    arweave_status: String,
 }
 
 pub async fn metadata<P: Provider>(
     //Path(multipool): Path<Address>,
+    Query(query): Query<ChainRequest>,
     State(state): State<Arc<crate::AppState<P>>>,
 ) -> AppResult<MsgPack<Vec<DbMetadata>>> {
-    sqlx::query_as("SELECT multipool, logo, description FROM multipools WHERE logo IS NOT NULL and description IS NOT NULL")
-        //.bind::<[u8; 20]>(multipool.into())
-        .fetch_all(&mut *state.connection.acquire().await.unwrap())
-        .await
-        .map(Into::into)
-        .map_err(Into::into)
+    sqlx::query_as(
+        "
+        SELECT
+            multipool,
+            logo,
+            description,
+            arweave_tx,
+            CASE
+                WHEN arweave_tx IS NOT NULL THEN 'uploaded'
+                WHEN arweave_attempts >= $2 THEN 'failed'
+                ELSE 'pending'
+            END as arweave_status
+        FROM
+            multipools
+        WHERE
+            chain_id = $1
+            and logo IS NOT NULL
+            and description IS NOT NULL",
+    )
+    .bind::<i64>(query.chain_id as i64)
+    .bind(MAX_UPLOAD_ATTEMPTS)
+    //.bind::<[u8; 20]>(multipool.into())
+    .fetch_all(&mut *state.connection.acquire().await.unwrap())
+    .await
+    .map(Into::into)
+    .map_err(Into::into)
+}
+
+#[derive(Deserialize)]
+pub struct StateRequest {
+    chain_id: u64,
+    #[serde(rename = "m")]
+    multipool: Address,
+    #[serde(rename = "b")]
+    block_number: u64,
+}
+
+pub async fn state<P: Provider>(
+    Query(query): Query<StateRequest>,
+    State(state): State<Arc<crate::AppState<P>>>,
+) -> AppResult<MsgPack<Multipool>> {
+    state.chain(query.chain_id)?;
+    let timer = Instant::now();
+    let multipool = history::multipool_at(
+        &state.connection,
+        query.chain_id,
+        query.multipool,
+        query.block_number,
+    )
+    .await?
+    .ok_or(AppError::MultipoolNotCreated)?;
+    DATABASE_REQUEST_DURATION_MS.record(
+        timer.elapsed().as_millis() as u64,
+        &[KeyValue::new("query_name", "mp_state")],
+    );
+    Ok(multipool.into())
 }
 
 #[derive(Deserialize)]
 pub struct PositionsRequest {
+    chain_id: u64,
     #[serde(rename = "a")]
     account: Address,
 }
@@ -231,7 +252,7 @@ pub async fn positions<P: Provider>(
     State(state): State<Arc<crate::AppState<P>>>,
 ) -> AppResult<MsgPack<Vec<DbPositions>>> {
     sqlx::query_as("SELECT * FROM positions WHERE chain_id = $1 and account = $2")
-        .bind::<i64>(state.chain_id as i64)
+        .bind::<i64>(query.chain_id as i64)
         .bind::<[u8; 20]>(query.account.into())
         .fetch_all(&mut *state.connection.acquire().await.unwrap())
         .await
@@ -256,6 +277,7 @@ pub struct DbPositions {
 
 #[derive(Deserialize)]
 pub struct PositionsHistoryRequest {
+    chain_id: u64,
     #[serde(rename = "a")]
     account: Address,
 }
@@ -265,7 +287,7 @@ pub async fn positions_history<P: Provider>(
     State(state): State<Arc<crate::AppState<P>>>,
 ) -> AppResult<MsgPack<Vec<DbPositionsHistory>>> {
     sqlx::query_as("SELECT * FROM positions_history WHERE chain_id = $1 and account = $2")
-        .bind::<i64>(state.chain_id as i64)
+        .bind::<i64>(query.chain_id as i64)
         .bind::<[u8; 20]>(query.account.into())
         .fetch_all(&mut *state.connection.acquire().await.unwrap())
         .await
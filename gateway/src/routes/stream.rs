@@ -0,0 +1,82 @@
+use std::{collections::HashSet, sync::Arc};
+
+use alloy::{primitives::Address, providers::Provider};
+use axum::{
+    extract::{
+        ws::{Message, WebSocket},
+        State, WebSocketUpgrade,
+    },
+    response::Response,
+};
+use backend_service::logging::LogTarget;
+use serde::Deserialize;
+use serde_json::json;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::service::log_target::GatewayTarget::Api;
+
+#[derive(Deserialize)]
+pub struct SubscriptionRequest {
+    chain_id: u64,
+    m: Address,
+    r: i32,
+    // unsubscribe from the pair instead
+    #[serde(default)]
+    u: bool,
+}
+
+/// Streams candle updates of subscribed `(multipool, resolution)` pairs. Client sends
+/// msgpack encoded `SubscriptionRequest` and receives `CandleUpdate` messages.
+pub async fn ws<P: Provider + 'static>(
+    ws: WebSocketUpgrade,
+    State(state): State<Arc<crate::AppState<P>>>,
+) -> Response {
+    ws.on_upgrade(move |socket| handle_socket(socket, state))
+}
+
+async fn handle_socket<P: Provider>(mut socket: WebSocket, state: Arc<crate::AppState<P>>) {
+    let mut updates = state.candle_updates.subscribe();
+    let mut subscriptions = HashSet::<(u64, Address, i32)>::new();
+
+    loop {
+        tokio::select! {
+            message = socket.recv() => match message {
+                Some(Ok(Message::Binary(data))) => {
+                    let request = match rmp_serde::from_slice::<SubscriptionRequest>(&data) {
+                        Ok(request) if state.resolutions.index_of(request.r).is_some() => request,
+                        _ => return,
+                    };
+                    let key = (request.chain_id, request.m, request.r);
+                    if request.u {
+                        subscriptions.remove(&key);
+                    } else {
+                        subscriptions.insert(key);
+                    }
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => (),
+            },
+            update = updates.recv() => match update {
+                Ok(update) => {
+                    let key = (update.chain_id, update.multipool, update.resolution);
+                    if !subscriptions.contains(&key) {
+                        continue;
+                    }
+                    let data = rmp_serde::to_vec_named(&update).expect("Candle is serializable");
+                    if socket.send(Message::Binary(data.into())).await.is_err() {
+                        return;
+                    }
+                }
+                // slow client misses intermediate updates, next one carries latest state
+                Err(RecvError::Lagged(skipped)) => {
+                    Api.info(json!({
+                        "m": "stream subscriber lagged",
+                        "s": skipped,
+                    }))
+                    .log();
+                }
+                Err(RecvError::Closed) => return,
+            },
+        }
+    }
+}
@@ -2,6 +2,7 @@ use backend_service::logging::LogTarget;
 
 pub enum GatewayTarget {
     Api,
+    Arweave,
     Indexer,
     PriceFetcher,
     Rpc,
@@ -12,6 +13,7 @@ impl LogTarget for GatewayTarget {
         use GatewayTarget::*;
         match self {
             Api => "api",
+            Arweave => "arweave",
             Indexer => "indexer",
             PriceFetcher => "price-fetcher",
             Rpc => "rpc",
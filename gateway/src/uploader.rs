@@ -0,0 +1,236 @@
+use std::{sync::Arc, time::Duration};
+
+use alloy::{primitives::Address, providers::Provider};
+use anyhow::{ensure, Context, Result};
+use arweave_client::{Tag, Transaction, UploadCursor, Uploader};
+use backend_service::logging::LogTarget;
+use serde_json::{from_value, json, to_value, Value};
+use sqlx::PgPool;
+
+use crate::{
+    cache::{AppState, ArwaveState},
+    service::log_target::GatewayTarget::Arweave,
+};
+
+/// Multipool metadata is not uploaded anymore after this many failed jobs
+pub const MAX_UPLOAD_ATTEMPTS: i32 = 5;
+/// Pending uploads are rechecked at least this often
+pub const UPLOAD_INTERVAL: Duration = Duration::from_secs(60);
+const UPLOADS_IN_CHUNK: i64 = 10;
+/// Confirmation is awaited this long after upload, unconfirmed uploads are
+/// checked again in the next round
+const CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(30);
+const CONFIRMATION_POLL: Duration = Duration::from_secs(10);
+
+#[derive(sqlx::FromRow, Debug, PartialEq, Eq)]
+pub struct UploadJob {
+    pub multipool: [u8; 20],
+    pub chain_id: i64,
+    pub name: String,
+    pub symbol: String,
+    pub description: String,
+    pub logo: Vec<u8>,
+    /// Signed transaction of started upload
+    pub signed_tx: Option<Value>,
+    pub cursor: Option<Value>,
+}
+
+impl UploadJob {
+    /// Returns tags and data of `MpData` transaction. Data is name, description
+    /// and logo concatenated, offsets of the last two are passed in tags.
+    pub fn payload(&self) -> (Vec<Tag>, Vec<u8>) {
+        let desc_offset = self.name.len();
+        let logo_offset = desc_offset + self.description.len();
+        let data = self
+            .name
+            .bytes()
+            .chain(self.description.bytes())
+            .chain(self.logo.iter().copied())
+            .collect();
+        let tag = |name: &str, value: String| Tag {
+            name: name.to_string(),
+            value,
+        };
+        let tags = vec![
+            tag("Content-Type", "MpData".to_string()),
+            tag("Address", Address::from(self.multipool).to_string()),
+            tag("ChainId", self.chain_id.to_string()),
+            tag("Symbol", self.symbol.clone()),
+            tag("DescriptionOffset", desc_offset.to_string()),
+            tag("LogoOffset", logo_offset.to_string()),
+        ];
+        (tags, data)
+    }
+
+    /// Returns signed transaction and cursor of started upload unless the
+    /// transaction was dropped
+    async fn started(&self, arwave: &ArwaveState) -> Result<Option<(Transaction, UploadCursor)>> {
+        let Some(signed_tx) = &self.signed_tx else {
+            return Ok(None);
+        };
+        let mut tx: Transaction = from_value(signed_tx.clone())?;
+        if arwave.rpc.is_dropped(&tx).await? {
+            Arweave
+                .info(json!({
+                    "m": "metadata upload dropped",
+                    "mp": Address::from(self.multipool),
+                    "tx": tx.id,
+                }))
+                .log();
+            return Ok(None);
+        }
+        tx.prepare_chunks(tx.data.clone());
+        let cursor = match &self.cursor {
+            Some(cursor) => from_value(cursor.clone())?,
+            None => UploadCursor {
+                tx_id: tx.id.clone(),
+                ..Default::default()
+            },
+        };
+        Ok(Some((tx, cursor)))
+    }
+
+    /// Signs a new transaction and stores it before anything is posted
+    async fn sign(&self, arwave: &ArwaveState, pool: &PgPool) -> Result<Transaction> {
+        let (tags, data) = self.payload();
+        let mut tx = Transaction::builder(arwave.rpc.clone())
+            .tags(tags)
+            .data(data)
+            .build()
+            .await?;
+        tx.sign(arwave.signer.clone())?;
+        // metadata could be replaced while transaction was built
+        let stored = sqlx::query(
+            "UPDATE multipools SET arweave_signed_tx = $2, arweave_cursor = NULL
+            WHERE multipool = $1 and description = $3 and logo = $4 and chain_id = $5",
+        )
+        .bind::<[u8; 20]>(self.multipool)
+        .bind(to_value(&tx)?)
+        .bind(&self.description)
+        .bind(&self.logo)
+        .bind(self.chain_id)
+        .execute(pool)
+        .await?;
+        ensure!(stored.rows_affected() == 1, "Metadata was replaced");
+        Ok(tx)
+    }
+
+    /// Uploads metadata continuing started upload, failed uploads are retried
+    /// with the same transaction until it is dropped. Returns transaction id once
+    /// it is confirmed or none if it is not confirmed yet.
+    async fn upload(&self, arwave: &ArwaveState, pool: &PgPool) -> Result<Option<String>> {
+        let mut uploader = match self.started(arwave).await? {
+            Some((tx, cursor)) => Uploader::new(arwave.rpc.clone(), tx).resume(cursor)?,
+            None => Uploader::new(arwave.rpc.clone(), self.sign(arwave, pool).await?),
+        };
+
+        let uploaded = uploader.upload_chunks().await;
+        // progress is kept even if upload is interrupted
+        sqlx::query(
+            "UPDATE multipools SET arweave_cursor = $2
+            WHERE multipool = $1 and arweave_signed_tx->>'id' = $3 and chain_id = $4",
+        )
+        .bind::<[u8; 20]>(self.multipool)
+        .bind(to_value(uploader.cursor())?)
+        .bind(uploader.tx_id())
+        .bind(self.chain_id)
+        .execute(pool)
+        .await?;
+        uploaded?;
+
+        match tokio::time::timeout(
+            CONFIRMATION_TIMEOUT,
+            uploader.wait_confirmation(1, CONFIRMATION_POLL),
+        )
+        .await
+        {
+            Ok(confirmation) => {
+                confirmation?;
+                Ok(Some(uploader.tx_id().to_string()))
+            }
+            Err(_) => Ok(None),
+        }
+    }
+}
+
+/// Uploads metadata of multipools that have no confirmed `arweave_tx` yet. Wakes
+/// up on `ArwaveState.queued` notification or every `UPLOAD_INTERVAL`.
+pub async fn run<P: Provider>(app_state: Arc<AppState<P>>) -> Result<()> {
+    let arwave = app_state
+        .arwave
+        .as_ref()
+        .context("Arweave is not configured")?;
+    let pool = &app_state.connection;
+
+    loop {
+        let jobs: Vec<UploadJob> = sqlx::query_as(
+            "
+            SELECT
+                multipool, chain_id, name, symbol, description, logo,
+                arweave_signed_tx as signed_tx, arweave_cursor as cursor
+            FROM
+                multipools
+            WHERE
+                arweave_tx IS NULL
+                and arweave_attempts < $1
+                and name IS NOT NULL
+                and symbol IS NOT NULL
+                and description IS NOT NULL
+                and logo IS NOT NULL
+            ORDER BY arweave_attempts ASC
+            LIMIT $2;",
+        )
+        .bind(MAX_UPLOAD_ATTEMPTS)
+        .bind(UPLOADS_IN_CHUNK)
+        .fetch_all(pool)
+        .await?;
+        let exhausted = (jobs.len() as i64) < UPLOADS_IN_CHUNK;
+
+        for job in jobs {
+            let multipool = Address::from(job.multipool);
+            match job.upload(arwave, pool).await {
+                Ok(None) => {}
+                Ok(Some(tx_id)) => {
+                    // metadata could be replaced while it was uploaded
+                    sqlx::query(
+                        "UPDATE multipools SET arweave_tx = $2
+                        WHERE multipool = $1 and arweave_signed_tx->>'id' = $2 and chain_id = $3",
+                    )
+                    .bind::<[u8; 20]>(job.multipool)
+                    .bind(&tx_id)
+                    .bind(job.chain_id)
+                    .execute(pool)
+                    .await?;
+                    Arweave
+                        .info(json!({
+                            "m": "metadata uploaded",
+                            "mp": multipool,
+                            "tx": tx_id,
+                        }))
+                        .log();
+                }
+                Err(e) => {
+                    sqlx::query(
+                        "UPDATE multipools SET arweave_attempts = arweave_attempts + 1
+                        WHERE multipool = $1 and chain_id = $2",
+                    )
+                    .bind::<[u8; 20]>(job.multipool)
+                    .bind(job.chain_id)
+                    .execute(pool)
+                    .await?;
+                    Arweave
+                        .error(json!({
+                            "m": "metadata upload failed",
+                            "mp": multipool,
+                            "e": e.to_string(),
+                        }))
+                        .log();
+                }
+            }
+        }
+
+        if exhausted {
+            let _ = tokio::time::timeout(UPLOAD_INTERVAL, arwave.queued.notified()).await;
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use std::collections::BTreeMap;
+
+use alloy::primitives::U256;
+use gateway::cache::{Candle, CandleBuffer, MultipoolCache, MAX_BUFFER_SIZE};
+use multipool_types::resolutions::{Resolutions, DAY, HOUR, MINUTE};
+use proptest::prelude::*;
+
+const QUARTER: u64 = 900;
+
+fn align(ts: u64, resolution: u64) -> u64 {
+    ts / resolution * resolution
+}
+
+// straightforward aggregation of every price into candles
+fn model(prices: &[(u64, u64)], resolution: u64) -> Vec<Candle> {
+    let mut candles = BTreeMap::<u64, Candle>::new();
+    for &(price, ts) in prices {
+        let price = U256::from(price);
+        let candle = candles.entry(align(ts, resolution)).or_insert(Candle {
+            ts: align(ts, resolution),
+            open: price,
+            close: price,
+            low: price,
+            hight: price,
+            open_ts: ts,
+            close_ts: ts,
+        });
+        candle.low = candle.low.min(price);
+        candle.hight = candle.hight.max(price);
+        if ts < candle.open_ts {
+            candle.open = price;
+            candle.open_ts = ts;
+        }
+        if ts >= candle.close_ts {
+            candle.close = price;
+            candle.close_ts = ts;
+        }
+    }
+    let skip = candles.len().saturating_sub(MAX_BUFFER_SIZE);
+    candles.into_values().skip(skip).collect()
+}
+
+fn cache(prices: &[(u64, u64)]) -> MultipoolCache {
+    cache_with(prices, &Resolutions::default())
+}
+
+fn cache_with(prices: &[(u64, u64)], resolutions: &Resolutions) -> MultipoolCache {
+    let mut cache = MultipoolCache::new("Test".into(), "TST".into(), resolutions);
+    for &(price, ts) in prices {
+        cache.insert_price(U256::from(price), ts);
+    }
+    cache
+}
+
+#[test]
+fn check_single_price_creates_one_candle() {
+    let cache = cache(&[(10, 65)]);
+    for (buffer, resolution) in cache.candles.iter().zip(Resolutions::default().iter()) {
+        assert_eq!(1, buffer.len());
+        let candle = buffer.get(65).unwrap();
+        assert_eq!(align(65, resolution as u64), candle.ts);
+        assert_eq!(U256::from(10), candle.close);
+    }
+}
+
+#[test]
+fn check_late_price_does_not_override_close() {
+    let cache = cache(&[(10, 60), (30, 100), (20, 70), (5, 61)]);
+    let candle = cache.candles[0].get(60).unwrap();
+    assert_eq!(U256::from(10), candle.open);
+    assert_eq!(U256::from(30), candle.close);
+    assert_eq!(U256::from(5), candle.low);
+    assert_eq!(U256::from(30), candle.hight);
+    assert_eq!(Some(U256::from(30)), cache.get_price(119));
+}
+
+#[test]
+fn check_too_old_price_is_dropped() {
+    let mut buffer = CandleBuffer::new(MINUTE);
+    for i in 1..=MAX_BUFFER_SIZE as u64 {
+        assert!(buffer.insert(U256::from(i), i * 60).is_some());
+    }
+    assert_eq!(None, buffer.insert(U256::from(1), 0));
+    assert_eq!(MAX_BUFFER_SIZE, buffer.len());
+
+    // newer candle evicts the oldest one
+    buffer.insert(U256::from(1), (MAX_BUFFER_SIZE as u64 + 1) * 60);
+    assert_eq!(MAX_BUFFER_SIZE, buffer.len());
+    assert_eq!(None, buffer.get(60));
+}
+
+#[test]
+fn check_configured_resolutions() {
+    let resolutions = Resolutions::new(vec![4 * HOUR, MINUTE, 5 * MINUTE]).unwrap();
+    let cache = cache_with(&[(10, 65), (20, 400)], &resolutions);
+    assert_eq!(3, cache.candles.len());
+    for (buffer, resolution) in cache.candles.iter().zip(resolutions.iter()) {
+        assert_eq!(resolution, buffer.resolution());
+    }
+    assert_eq!(2, cache.candles[0].len());
+    assert_eq!(2, cache.candles[1].len());
+    assert_eq!(1, cache.candles[2].len());
+    // 24h stats do not depend on configured resolutions
+    assert_eq!(U256::from(20), cache.stats.hight_24h);
+    assert_eq!(U256::from(10), cache.stats.open_price);
+}
+
+fn prices(max_ts: u64) -> impl Strategy<Value = Vec<(u64, u64)>> {
+    prop::collection::vec((1..1000u64, 0..max_ts), 1..300)
+}
+
+proptest! {
+    #[test]
+    fn check_buffers_match_model(prices in prop_oneof![prices(2_000), prices(3 * DAY as u64)]) {
+        let cache = cache(&prices);
+        for (buffer, resolution) in cache.candles.iter().zip(Resolutions::default().iter()) {
+            prop_assert_eq!(model(&prices, resolution as u64), buffer.to_vec());
+        }
+    }
+
+    #[test]
+    fn check_stats_match_window(prices in prices(3 * DAY as u64)) {
+        let stats = cache(&prices).stats;
+
+        let latest = prices.iter().map(|(_, ts)| align(*ts, QUARTER)).max().unwrap();
+        let window: Vec<(u64, u64)> = prices
+            .iter()
+            .copied()
+            .filter(|(_, ts)| align(*ts, QUARTER) + DAY as u64 > latest)
+            .collect();
+        let open = window.iter().min_by_key(|(_, ts)| *ts).unwrap().0;
+        let current = window.iter().max_by_key(|(_, ts)| *ts).unwrap().0;
+
+        prop_assert_eq!(U256::from(window.iter().map(|p| p.0).max().unwrap()), stats.hight_24h);
+        prop_assert_eq!(U256::from(window.iter().map(|p| p.0).min().unwrap()), stats.low_24h);
+        prop_assert_eq!(U256::from(open), stats.open_price);
+        prop_assert_eq!(U256::from(current), stats.current_price);
+
+        let minutes = model(&prices, MINUTE as u64);
+        prop_assert_eq!(minutes.last().cloned(), stats.current_candle);
+        prop_assert_eq!(minutes.iter().rev().nth(1).cloned(), stats.previous_candle);
+    }
+}
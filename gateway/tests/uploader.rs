@@ -0,0 +1,36 @@
+use alloy::primitives::address;
+use gateway::uploader::UploadJob;
+
+#[test]
+fn check_metadata_payload() {
+    let multipool = address!("1111111111111111111111111111111111111111");
+    let job = UploadJob {
+        multipool: multipool.into(),
+        chain_id: 10143,
+        name: "Test".into(),
+        symbol: "TST".into(),
+        description: "Описание".into(),
+        logo: vec![0x89, 0x50, 0x4e, 0x47],
+        signed_tx: None,
+        cursor: None,
+    };
+    let (tags, data) = job.payload();
+
+    let tag = |name: &str| {
+        tags.iter()
+            .find(|t| t.name == name)
+            .map(|t| t.value.clone())
+            .unwrap()
+    };
+    assert_eq!("MpData", tag("Content-Type"));
+    assert_eq!(multipool.to_string(), tag("Address"));
+    assert_eq!("10143", tag("ChainId"));
+    assert_eq!("TST", tag("Symbol"));
+
+    // offsets are in bytes, description is not ascii
+    let desc_offset: usize = tag("DescriptionOffset").parse().unwrap();
+    let logo_offset: usize = tag("LogoOffset").parse().unwrap();
+    assert_eq!(b"Test", &data[..desc_offset]);
+    assert_eq!("Описание".as_bytes(), &data[desc_offset..logo_offset]);
+    assert_eq!(job.logo, data[logo_offset..]);
+}
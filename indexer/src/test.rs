@@ -10,7 +10,7 @@ use alloy::{
 use anyhow::Result;
 use indexer1::Indexer;
 use multipool::Multipool;
-use multipool_storage::storage::MultipoolStorage;
+use multipool_storage::{storage::MultipoolStorage, store::SledStore};
 use multipool_types::MultipoolFactory::{self, MultipoolCreationParams};
 
 use crate::processors::{EmbededProcessor, EmptyHookInitialiser};
@@ -71,7 +71,7 @@ async fn happy_path(pool: sqlx::SqlitePool) -> Result<()> {
         .await?;
 
     let db = sled::open("test_db")?;
-    let storage = MultipoolStorage::init(db, EmptyHookInitialiser, *factory.address()).await?;
+    let storage = MultipoolStorage::init(SledStore::new(db)?, EmptyHookInitialiser, *factory.address()).await?;
 
     Indexer::builder()
         .sqlite_storage(pool)
@@ -17,6 +17,23 @@ create table if not exists blocks (
     CONSTRAINT blocks_pkey PRIMARY KEY (chain_id, block_number)
 );
 
+create table if not exists multipool_checkpoints (
+    chain_id            BIGINT  NOT NULL,
+    multipool           ADDRESS NOT NULL,
+    block_number        BIGINT  NOT NULL,
+    data                BYTEA   NOT NULL,
+
+    CONSTRAINT multipool_checkpoints_pkey PRIMARY KEY (chain_id, multipool, block_number)
+);
+
+CREATE INDEX IF NOT EXISTS multipool_checkpoints_block_idx ON multipool_checkpoints (chain_id, block_number);
+
+-- highest block the indexer committed, blocks are indexed once they are finalized
+create table if not exists indexed_blocks (
+    chain_id        BIGINT  PRIMARY KEY,
+    block_number    BIGINT  NOT NULL
+);
+
 create table if not exists price_indexes (
     chain_id        BIGINT  PRIMARY KEY,
     block_number    BIGINT  NOT NULL
@@ -70,7 +87,7 @@ CREATE TABLE IF NOT EXISTS actions_history
 CREATE TABLE IF NOT EXISTS multipools
 (
     chain_id            BIGINT  NOT NULL,
-    multipool           ADDRESS PRIMARY KEY,
+    multipool           ADDRESS NOT NULL,
     owner               ADDRESS NOT NULL,
 
     name                TEXT        NULL,
@@ -78,11 +95,41 @@ CREATE TABLE IF NOT EXISTS multipools
     description         TEXT        NULL,
     logo                BYTEA       NULL,
 
-    total_supply        U256    NOT NULL DEFAULT '0'
+    -- metadata upload, tx id is NULL until upload is confirmed
+    arweave_tx          TEXT        NULL,
+    arweave_attempts    INT     NOT NULL DEFAULT 0,
+    -- signed transaction and UploadCursor of started upload
+    arweave_signed_tx   JSONB       NULL,
+    arweave_cursor      JSONB       NULL,
+
+    total_supply        U256    NOT NULL DEFAULT '0',
+
+    CONSTRAINT multipools_pkey PRIMARY KEY (chain_id, multipool)
 );
 
+-- factories share addresses across chains, so do multipools created by them
+DO $$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM information_schema.key_column_usage
+        WHERE table_name = 'multipools'
+            AND constraint_name = 'multipools_pkey'
+            AND column_name = 'chain_id'
+    ) THEN
+        ALTER TABLE multipools DROP CONSTRAINT multipools_pkey;
+        ALTER TABLE multipools ADD CONSTRAINT multipools_pkey PRIMARY KEY (chain_id, multipool);
+    END IF;
+END
+$$;
+
+ALTER TABLE multipools ADD COLUMN IF NOT EXISTS arweave_tx TEXT NULL;
+ALTER TABLE multipools ADD COLUMN IF NOT EXISTS arweave_attempts INT NOT NULL DEFAULT 0;
+ALTER TABLE multipools ADD COLUMN IF NOT EXISTS arweave_signed_tx JSONB NULL;
+ALTER TABLE multipools ADD COLUMN IF NOT EXISTS arweave_cursor JSONB NULL;
+
 CREATE TABLE IF NOT EXISTS candles
 (
+    chain_id            BIGINT  NOT NULL,
     multipool           ADDRESS NOT NULL,
     resolution          INT     NOT NULL,
     ts                  BIGINT  NOT NULL,
@@ -92,28 +139,66 @@ CREATE TABLE IF NOT EXISTS candles
     low                 U256 NOT NULL,
     hight               U256 NOT NULL,
 
-    CONSTRAINT candles_pkey PRIMARY KEY (multipool, resolution, ts)
+    CONSTRAINT candles_pkey PRIMARY KEY (chain_id, multipool, resolution, ts)
+);
+
+-- candles written before chain_id was added take it from their multipool, or
+-- from the only indexed chain of single chain deployments
+DO $$
+DECLARE
+    var_missing BIGINT;
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM information_schema.columns
+        WHERE table_name = 'candles' AND column_name = 'chain_id'
+    ) THEN
+        ALTER TABLE candles ADD COLUMN chain_id BIGINT;
+        UPDATE candles SET chain_id = multipools.chain_id
+        FROM multipools WHERE candles.multipool = multipools.multipool;
+        IF (SELECT count(DISTINCT chain_id) FROM blocks) = 1 THEN
+            UPDATE candles SET chain_id = (SELECT chain_id FROM blocks LIMIT 1)
+            WHERE chain_id IS NULL;
+        END IF;
+        SELECT count(DISTINCT multipool) INTO var_missing FROM candles WHERE chain_id IS NULL;
+        IF var_missing > 0 THEN
+            RAISE EXCEPTION 'chain of candles of % multipools is unknown, set candles.chain_id manually', var_missing;
+        END IF;
+        ALTER TABLE candles ALTER COLUMN chain_id SET NOT NULL;
+        ALTER TABLE candles DROP CONSTRAINT candles_pkey;
+        ALTER TABLE candles ADD CONSTRAINT candles_pkey PRIMARY KEY (chain_id, multipool, resolution, ts);
+    END IF;
+END
+$$;
+
+-- resolutions in seconds are synced from service config on start
+CREATE TABLE IF NOT EXISTS resolutions
+(
+    resolution          INT     PRIMARY KEY
 );
 
+INSERT INTO resolutions(resolution) VALUES (60), (900), (3600), (86400) ON CONFLICT DO NOTHING;
+
+DROP PROCEDURE IF EXISTS insert_price(ADDRESS, BIGINT, U256);
+
 -- price is decimal with precision 10^6
-CREATE OR REPLACE PROCEDURE insert_price(arg_multipool ADDRESS, arg_timestamp BIGINT, arg_new_price U256)
+CREATE OR REPLACE PROCEDURE insert_price(arg_chain_id BIGINT, arg_multipool ADDRESS, arg_timestamp BIGINT, arg_new_price U256)
 LANGUAGE plpgsql
 AS $$
 DECLARE
-    --                         1m 15m 60m  24h
-    var_resolutions INT[] := '{60,900,3600,86400}';
+    var_resolutions INT[] := ARRAY(SELECT resolution FROM resolutions ORDER BY resolution);
     var_resol INT;
 BEGIN
 
-        IF (select multipool from multipools where multipool=arg_multipool limit 1) IS NULL THEN
-            insert into multipools(multipool) values (arg_multipool);
+        IF (select multipool from multipools where chain_id=arg_chain_id and multipool=arg_multipool limit 1) IS NULL THEN
+            insert into multipools(chain_id, multipool, owner) values (arg_chain_id, arg_multipool, '\x0000000000000000000000000000000000000000');
         END IF;
 
         -- gen candles
         FOREACH var_resol in array var_resolutions
         LOOP
-            INSERT INTO candles(multipool, ts, resolution, open, close, low, hight)
+            INSERT INTO candles(chain_id, multipool, ts, resolution, open, close, low, hight)
             VALUES(
+                arg_chain_id,
                 arg_multipool,
                 arg_timestamp / var_resol * var_resol,
                 var_resol,
@@ -122,7 +207,7 @@ BEGIN
                 arg_new_price,
                 arg_new_price
                 )
-            ON CONFLICT (multipool, resolution, ts) DO UPDATE SET
+            ON CONFLICT (chain_id, multipool, resolution, ts) DO UPDATE SET
                 close = arg_new_price,
                 low = least(candles.low, arg_new_price),
                 hight = greatest(candles.hight, arg_new_price);
@@ -167,3 +252,30 @@ $$;
 CREATE TRIGGER trigger_trading_history
 AFTER INSERT ON actions_history
 FOR EACH ROW EXECUTE FUNCTION update_positions();
+
+-- checkpoints are replayed from blocks, rewriting any block invalidates checkpoints
+-- at and above it
+CREATE OR REPLACE FUNCTION invalidate_checkpoints()
+RETURNS TRIGGER
+LANGUAGE plpgsql
+AS $$
+DECLARE
+    var_block blocks := CASE WHEN TG_OP = 'DELETE' THEN OLD ELSE NEW END;
+BEGIN
+    DELETE FROM multipool_checkpoints
+    WHERE
+            chain_id = var_block.chain_id
+        and block_number >= var_block.block_number;
+    IF TG_OP = 'UPDATE' THEN
+        DELETE FROM multipool_checkpoints
+        WHERE
+                chain_id = OLD.chain_id
+            and block_number >= OLD.block_number;
+    END IF;
+    RETURN NULL;
+END
+$$;
+
+CREATE TRIGGER trigger_invalidate_checkpoints
+AFTER INSERT OR UPDATE OR DELETE ON blocks
+FOR EACH ROW EXECUTE FUNCTION invalidate_checkpoints();
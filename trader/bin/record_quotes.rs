@@ -0,0 +1,134 @@
+//! Records `QuoterV2` quotes of single Uniswap V3 pools together with pool state
+//! at the same block. Output is read by `recorded_quoter_parity_test`:
+//!
+//! cargo run --bin record_quotes -- --rpc-url <url> --block <number> \
+//!     --quoter <address> --pool <address> --pool <address> \
+//!     > trader/src/uniswap/fixtures/recorded_quotes.json
+
+use alloy::{
+    eips::BlockId,
+    primitives::{Address, Bytes, U256},
+    providers::{MulticallBuilder, ProviderBuilder},
+};
+use anyhow::Result;
+use clap::Parser;
+use multipool_trader::{
+    contracts::Quoter,
+    uniswap::{pool::PoolState, simulator::load_pool},
+};
+use reqwest::Url;
+use serde::Serialize;
+
+#[derive(Parser)]
+struct Args {
+    #[arg(long)]
+    rpc_url: Url,
+    /// Block to record at, recorded quotes are only valid against this state
+    #[arg(long)]
+    block: u64,
+    #[arg(long)]
+    quoter: Address,
+    #[arg(long = "pool", required = true)]
+    pools: Vec<Address>,
+    /// Bitmap words loaded around current tick, should cover price moves of the
+    /// largest quoted amounts
+    #[arg(long, default_value_t = 20)]
+    bitmap_words: i16,
+}
+
+#[derive(Serialize)]
+struct QuoteFixture {
+    zero_for_one: bool,
+    exact_input: bool,
+    amount: U256,
+    result: Option<U256>,
+}
+
+#[derive(Serialize)]
+struct PoolFixture {
+    pool: PoolState,
+    quotes: Vec<QuoteFixture>,
+}
+
+#[derive(Serialize)]
+struct Recording {
+    block: u64,
+    quoter: Address,
+    pools: Vec<PoolFixture>,
+}
+
+fn path(token_in: Address, fee: u32, token_out: Address) -> Bytes {
+    [
+        token_in.as_slice(),
+        &fee.to_be_bytes()[1..],
+        token_out.as_slice(),
+    ]
+    .concat()
+    .into()
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+    let rpc = ProviderBuilder::new().connect_http(args.rpc_url);
+    let block = BlockId::number(args.block);
+    let quoter = Quoter::new(args.quoter, &rpc);
+
+    let amounts: Vec<U256> = (1..=8)
+        .map(|k| U256::from(10).pow(U256::from(3 * k)))
+        .collect();
+    let mut pools = Vec::new();
+    for address in args.pools {
+        let pool = load_pool(&rpc, address, block, args.bitmap_words).await?;
+        let mut quotes = Vec::new();
+        for zero_for_one in [true, false] {
+            let (token_in, token_out) = if zero_for_one {
+                (pool.token0, pool.token1)
+            } else {
+                (pool.token1, pool.token0)
+            };
+            let input = MulticallBuilder::new_dynamic(&rpc)
+                .extend(amounts.iter().map(|amount| {
+                    quoter.quoteExactInput(path(token_in, pool.fee, token_out), *amount)
+                }))
+                .block(block)
+                .try_aggregate(false)
+                .await?;
+            // exact output path starts from the output token
+            let output = MulticallBuilder::new_dynamic(&rpc)
+                .extend(amounts.iter().map(|amount| {
+                    quoter.quoteExactOutput(path(token_out, pool.fee, token_in), *amount)
+                }))
+                .block(block)
+                .try_aggregate(false)
+                .await?;
+            for (amount, quote) in amounts.iter().zip(input) {
+                quotes.push(QuoteFixture {
+                    zero_for_one,
+                    exact_input: true,
+                    amount: *amount,
+                    result: quote.ok().map(|quote| quote.amountOut),
+                });
+            }
+            for (amount, quote) in amounts.iter().zip(output) {
+                quotes.push(QuoteFixture {
+                    zero_for_one,
+                    exact_input: false,
+                    amount: *amount,
+                    result: quote.ok().map(|quote| quote.amountIn),
+                });
+            }
+        }
+        pools.push(PoolFixture { pool, quotes });
+    }
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&Recording {
+            block: args.block,
+            quoter: args.quoter,
+            pools,
+        })?
+    );
+    Ok(())
+}
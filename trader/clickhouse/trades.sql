@@ -22,6 +22,15 @@ CREATE OR REPLACE TABLE trades
 
     strategy_type           LowCardinality(String)  NOT NULL,
 
+    tx_hash                 String                  NOT NULL,
+    tx_status               LowCardinality(String)  NOT NULL,
+    gas_used                UInt64                  NOT NULL,
+    gas_cost                UInt256                 NOT NULL,
+    realized_profit         Int256                  NOT NULL,
+
+    failure_category        LowCardinality(String)  NOT NULL,
+    failure_reason          String                  NOT NULL,
+
     is_profitable           BOOL                    NOT NULL MATERIALIZED profit > 0,
 
     profit                  Int256                  NOT NULL MATERIALIZED toInt256(trade_output) - toInt256(trade_input),
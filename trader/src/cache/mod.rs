@@ -1,2 +0,0 @@
-pub mod cache;
-pub mod multipool;
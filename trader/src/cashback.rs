@@ -1,5 +0,0 @@
-// use anyhow::Result;
-
-// pub async fn check_cashback() -> Result<()> {
-
-// }
@@ -55,6 +55,17 @@ pub struct TradeStats {
     #[serde(with = "u256")]
     pub multipool_amount_out: U256,
     pub strategy_type: String,
+    /// Included transaction, empty if the trade was not sent
+    pub tx_hash: String,
+    pub tx_status: String,
+    pub gas_used: u64,
+    #[serde(with = "u256")]
+    pub gas_cost: U256,
+    #[serde(with = "i256")]
+    pub realized_profit: I256,
+    /// `TradeFailure` category of failed simulations
+    pub failure_category: String,
+    pub failure_reason: String,
 }
 
 // u256 serde -- https://github.com/ClickHouse/clickhouse-rs/issues/48
@@ -2,29 +2,43 @@ use alloy::{
     primitives::{address, Address},
     sol,
 };
-
-// pub const TRADER_ADDRESS: Address = address!("955aDe421294B9D9C49b09bd64a92a4138EA6C56");
-// pub const TRADER_ADDRESS: Address = address!("1991E54D4d503086a9De4ff272c316f0ed4AA263");
-pub const TRADER_ADDRESS: Address = address!("F69ae94063f4671Ea4e4b9f8c97eb1aAC1731cb8");
-pub const CASHBACK_VAULT: Address = address!("B9cb365F599885F6D97106918bbd406FE09b8590");
-
-// pub const WETH_ADDRESS: Address = address!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2");
-pub const WETH_ADDRESS: Address = address!("760AfE86e5de5fa0Ee542fc7B7B713e1c5425701");
-
-// pub const QUOTERV2_ADDRESS: Address = address!("61fFE014bA17989E743c5F6cB21bF9697530B21e");
-pub const QUOTERV2_ADDRESS: Address = address!("1b4E313fEF15630AF3e6F2dE550Dbf4cC9D3081d"); // v1??
-// pub const QUOTERV2_ADDRESS: Address = address!("1BA215c17565dE7b0Cb7eCaB971bcF540c24a862"); // v2??
+use multipool_types::deployments::{Contract, Deployment, DeploymentError};
 
 pub const MULTICALL_ADDRESS: Address = address!("cA11bde05977b3631167028862bE2a173976CA11");
 
-pub const SILO_LENS: Address = address!("BDb843c7a7e48Dc543424474d7Aa63b61B5D9536");
-pub const SILO_WRAPPER: Address = address!("5F127Aedf5A31E2F2685E49618D4f4809205fd62");
+/// Addresses of contracts the trader calls, resolved from the deployment of
+/// its chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraderContracts {
+    pub trader: Address,
+    pub weth: Address,
+    pub uniswap_factory: Address,
+    pub quoter: Address,
+    pub cashback_vault: Address,
+    /// Silo contracts are needed only to trade wrapped silo deposits
+    pub silo_lens: Option<Address>,
+    pub silo_wrapper: Option<Address>,
+}
+
+impl TraderContracts {
+    pub fn resolve(deployment: &Deployment) -> Result<Self, DeploymentError> {
+        Ok(Self {
+            trader: deployment.get(Contract::Trader)?,
+            weth: deployment.get(Contract::Weth)?,
+            uniswap_factory: deployment.get(Contract::UniswapFactory)?,
+            quoter: deployment.get(Contract::UniswapQuoter)?,
+            cashback_vault: deployment.get(Contract::CashbackVault)?,
+            silo_lens: deployment.optional(Contract::SiloLens),
+            silo_wrapper: deployment.optional(Contract::SiloWrapper),
+        })
+    }
+}
 
 pub mod multipool {
     use super::sol;
     sol!(
         #[allow(missing_docs)]
-        #[sol(rpc, all_derives=true)]
+        #[sol(rpc, abi, all_derives = true)]
         MultipoolContract,
         "../arcanum-contracts/out/Multipool.sol/Multipool.json"
     );
@@ -34,7 +48,7 @@ pub mod trader {
     use super::sol;
     sol!(
         #[allow(missing_docs)]
-        #[sol(rpc)]
+        #[sol(rpc, abi)]
         Trader,
         "../arcanum-contracts/out/Trader.sol/Trader.json"
     );
@@ -42,21 +56,21 @@ pub mod trader {
 
 sol!(
     #[allow(missing_docs)]
-    #[sol(rpc)]
+    #[sol(rpc, abi)]
     IUniswapV3Pool,
     "../arcanum-contracts/out/IUniswapV3Pool.sol/IUniswapV3Pool.json"
 );
 
 sol!(
     #[allow(missing_docs)]
-    #[sol(rpc)]
+    #[sol(rpc, abi)]
     Quoter,
     "../arcanum-contracts/out/IQuoterV2.sol/IQuoterV2.json"
 );
 
 sol!(
     #[allow(missing_docs)]
-    #[sol(rpc)]
+    #[sol(rpc, abi)]
     contract SiloPool {
         function deposit(address _asset, uint256 _amount, bool _collateralOnly) external returns (uint256 collateralAmount, uint256 collateralShare);
         function withdraw(address _asset, uint256 _amount, bool _collateralOnly) external returns (uint256 withdrawnAmount, uint256 withdrawnShare);
@@ -65,7 +79,7 @@ sol!(
 
 sol!(
     #[allow(missing_docs)]
-    #[sol(rpc)]
+    #[sol(rpc, abi)]
     contract SiloLens {
         function totalDepositsWithInterest(address _silo,address _asset) external view returns (uint256 _totalDeposits);
     }
@@ -73,8 +87,9 @@ sol!(
 
 sol!(
     #[allow(missing_docs)]
-    #[sol(rpc)]
+    #[sol(rpc, abi)]
     contract ERC20 {
+        event Transfer(address indexed from, address indexed to, uint256 value);
         function totalSupply() external view returns (uint256 value);
         function approve(address recepient,uint256 amount) external;
         function transfer(address recepient,uint256 amount) external;
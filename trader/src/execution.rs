@@ -1,25 +1,30 @@
 use alloy::providers::Provider;
-use anyhow::{anyhow, Result};
-use colored::Colorize;
-use std::time::Duration;
+use alloy::sol_types::SolEvent;
+use anyhow::{bail, Result};
+use backend_service::{logging::LogTarget, KeyValue};
+use serde_json::json;
 
 use crate::clickhouse::{Click, TradeStats};
 use crate::contracts::trader::Trader::{self, Args, Call};
-use crate::contracts::TRADER_ADDRESS;
+use crate::contracts::ERC20;
+use crate::failure::TradeFailure;
+use crate::gas::GasOracle;
+use crate::log_target::TraderTarget::Execution as ExecutionLog;
+use crate::metrics::{TOTAL_SIMULATIONS, TOTAL_TRADE_FAILURES};
 use crate::trade::UniswapChoise;
+use crate::tx_manager::{TxManager, TxOutcome, TxStatus};
 use alloy::hex::ToHexExt;
-use alloy::primitives::{Address, TxHash, U256};
-use std::sync::Arc;
-use tokio::time::sleep;
+use alloy::primitives::{Address, Log, I256, U256};
+use std::{sync::Arc, time::Duration};
+use tokio::time::timeout;
 
 impl<P: Provider> UniswapChoise<P> {
-    pub async fn execute(&self, click: Arc<Click>) -> Result<()> {
-        let multipool = &self
-            .trading_data
-            .trading_data_with_assets
-            .trading_data
-            .multipool;
-        let stats = TradeStats {
+    /// Trade is dropped if its simulation takes longer than `task_timeout`,
+    /// sent transaction is tracked until it is included or cancelled
+    pub async fn execute(&self, click: Arc<Click>, task_timeout: Duration) -> Result<()> {
+        let trading_data = &self.trading_data.trading_data_with_assets.trading_data;
+        let multipool = &trading_data.multipool;
+        let mut stats = TradeStats {
             trade_input: self.input.estimated,
             trade_output: self.output.estimated,
 
@@ -44,21 +49,37 @@ impl<P: Provider> UniswapChoise<P> {
             multipool_amount_in: self.trading_data.multipool_amount_in,
             multipool_amount_out: self.trading_data.multipool_amount_out,
 
-            strategy_type: "collectCashbacks".into(),
+            strategy_type: self
+                .trading_data
+                .trading_data_with_assets
+                .strategy
+                .label()
+                .into(),
 
             multipool_address: self
                 .trading_data
                 .trading_data_with_assets
                 .trading_data
                 .multipool
-                .address
+                .contract_address()
                 .0
                 .encode_hex(),
 
             pool_in_fee: self.input.best_fee,
             pool_out_fee: self.output.best_fee,
+
+            tx_hash: String::new(),
+            tx_status: "not_sent".into(),
+            gas_used: 0,
+            gas_cost: U256::ZERO,
+            realized_profit: I256::ZERO,
+
+            failure_category: String::new(),
+            failure_reason: String::new(),
         };
 
+        // negative fee is a cashback paid by the multipool, nothing is sent for it
+        let multipool_fee = U256::try_from(self.trading_data.fee.max(I256::ZERO))?;
         let args = Args {
             tokenIn: self.trading_data.swap_asset_in,
             multipoolTokenIn: self.trading_data.trading_data_with_assets.asset1,
@@ -68,22 +89,25 @@ impl<P: Provider> UniswapChoise<P> {
             zeroForOneOut: !self.output.zero_for_one,
 
             tmpAmount: self.trading_data.unwrapped_amount_in,
-            multipoolFee: U256::from(10000000000000u128),
+            multipoolFee: multipool_fee,
 
             poolIn: self.input.best_pool,
             poolOut: self.output.best_pool,
 
-            multipool: multipool.address,
+            multipool: multipool.contract_address(),
             oraclePrice: self
                 .trading_data
                 .trading_data_with_assets
                 .trading_data
                 .oracle_price
                 .clone(),
-            gasLimit: U256::from(4000000),
-            weth: self.trading_data.trading_data_with_assets.trading_data.weth,
-            // cashback: CASHBACK_VAULT,
-            cashback: Address::ZERO,
+            gasLimit: U256::from(trading_data.gas_oracle.config().max_gas_limit),
+            weth: trading_data.contracts.weth,
+            cashback: self
+                .trading_data
+                .trading_data_with_assets
+                .strategy
+                .cashback_receiver(&trading_data.contracts),
             assets: vec![
                 self.trading_data.trading_data_with_assets.asset1,
                 self.trading_data.trading_data_with_assets.asset2,
@@ -100,12 +124,50 @@ impl<P: Provider> UniswapChoise<P> {
             },
         };
 
-        check_and_send(
-            &self.trading_data.trading_data_with_assets.trading_data.rpc,
+        let execution = match check_and_send(
+            &trading_data.rpc,
+            trading_data.contracts.trader,
+            &trading_data.gas_oracle,
+            &trading_data.tx_manager,
             args,
+            task_timeout,
         )
         .await
-        .map_err(|e| anyhow!("{e:?}"))?;
+        {
+            Ok(execution) => execution,
+            Err(failure) => {
+                TOTAL_TRADE_FAILURES.add(
+                    1,
+                    &[KeyValue::new("category", failure.category().to_string())],
+                );
+                stats.tx_status = "simulation_failed".into();
+                stats.failure_category = failure.category().into();
+                stats.failure_reason = failure.to_string();
+                click.insert(stats).await?;
+                bail!("{failure}");
+            }
+        };
+
+        match &execution.transaction {
+            Some(Ok(outcome)) => {
+                stats.tx_hash = outcome.hash.encode_hex_with_prefix();
+                stats.tx_status = outcome.status.label().into();
+                stats.gas_used = outcome.gas_used;
+                stats.gas_cost = outcome.gas_cost();
+            }
+            Some(Err(e)) => {
+                stats.tx_status = "send_failed".into();
+                stats.failure_reason = e.clone();
+            }
+            None => {}
+        }
+        stats.realized_profit = execution.realized_profit(
+            &[
+                trading_data.contracts.trader,
+                trading_data.tx_manager.address(),
+            ],
+            trading_data.contracts.weth,
+        );
 
         // insert post trade
         click.insert(stats).await?;
@@ -116,67 +178,126 @@ impl<P: Provider> UniswapChoise<P> {
 #[derive(Debug, Clone)]
 pub struct Execution {
     pub estimated_gas: U256,
+    pub gas_limit: u64,
     pub estimated_profit: U256,
-    pub transaction: Option<Result<TxHash, String>>,
+    pub transaction: Option<Result<TxOutcome, String>>,
+}
+
+impl Execution {
+    /// WETH `accounts` gained in the included trade less gas actually paid
+    pub fn realized_profit(&self, accounts: &[Address], weth: Address) -> I256 {
+        match &self.transaction {
+            Some(Ok(outcome)) => {
+                let profit = if outcome.status == TxStatus::Success {
+                    weth_delta(&outcome.logs, weth, accounts)
+                } else {
+                    I256::ZERO
+                };
+                profit - I256::from_raw(outcome.gas_cost())
+            }
+            _ => I256::ZERO,
+        }
+    }
+}
+
+/// WETH received by `accounts` less WETH they sent, decoded from WETH `Transfer`
+/// logs. Transfers between `accounts` are ignored.
+pub fn weth_delta(logs: &[Log], weth: Address, accounts: &[Address]) -> I256 {
+    logs.iter()
+        .filter(|log| log.address == weth)
+        .filter_map(|log| ERC20::Transfer::decode_log_data(&log.data).ok())
+        .fold(I256::ZERO, |delta, transfer| {
+            let value = I256::from_raw(transfer.value);
+            match (
+                accounts.contains(&transfer.from),
+                accounts.contains(&transfer.to),
+            ) {
+                (false, true) => delta + value,
+                (true, false) => delta - value,
+                _ => delta,
+            }
+        })
 }
 
-pub async fn check_and_send<P: Provider>(rpc: &P, args: Args) -> Result<Execution, String> {
-    let contract = Trader::new(TRADER_ADDRESS, rpc);
+pub async fn check_and_send<P: Provider>(
+    rpc: &P,
+    trader: Address,
+    gas_oracle: &GasOracle,
+    tx_manager: &TxManager,
+    args: Args,
+    task_timeout: Duration,
+) -> Result<Execution, TradeFailure> {
+    let fees = gas_oracle
+        .fees(rpc)
+        .await
+        .map_err(|e| TradeFailure::Rpc(e.to_string()))?;
+    let contract = Trader::new(trader, rpc);
     let tx = contract
         .trade(args.clone())
-        .gas(args.gasLimit.to::<u64>())
-        .gas_price(10000000)
-        // 0.02
-        .value(U256::from(30000000000000000u128));
-    let simulate = tx.call().await;
+        .gas(args.gasLimit.saturating_to::<u64>())
+        .max_fee_per_gas(fees.max_fee_per_gas)
+        .max_priority_fee_per_gas(fees.max_priority_fee_per_gas)
+        .value(gas_oracle.config().trade_value);
+    TOTAL_SIMULATIONS.add(1, &[]);
+    let simulate = timeout(task_timeout, tx.call())
+        .await
+        .map_err(|_| TradeFailure::Timeout)?;
 
     match simulate {
         Ok(res) => {
             let profit = res.profit;
             let gas_used = res.gasUsed;
-            println!("Simlulation SUCCESS, profit: {}, gas: {}", profit, gas_used);
-            // * 0.1 / 10^9
-            let eth_for_gas = gas_used * U256::from(10_000_000u128);
-            println!(
-                "ETH for gas {}",
-                eth_for_gas.to::<u128>() as f64 / 10f64.powf(18f64)
-            );
-
-            if profit > eth_for_gas {
-                println!(
-                    "Actual profit {}",
-                    (profit - eth_for_gas).to::<u128>() as f64 / 10f64.powf(18f64)
-                );
-                let broadcast = tx.send().await;
-
-                let val = match broadcast {
-                    Ok(v) => {
-                        println!("Successful trade {:?}", v);
-                        Ok(Execution {
-                            estimated_gas: gas_used,
-                            estimated_profit: profit,
-                            transaction: Some(Ok(v.tx_hash().to_owned())),
-                        })
+            let gas_limit = gas_oracle.gas_limit(gas_used);
+            let eth_for_gas = fees.gas_cost(gas_used);
+            ExecutionLog
+                .info(json!({
+                    "m": "simulation succeeded",
+                    "profit": ether(profit),
+                    "gas": gas_used.to_string(),
+                    "gas_cost": ether(eth_for_gas),
+                }))
+                .log();
+
+            if let Some(net_profit) = gas_oracle.net_profit(profit, gas_used, &fees) {
+                let request = tx.gas(gas_limit).into_transaction_request();
+                let transaction = match tx_manager.send(rpc, request, fees).await {
+                    Ok(outcome) => {
+                        ExecutionLog
+                            .info(json!({
+                                "m": "trade sent",
+                                "net_profit": ether(net_profit),
+                                "outcome": format!("{outcome:?}"),
+                            }))
+                            .log();
+                        Ok(outcome)
                     }
                     Err(e) => {
-                        println!("Trade failed {:?}", e);
-                        Ok(Execution {
-                            estimated_gas: gas_used,
-                            estimated_profit: profit,
-                            transaction: Some(Err(e.to_string())),
-                        })
+                        ExecutionLog
+                            .error(json!({
+                                "m": "trade failed",
+                                "net_profit": ether(net_profit),
+                                "e": e.to_string(),
+                            }))
+                            .log();
+                        Err(e.to_string())
                     }
                 };
-                sleep(Duration::from_millis(4000)).await;
-                val
+                Ok(Execution {
+                    estimated_gas: gas_used,
+                    gas_limit,
+                    estimated_profit: profit,
+                    transaction: Some(transaction),
+                })
             } else {
-                let profit_with_gas = format!(
-                    "Profit with gas: {}",
-                    (profit.to::<i128>() - eth_for_gas.to::<i128>()) as f64 / 10f64.powf(18f64)
-                );
-                println!("{}", profit_with_gas.yellow().bold());
+                ExecutionLog
+                    .info(json!({
+                        "m": "trade is not profitable",
+                        "profit_with_gas": ether(profit) - ether(eth_for_gas),
+                    }))
+                    .log();
                 Ok(Execution {
                     estimated_gas: gas_used,
+                    gas_limit,
                     estimated_profit: profit,
                     transaction: None,
                 })
@@ -184,41 +305,67 @@ pub async fn check_and_send<P: Provider>(rpc: &P, args: Args) -> Result<Executio
         }
 
         Err(e) => {
-            // if e.as_revert() ==
-            //     Some(
-            //         bytes!("08c379a000000000000000000000000000000000000000000000000000000000000000200000000000000000\
-            //         0000000000000000000000000000000000000000000000096e6f2070726f66697400000000000000000000000000\
-            //         00000000000000000000")) {
-            //     println!("Simulation FAILED, no profit");
-            // } else if e.as_revert() == Some(
-            //     bytes!("08c379a00000000000000000000000000000000000000000000000000000000000000020000000000000000\
-            //     00000000000000000000000000000000000000000000000035354460000000000000000000000000000000000\
-            //     000000000000000000000000")) {
-            //     println!("Simulation FAILED, uniswap STF");
-            // } else if e.as_revert() == Some(
-            //     bytes!("08c379a0000000000000000000000000000000000000000000000000000000000000002000000000000000\
-            //     000000000000000000000000000000000000000000000000096e6f2070726f6669740000000000000000000000\
-            //     000000000000000000000000")) {
-            //     println!("No profit");
-            // } else if e.as_revert() == Some(
-            //     bytes!("08c379a000000000000000000000000000000000000000000000000000000000000000200000000000000000\
-            //     00000000000000000000000000000000000000000000002645524332303a207472616e7366657220616d6f756e74\
-            //     20657863656564732062616c616e63650000000000000000000000000000000000000000000000000000")) {
-            //     println!("Simulation FAILED, ERC20: transfer amount exceeds balance");
-
-            // } else if e.as_revert() == Some(
-            //     bytes!("08c379a00000000000000000000000000000000000000000000000000000000000000020000000000000000000\
-            //     000000000000000000000000000000000000000000002645524332303a207472616e7366657220616d6f756e7420657\
-            //     863656564732062616c616e63650000000000000000000000000000000000000000000000000000")) {
-            //     println!("ERC20: transfer amount exceeds balance");
-            // } else if e.as_revert() == Some(bytes!("3fb8e961")) {
-            //     println!("Simulation FAILED, deviation exceeds limit");
-            // } else if e.as_revert() == Some(bytes!("7cb71f89")) {
-            //     println!("Simulation FAILED, sleepage exceeded");
-            // } else {
-            //     println!("Simulation FAILED, error: {:?}", e);
-            // }
-            Err(e.to_string())
+            let failure = TradeFailure::from_error(&e);
+            ExecutionLog
+                .info(json!({
+                    "m": "simulation failed",
+                    "e": failure.to_string(),
+                }))
+                .log();
+            Err(failure)
+        }
+    }
+}
+
+/// Lossy conversion of wei amount to ether, used only for logging
+fn ether(wei: U256) -> f64 {
+    f64::from(wei) / 1e18
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::{address, Address, Log, I256, U256};
+    use alloy::sol_types::SolEvent;
+
+    use super::weth_delta;
+    use crate::contracts::ERC20;
+
+    fn transfer(token: Address, from: Address, to: Address, value: u64) -> Log {
+        Log {
+            address: token,
+            data: ERC20::Transfer {
+                from,
+                to,
+                value: U256::from(value),
+            }
+            .encode_log_data(),
         }
     }
+
+    #[test]
+    fn weth_delta_test() {
+        let weth = address!("4200000000000000000000000000000000000006");
+        let trader = address!("1000000000000000000000000000000000000001");
+        let sender = address!("1000000000000000000000000000000000000002");
+        let pool_in = address!("2000000000000000000000000000000000000001");
+        let pool_out = address!("2000000000000000000000000000000000000002");
+        let token = address!("3000000000000000000000000000000000000001");
+
+        let logs = [
+            transfer(weth, trader, pool_in, 1000),
+            transfer(token, pool_in, trader, 500),
+            transfer(weth, pool_out, trader, 1100),
+            // profit forwarded to sender is still gained by the trade
+            transfer(weth, trader, sender, 100),
+        ];
+        assert_eq!(
+            weth_delta(&logs, weth, &[trader, sender]),
+            I256::try_from(100).unwrap()
+        );
+        assert_eq!(weth_delta(&logs, weth, &[trader]), I256::ZERO);
+        assert_eq!(
+            weth_delta(&logs[..1], weth, &[trader, sender]),
+            I256::try_from(-1000).unwrap()
+        );
+    }
 }
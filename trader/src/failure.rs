@@ -0,0 +1,181 @@
+use std::fmt::Display;
+
+use alloy::{
+    dyn_abi::ErrorExt,
+    json_abi::{Error, JsonAbi},
+    primitives::{Bytes, U256},
+    sol_types::{ContractError, GenericContractError, SolInterface},
+};
+
+use crate::contracts::{
+    multipool::MultipoolContract, trader::Trader, IUniswapV3Pool, Quoter, SiloLens, SiloPool, ERC20,
+};
+
+lazy_static::lazy_static! {
+    /// Custom errors of every contract the trader calls, with contract label
+    static ref CUSTOM_ERRORS: Vec<(&'static str, Error)> = {
+        let abis: [(&'static str, JsonAbi); 7] = [
+            ("trader", Trader::abi::contract()),
+            ("multipool", MultipoolContract::abi::contract()),
+            ("uniswap_pool", IUniswapV3Pool::abi::contract()),
+            ("quoter", Quoter::abi::contract()),
+            ("silo_pool", SiloPool::abi::contract()),
+            ("silo_lens", SiloLens::abi::contract()),
+            ("erc20", ERC20::abi::contract()),
+        ];
+        abis.into_iter()
+            .flat_map(|(contract, abi)| {
+                abi.errors
+                    .into_values()
+                    .flatten()
+                    .map(move |error| (contract, error))
+            })
+            .collect()
+    };
+}
+
+/// Reason of a failed trade simulation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TradeFailure {
+    /// Trader contract didn't get more WETH than it spent
+    NoProfit,
+    /// Uniswap `TransferHelper` failed to transfer tokens
+    UniswapTransferFailed,
+    InsufficientBalance,
+    /// Other `Error(string)` revert
+    Revert(String),
+    /// `Panic(uint256)` with its code
+    Panic(U256),
+    /// Custom error declared by one of the called contracts
+    Custom {
+        contract: &'static str,
+        name: String,
+        data: Bytes,
+    },
+    /// Revert data that doesn't match any known error
+    Unknown(Bytes),
+    /// Request failed without revert data
+    Rpc(String),
+    Timeout,
+}
+
+impl TradeFailure {
+    /// Decodes revert data of the trader contract, it forwards errors of the
+    /// contracts it calls
+    pub fn decode(data: &[u8]) -> Self {
+        match GenericContractError::abi_decode(data) {
+            Ok(ContractError::CustomError(never)) => match never {},
+            Ok(ContractError::Panic(panic)) => Self::Panic(panic.code),
+            Ok(ContractError::Revert(revert)) => match revert.reason.as_str() {
+                "no profit" => Self::NoProfit,
+                "STF" => Self::UniswapTransferFailed,
+                "ERC20: transfer amount exceeds balance" => Self::InsufficientBalance,
+                _ => Self::Revert(revert.reason),
+            },
+            Err(_) => CUSTOM_ERRORS
+                .iter()
+                .find(|(_, error)| error.decode_error(data).is_ok())
+                .map_or(
+                    Self::Unknown(Bytes::copy_from_slice(data)),
+                    |(contract, error)| Self::Custom {
+                        contract,
+                        name: error.name.clone(),
+                        data: Bytes::copy_from_slice(data),
+                    },
+                ),
+        }
+    }
+
+    pub fn from_error(error: &alloy::contract::Error) -> Self {
+        match error.as_revert_data() {
+            Some(data) => Self::decode(&data),
+            None => Self::Rpc(error.to_string()),
+        }
+    }
+
+    /// Label of failures counted in metrics and stored with trade stats
+    pub fn category(&self) -> &str {
+        match self {
+            Self::NoProfit => "no_profit",
+            Self::UniswapTransferFailed => "uniswap_transfer_failed",
+            Self::InsufficientBalance => "insufficient_balance",
+            Self::Revert(_) => "revert",
+            Self::Panic(_) => "panic",
+            Self::Custom { name, .. } => name,
+            Self::Unknown(_) => "unknown",
+            Self::Rpc(_) => "rpc",
+            Self::Timeout => "timeout",
+        }
+    }
+}
+
+impl Display for TradeFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Revert(reason) => write!(f, "revert: {reason}"),
+            Self::Panic(code) => write!(f, "panic: {code:#x}"),
+            Self::Unknown(data) => write!(f, "unknown revert: {data}"),
+            Self::Rpc(error) => write!(f, "rpc: {error}"),
+            Self::Custom { contract, name, .. } => write!(f, "{contract}: {name}"),
+            _ => write!(f, "{}", self.category()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::hex;
+
+    use super::*;
+
+    #[test]
+    fn decode_test() {
+        let no_profit = hex!(
+            "08c379a0"
+            "0000000000000000000000000000000000000000000000000000000000000020"
+            "0000000000000000000000000000000000000000000000000000000000000009"
+            "6e6f2070726f6669740000000000000000000000000000000000000000000000"
+        );
+        assert_eq!(TradeFailure::decode(&no_profit), TradeFailure::NoProfit);
+
+        let stf = hex!(
+            "08c379a0"
+            "0000000000000000000000000000000000000000000000000000000000000020"
+            "0000000000000000000000000000000000000000000000000000000000000003"
+            "5354460000000000000000000000000000000000000000000000000000000000"
+        );
+        assert_eq!(
+            TradeFailure::decode(&stf),
+            TradeFailure::UniswapTransferFailed
+        );
+
+        let panic = hex!(
+            "4e487b71"
+            "0000000000000000000000000000000000000000000000000000000000000011"
+        );
+        assert_eq!(
+            TradeFailure::decode(&panic),
+            TradeFailure::Panic(U256::from(0x11))
+        );
+        assert_eq!(TradeFailure::decode(&panic).category(), "panic");
+
+        let deviation = TradeFailure::decode(&hex!("3fb8e961"));
+        assert!(matches!(
+            deviation,
+            TradeFailure::Custom {
+                contract: "multipool",
+                ..
+            }
+        ));
+        assert_eq!(deviation.category(), "DeviationExceedsLimit");
+        assert_eq!(
+            TradeFailure::decode(&hex!("7cb71f89")).category(),
+            "SleepageExceeded"
+        );
+
+        assert_eq!(
+            TradeFailure::decode(&hex!("deadbeef")),
+            TradeFailure::Unknown(Bytes::from(hex!("deadbeef")))
+        );
+    }
+}
@@ -0,0 +1,163 @@
+// Signs an oracle price the way the multipool verifies it, without alloy:
+// `ECDSA.recover(toEthSignedMessageHash(keccak256(abi.encodePacked(
+// contractAddress, timestamp, sharePrice))), signature)`. Keccak and secp256k1
+// are written out with BigInt and nonce is random, so the signature can't
+// coincide with RFC 6979 one of `OracleSigner`. Run with `node oracle_signature.mjs`
+// and copy the output into `oracle.rs` tests.
+import { randomBytes } from "node:crypto";
+
+// Keccak-256, FIPS 202 permutation with original 0x01 padding
+const MASK = (1n << 64n) - 1n;
+const ROUND_CONSTANTS = [
+  0x0000000000000001n, 0x0000000000008082n, 0x800000000000808an,
+  0x8000000080008000n, 0x000000000000808bn, 0x0000000080000001n,
+  0x8000000080008081n, 0x8000000000008009n, 0x000000000000008an,
+  0x0000000000000088n, 0x0000000080008009n, 0x000000008000000an,
+  0x000000008000808bn, 0x800000000000008bn, 0x8000000000008089n,
+  0x8000000000008003n, 0x8000000000008002n, 0x8000000000000080n,
+  0x000000000000800an, 0x800000008000000an, 0x8000000080008081n,
+  0x8000000000008080n, 0x0000000080000001n, 0x8000000080008008n,
+];
+const ROTATIONS = [
+  [0, 36, 3, 41, 18],
+  [1, 44, 10, 45, 2],
+  [62, 6, 43, 15, 61],
+  [28, 55, 25, 21, 56],
+  [27, 20, 39, 8, 14],
+];
+
+const rotl = (v, n) => (n === 0 ? v : ((v << BigInt(n)) | (v >> BigInt(64 - n))) & MASK);
+
+function keccakF(state) {
+  for (const rc of ROUND_CONSTANTS) {
+    const c = [0, 1, 2, 3, 4].map(
+      (x) => state[x] ^ state[x + 5] ^ state[x + 10] ^ state[x + 15] ^ state[x + 20]
+    );
+    for (let x = 0; x < 5; x++) {
+      const d = c[(x + 4) % 5] ^ rotl(c[(x + 1) % 5], 1);
+      for (let y = 0; y < 5; y++) state[x + 5 * y] ^= d;
+    }
+    const b = new Array(25).fill(0n);
+    for (let x = 0; x < 5; x++) {
+      for (let y = 0; y < 5; y++) {
+        b[y + 5 * ((2 * x + 3 * y) % 5)] = rotl(state[x + 5 * y], ROTATIONS[x][y]);
+      }
+    }
+    for (let x = 0; x < 5; x++) {
+      for (let y = 0; y < 5; y++) {
+        state[x + 5 * y] = b[x + 5 * y] ^ (~b[((x + 1) % 5) + 5 * y] & MASK & b[((x + 2) % 5) + 5 * y]);
+      }
+    }
+    state[0] ^= rc;
+  }
+}
+
+function keccak256(data) {
+  const rate = 136;
+  const padded = Buffer.alloc(Math.ceil((data.length + 1) / rate) * rate);
+  data.copy(padded);
+  padded[data.length] ^= 0x01;
+  padded[padded.length - 1] ^= 0x80;
+  const state = new Array(25).fill(0n);
+  for (let offset = 0; offset < padded.length; offset += rate) {
+    for (let i = 0; i < rate / 8; i++) {
+      state[i] ^= padded.readBigUInt64LE(offset + 8 * i);
+    }
+    keccakF(state);
+  }
+  const out = Buffer.alloc(32);
+  for (let i = 0; i < 4; i++) out.writeBigUInt64LE(state[i], 8 * i);
+  return out;
+}
+
+// secp256k1
+const P = 0xfffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2fn;
+const N = 0xfffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141n;
+const G = [
+  0x79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798n,
+  0x483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8n,
+];
+
+const mod = (a, m) => ((a % m) + m) % m;
+
+function inverse(a, m) {
+  let [r0, r1, t0, t1] = [mod(a, m), m, 1n, 0n];
+  while (r1 !== 0n) {
+    const q = r0 / r1;
+    [r0, r1, t0, t1] = [r1, r0 - q * r1, t1, t0 - q * t1];
+  }
+  return mod(t0, m);
+}
+
+function add(a, b) {
+  if (a === null) return b;
+  if (b === null) return a;
+  if (a[0] === b[0] && mod(a[1] + b[1], P) === 0n) return null;
+  const l =
+    a[0] === b[0]
+      ? mod(3n * a[0] * a[0] * inverse(2n * a[1], P), P)
+      : mod((b[1] - a[1]) * inverse(b[0] - a[0], P), P);
+  const x = mod(l * l - a[0] - b[0], P);
+  return [x, mod(l * (a[0] - x) - a[1], P)];
+}
+
+function multiply(k, point) {
+  let result = null;
+  for (; k > 0n; k >>= 1n, point = add(point, point)) {
+    if (k & 1n) result = add(result, point);
+  }
+  return result;
+}
+
+const toBigInt = (bytes) => BigInt("0x" + bytes.toString("hex"));
+const toBytes = (value, length) => Buffer.from(value.toString(16).padStart(2 * length, "0"), "hex");
+
+function address(key) {
+  const [x, y] = multiply(key, G);
+  return keccak256(Buffer.concat([toBytes(x, 32), toBytes(y, 32)])).subarray(12);
+}
+
+// 65 bytes `r`, `s`, `v` with low `s` as required by OpenZeppelin ECDSA
+function sign(digest, key) {
+  const z = toBigInt(digest);
+  for (;;) {
+    const k = mod(toBigInt(randomBytes(32)), N);
+    if (k === 0n) continue;
+    const [x, y] = multiply(k, G);
+    const r = mod(x, N);
+    let s = mod(inverse(k, N) * (z + r * key), N);
+    if (r === 0n || s === 0n || x >= N) continue;
+    let recovery = Number(y & 1n);
+    if (s > N / 2n) {
+      s = N - s;
+      recovery ^= 1;
+    }
+    return Buffer.concat([toBytes(r, 32), toBytes(s, 32), Buffer.from([27 + recovery])]);
+  }
+}
+
+// first anvil account
+const key = 0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80n;
+const contract = Buffer.from("5FbDB2315678afecb367f032d93F642f64180aa3", "hex");
+const timestamp = 1700000000n;
+// 1.5 in X96
+const sharePrice = 118842243771396506390315925504n;
+
+const priceHash = keccak256(
+  Buffer.concat([contract, toBytes(timestamp, 16), toBytes(sharePrice, 16)])
+);
+const digest = keccak256(
+  Buffer.concat([Buffer.from("\x19Ethereum Signed Message:\n32"), priceHash])
+);
+console.log(
+  JSON.stringify(
+    {
+      signer: "0x" + address(key).toString("hex"),
+      price_hash: "0x" + priceHash.toString("hex"),
+      digest: "0x" + digest.toString("hex"),
+      signature: "0x" + sign(digest, key).toString("hex"),
+    },
+    null,
+    2
+  )
+);
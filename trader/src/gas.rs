@@ -0,0 +1,229 @@
+use alloy::{
+    eips::BlockNumberOrTag, primitives::U256, providers::Provider, rpc::types::FeeHistory,
+};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+fn default_history_blocks() -> u64 {
+    10
+}
+
+fn default_reward_percentile() -> f64 {
+    50.0
+}
+
+fn default_base_fee_multiplier() -> u128 {
+    2
+}
+
+fn default_gas_headroom() -> u64 {
+    20
+}
+
+fn default_max_gas_limit() -> u64 {
+    4_000_000
+}
+
+fn default_trade_value() -> U256 {
+    // 0.03 ETH
+    U256::from(30_000_000_000_000_000u128)
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct GasConfig {
+    /// Number of latest blocks of `eth_feeHistory`
+    #[serde(default = "default_history_blocks")]
+    pub history_blocks: u64,
+    /// Percentile of priority fees paid in each block
+    #[serde(default = "default_reward_percentile")]
+    pub reward_percentile: f64,
+    #[serde(default)]
+    pub min_priority_fee: u128,
+    /// `max_fee_per_gas` covers this many times the next block base fee
+    #[serde(default = "default_base_fee_multiplier")]
+    pub base_fee_multiplier: u128,
+    /// Percent added to simulated gas usage for the gas limit
+    #[serde(default = "default_gas_headroom")]
+    pub gas_headroom: u64,
+    /// Gas limit of the simulation and upper bound of trade gas limits
+    #[serde(default = "default_max_gas_limit")]
+    pub max_gas_limit: u64,
+    /// Minimal profit in wei left after paying for gas
+    #[serde(default)]
+    pub min_profit: U256,
+    /// ETH sent along with trades
+    #[serde(default = "default_trade_value")]
+    pub trade_value: U256,
+}
+
+impl Default for GasConfig {
+    fn default() -> Self {
+        Self {
+            history_blocks: default_history_blocks(),
+            reward_percentile: default_reward_percentile(),
+            min_priority_fee: 0,
+            base_fee_multiplier: default_base_fee_multiplier(),
+            gas_headroom: default_gas_headroom(),
+            max_gas_limit: default_max_gas_limit(),
+            min_profit: U256::ZERO,
+            trade_value: default_trade_value(),
+        }
+    }
+}
+
+/// EIP-1559 fees of a trade
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fees {
+    pub base_fee: u128,
+    pub max_fee_per_gas: u128,
+    pub max_priority_fee_per_gas: u128,
+}
+
+impl Fees {
+    /// Price paid per gas if the trade is included in the next block
+    pub fn gas_price(&self) -> u128 {
+        (self.base_fee + self.max_priority_fee_per_gas).min(self.max_fee_per_gas)
+    }
+
+    pub fn gas_cost(&self, gas: U256) -> U256 {
+        gas * U256::from(self.gas_price())
+    }
+}
+
+pub struct GasOracle {
+    config: GasConfig,
+}
+
+impl GasOracle {
+    pub fn new(config: GasConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn config(&self) -> &GasConfig {
+        &self.config
+    }
+
+    pub async fn fees<P: Provider>(&self, rpc: &P) -> Result<Fees> {
+        let history = rpc
+            .get_fee_history(
+                self.config.history_blocks,
+                BlockNumberOrTag::Latest,
+                &[self.config.reward_percentile],
+            )
+            .await?;
+        self.fees_from_history(&history)
+    }
+
+    /// Priority fee is the median of per block rewards, max fee covers
+    /// `base_fee_multiplier` next block base fees on top of it
+    pub fn fees_from_history(&self, history: &FeeHistory) -> Result<Fees> {
+        let base_fee = history
+            .next_block_base_fee()
+            .context("Fee history has no base fee")?;
+        let mut rewards: Vec<u128> = history
+            .reward
+            .iter()
+            .flatten()
+            .filter_map(|block| block.first().copied())
+            .collect();
+        rewards.sort();
+        let priority_fee = rewards
+            .get(rewards.len() / 2)
+            .copied()
+            .unwrap_or_default()
+            .max(self.config.min_priority_fee);
+        Ok(Fees {
+            base_fee,
+            max_fee_per_gas: base_fee * self.config.base_fee_multiplier + priority_fee,
+            max_priority_fee_per_gas: priority_fee,
+        })
+    }
+
+    /// Simulated gas usage with headroom, capped by `max_gas_limit`
+    pub fn gas_limit(&self, gas_used: U256) -> u64 {
+        let gas_used = gas_used.saturating_to::<u64>();
+        gas_used
+            .saturating_add(gas_used * self.config.gas_headroom / 100)
+            .min(self.config.max_gas_limit)
+    }
+
+    /// Profit left after paying for `gas_used`, none if it is below `min_profit`
+    pub fn net_profit(&self, profit: U256, gas_used: U256, fees: &Fees) -> Option<U256> {
+        profit
+            .checked_sub(fees.gas_cost(gas_used))
+            .filter(|net| *net >= self.config.min_profit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(base_fees: Vec<u128>, rewards: Vec<u128>) -> FeeHistory {
+        FeeHistory {
+            base_fee_per_gas: base_fees,
+            reward: Some(rewards.into_iter().map(|reward| vec![reward]).collect()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn fees_from_history_test() {
+        let oracle = GasOracle::new(GasConfig::default());
+        let fees = oracle
+            .fees_from_history(&history(vec![100, 110, 120], vec![3, 1, 2]))
+            .unwrap();
+        assert_eq!(
+            fees,
+            Fees {
+                base_fee: 120,
+                max_fee_per_gas: 242,
+                max_priority_fee_per_gas: 2,
+            }
+        );
+        assert_eq!(fees.gas_price(), 122);
+        assert_eq!(fees.gas_cost(U256::from(10)), U256::from(1220));
+
+        let oracle = GasOracle::new(GasConfig {
+            min_priority_fee: 5,
+            ..Default::default()
+        });
+        let fees = oracle
+            .fees_from_history(&history(vec![100], Vec::new()))
+            .unwrap();
+        assert_eq!(fees.max_priority_fee_per_gas, 5);
+        assert_eq!(fees.max_fee_per_gas, 205);
+
+        assert!(oracle
+            .fees_from_history(&history(Vec::new(), Vec::new()))
+            .is_err());
+    }
+
+    #[test]
+    fn gas_limit_and_profit_test() {
+        let oracle = GasOracle::new(GasConfig {
+            min_profit: U256::from(1000),
+            ..Default::default()
+        });
+        assert_eq!(oracle.gas_limit(U256::from(200_000)), 240_000);
+        assert_eq!(oracle.gas_limit(U256::from(10_000_000)), 4_000_000);
+
+        let fees = Fees {
+            base_fee: 9,
+            max_fee_per_gas: 20,
+            max_priority_fee_per_gas: 1,
+        };
+        assert_eq!(
+            oracle.net_profit(U256::from(3000), U256::from(200), &fees),
+            Some(U256::from(1000))
+        );
+        assert_eq!(
+            oracle.net_profit(U256::from(2999), U256::from(200), &fees),
+            None
+        );
+        assert_eq!(
+            oracle.net_profit(U256::from(1000), U256::from(200), &fees),
+            None
+        );
+    }
+}
@@ -1,66 +1,115 @@
 use std::{collections::HashMap, sync::Arc, time::Duration};
 
-use crate::cache::cache::Cache;
-use crate::cache::multipool::Multipool;
 use crate::clickhouse::Click;
-use crate::contracts::{trader::Trader::OraclePrice, WETH_ADDRESS};
-use crate::trade::{AssetsChoise, TradingData};
-use alloy::primitives::{Address, Bytes, I256, U256};
+use crate::contracts::TraderContracts;
+use crate::gas::GasOracle;
+use crate::oracle::OracleSigner;
+use crate::strategies::{StrategiesConfig, Strategy};
+use crate::trade::TradingData;
+use crate::tx_manager::TxManager;
+use crate::uniswap::{route::RoutingConfig, simulator::Simulator};
 use alloy::providers::Provider;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use multipool::Multipool;
 use multipool_storage::hook::HookInitializer;
-use multipool_types::expiry::MayBeExpired;
-use tokio::{runtime::Handle, time::timeout};
+use multipool_storage::price_fetch::get_asset_prices;
+use multipool_types::expiry::{MayBeExpired, StdTimeExtractor};
+use tokio::runtime::Handle;
+use tokio::task::JoinHandle;
 
+/// Runs strategies of every multipool kept by `MultipoolStorage`
 #[derive(Clone)]
 pub struct TraderHook<P: Provider + Clone + 'static> {
     pub click: Arc<Click>,
     pub handle: Handle,
     pub rpc: P,
     pub task_timeout: Duration,
+    pub oracle: Arc<OracleSigner>,
+    pub strategies: StrategiesConfig,
+    pub routing: RoutingConfig,
+    pub simulator: Arc<Simulator>,
+    pub gas_oracle: Arc<GasOracle>,
+    pub tx_manager: Arc<TxManager>,
+    pub contracts: TraderContracts,
+    /// Delay between trading rounds of a multipool
+    pub delay: Duration,
+    pub multicall_chunk_size: usize,
 }
 
-pub async fn process_pool<P: Provider + Sync + Send + 'static>(
-    cache: Arc<Cache<P>>,
-    click: Arc<Click>,
-    multipool: Multipool,
-    task_timeout: Duration,
-) -> Result<()> {
-    println!("Initialized hook");
-        tokio::time::sleep(Duration::from_secs(3)).await;
-        let price = multipool.cap;
-        let signed_price = some_sign_method(&price);
-        let contract_address = multipool.address;
-        let asset_list = &multipool.assets_addresses;
+impl<P: Provider + Clone + 'static> HookInitializer for TraderHook<P> {
+    async fn initialize_hook<F: Fn() -> Multipool + Send + Sync + 'static>(
+        &mut self,
+        multipool: F,
+    ) -> Vec<JoinHandle<Result<()>>> {
+        let instance = self.clone();
+        vec![self.handle.spawn(async move {
+            println!("Initialized hook");
+            let strategies = instance
+                .strategies
+                .build::<P>(&multipool().contract_address());
+            loop {
+                let mp = multipool();
+                let address = mp.contract_address();
+                if let Err(e) = instance.process_pool(mp, &strategies).await {
+                    println!("Failed to process multipool {address}: {e:?}");
+                }
+                tokio::time::sleep(instance.delay).await;
+            }
+        })]
+    }
+}
+
+impl<P: Provider + Clone + 'static> TraderHook<P> {
+    /// Prices aren't emitted in events, they are read from the multipool
+    /// before each round and the share price is signed with them
+    async fn process_pool(
+        &self,
+        mut multipool: Multipool,
+        strategies: &[Box<dyn Strategy<P>>],
+    ) -> Result<()> {
+        let mut prices: Vec<_> = get_asset_prices(
+            multipool.contract_address(),
+            multipool.asset_list(),
+            self.multicall_chunk_size,
+            &self.rpc,
+        )
+        .await?
+        .into_iter()
+        .map(|(address, price)| (address, MayBeExpired::build::<StdTimeExtractor>(price)))
+        .collect();
+        // prices are merged with assets by address
+        prices.sort_by_key(|(address, _)| *address);
+        multipool.assets.sort_by_key(|asset| asset.address);
+        multipool.update_prices(&prices);
+
+        let share_price = multipool
+            .get_price(&multipool.contract_address())
+            .map_err(|e| anyhow!("Failed to get share price {e:?}"))?;
+        let oracle_price = self.oracle.sign(
+            multipool.contract_address(),
+            MayBeExpired::with_time(share_price.clone().any_age(), share_price.time()),
+        )?;
         let trading_data = Arc::new(TradingData {
-            rpc: cache.provider.clone(),
-            multipool: multipool.clone(),
+            rpc: self.rpc.clone(),
+            multipool,
             silo_assets: HashMap::new(),
-            oracle_price: OraclePrice {
-                contractAddress: contract_address,
-                // timestamp: price.time() as u128,
-                timestamp: 0,
-                sharePrice: price.to::<u128>(),
-                signature: signed_price,
-            },
-            weth: WETH_ADDRESS,
+            oracle_price,
+            contracts: self.contracts,
+            routing: self.routing.clone(),
+            simulator: self.simulator.clone(),
+            gas_oracle: self.gas_oracle.clone(),
+            tx_manager: self.tx_manager.clone(),
         });
-        for asset1 in asset_list.iter() {
-            for asset2 in asset_list.iter() {
-                if asset1 == asset2 {continue;}
-                let s = AssetsChoise {
-                    trading_data: trading_data.clone(),
-                    asset1: *asset1,
-                    asset2: *asset2,
-                    deviation_bound: I256::ZERO,
-                };
-                let click = click.clone();
-                tokio::spawn(async move {
+        for strategy in strategies {
+            for s in strategy.candidates(&trading_data) {
+                let click = self.click.clone();
+                let task_timeout = self.task_timeout;
+                self.handle.spawn(async move {
                     let err = s.estimate_multipool().await;
                     match err {
                         Ok(v) => match v.estimate_uniswap().await {
                             Ok(v) => {
-                                let r = timeout(task_timeout, v.execute(click)).await;
+                                let r = v.execute(click, task_timeout).await;
                                 println!("Send trade result: {r:?}");
                             }
                             Err(e) => {
@@ -74,7 +123,4 @@ pub async fn process_pool<P: Provider + Sync + Send + 'static>(
         }
         Ok(())
     }
-
-fn some_sign_method(_price: &U256) -> Bytes {
-    Bytes::new()
 }
@@ -1,33 +1,46 @@
 use crate::{
     clickhouse::{Click, ClickhouseConfig},
+    contracts::TraderContracts,
+    gas::{GasConfig, GasOracle},
     hook::TraderHook,
+    oracle::{OracleConfig, OracleSigner},
+    strategies::StrategiesConfig,
+    tx_manager::{TxManager, TxManagerConfig},
+    uniswap::{route::RoutingConfig, simulator::Simulator},
 };
 use alloy::network::EthereumWallet;
-use alloy::primitives::address;
 use alloy::signers::local::PrivateKeySigner;
-use alloy::{primitives::Address, providers::ProviderBuilder};
-use anyhow::anyhow;
+use alloy::providers::{Provider, ProviderBuilder};
 use anyhow::Context;
 use backend_service::ServiceData;
-use cache::cache::Cache;
-use multipool_storage::{pg::into_fetching_task, storage::MultipoolStorage};
-use multipool_types::messages::KafkaTopics;
+use multipool_storage::{pg::into_fetching_task, storage::MultipoolStorage, store::SledStore};
+use multipool_types::deployments::{Contract, Deployments, DEFAULT_DEPLOYMENTS_PATH};
 use reqwest::Url;
 use serde::Deserialize;
 use std::{sync::Arc, time::Duration};
 use tokio::runtime::Handle;
 
-pub mod cache;
-pub mod cashback;
 pub mod clickhouse;
 pub mod contracts;
 pub mod execution;
+pub mod failure;
+pub mod gas;
 pub mod hook;
+pub mod log_target;
+pub mod metrics;
+pub mod oracle;
 pub mod strategies;
 pub mod trade;
+pub mod tx_manager;
 pub mod uniswap;
 
-const FACTORY_ADDRESS: Address = address!("7eFe6656d08f2d6689Ed8ca8b5A3DEA0efaa769f");
+fn default_deployments() -> String {
+    DEFAULT_DEPLOYMENTS_PATH.into()
+}
+
+fn default_storage_path() -> String {
+    "trader_sled_db".into()
+}
 
 #[derive(Deserialize)]
 pub struct DbConfig {
@@ -37,14 +50,28 @@ pub struct DbConfig {
 #[derive(Deserialize)]
 pub struct TraderService {
     rpc_url: String,
+    /// Registry of contract addresses
+    #[serde(default = "default_deployments")]
+    deployments: String,
     database: Option<DbConfig>,
     pk_file: String,
     clickhouse: ClickhouseConfig,
+    oracle: OracleConfig,
+    #[serde(default)]
+    strategies: StrategiesConfig,
+    #[serde(default)]
+    routing: RoutingConfig,
+    #[serde(default)]
+    gas: GasConfig,
+    #[serde(default)]
+    transactions: TxManagerConfig,
+    /// Sled database with multipools built from indexed events
+    #[serde(default = "default_storage_path")]
+    storage_path: String,
 }
 
 impl ServiceData for TraderService {
     async fn run(self) -> anyhow::Result<()> {
-
         let database_env_key = self
             .database
             .map(|d| d.env_key)
@@ -56,30 +83,55 @@ impl ServiceData for TraderService {
         let pk =
             std::fs::read_to_string(self.pk_file).expect("Should have been able to read the file");
         let signer: PrivateKeySigner = pk.parse().expect("should parse private key");
+        let tx_manager = Arc::new(TxManager::new(signer.address(), self.transactions));
 
         let wallet = EthereumWallet::from(signer);
 
         let rpc = ProviderBuilder::new()
             .wallet(wallet)
             .connect_http(Url::parse(&self.rpc_url).unwrap());
+        let chain_id = rpc.get_chain_id().await?;
+        let deployments = Deployments::from_file(&self.deployments)?;
+        let deployment = deployments.get(chain_id)?;
+        let contracts = TraderContracts::resolve(deployment)?;
+        deployment.validate(&rpc).await?;
 
-        let cache = Arc::new(Cache::initialize(pool, Arc::new(rpc)).await.map_err(|e| anyhow!("Failed to initialize cache {e}"))?);
-        let click = Arc::new(Click::new(self.clickhouse).unwrap());
-        loop {
-            for mp in cache.mp_cache.iter() {
-                let inn_cache = cache.clone();
-                tokio::spawn(crate::hook::process_pool(inn_cache, click.clone(), mp.clone(), Duration::from_secs(10)));
+        let simulator = Arc::new(Simulator::new(self.routing.bitmap_words));
+        let sync_rpc = rpc.clone();
+        let sync_simulator = simulator.clone();
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = sync_simulator.sync(&sync_rpc).await {
+                    println!("Failed to sync pools {e:?}");
+                }
                 tokio::time::sleep(Duration::from_secs(10)).await;
             }
-        }
-        // let th = TraderHook {
-        //     click: ,
-        //     task_timeout: Duration::from_secs(2),
-        //     handle: Handle::current(),
-        //     rpc: ProviderBuilder::new()
-        //         .wallet(wallet)
-        //         .connect_http(Url::parse(&self.rpc_url).unwrap()),
-        // };
+        });
+
+        let hook = TraderHook {
+            click: Arc::new(Click::new(self.clickhouse).unwrap()),
+            handle: Handle::current(),
+            rpc,
+            task_timeout: Duration::from_secs(10),
+            oracle: Arc::new(OracleSigner::from_config(self.oracle)?),
+            strategies: self.strategies,
+            routing: self.routing,
+            simulator,
+            gas_oracle: Arc::new(GasOracle::new(self.gas)),
+            tx_manager,
+            contracts,
+            delay: Duration::from_secs(10),
+            multicall_chunk_size: 30,
+        };
+        // multipools are restored from the store and followed by indexed events
+        let db = sled::open(&self.storage_path)?;
+        let mut storage = MultipoolStorage::init(
+            SledStore::new(db)?,
+            hook,
+            deployment.get(Contract::MultipoolFactory)?,
+        )
+        .await?;
+        into_fetching_task(&mut storage, pool, Duration::from_secs(1), vec![chain_id]).await?;
 
         Ok(())
     }
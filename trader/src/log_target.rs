@@ -0,0 +1,14 @@
+use backend_service::logging::LogTarget;
+
+pub enum TraderTarget {
+    Execution,
+}
+
+impl LogTarget for TraderTarget {
+    fn target(&self) -> &str {
+        use TraderTarget::*;
+        match self {
+            Execution => "execution",
+        }
+    }
+}
@@ -0,0 +1,11 @@
+use backend_service::{
+    global,
+    metrics::{Counter, Meter},
+};
+
+lazy_static::lazy_static! {
+    pub static ref METER: Meter = global::meter("trader");
+
+    pub static ref TOTAL_SIMULATIONS: Counter<u64> = METER.u64_counter("total_simulations").build();
+    pub static ref TOTAL_TRADE_FAILURES: Counter<u64> = METER.u64_counter("total_trade_failures").build();
+}
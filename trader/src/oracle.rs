@@ -0,0 +1,174 @@
+use alloy::{
+    primitives::{keccak256, Address, Bytes, B256, U256},
+    signers::{local::PrivateKeySigner, SignerSync},
+};
+use anyhow::{anyhow, Context, Result};
+use multipool_types::expiry::{MayBeExpired, StdTimeExtractor};
+use serde::Deserialize;
+
+use crate::contracts::trader::Trader::OraclePrice;
+
+const DEFAULT_ENV_KEY: &str = "ORACLE_PK";
+
+#[derive(Deserialize)]
+pub struct OracleConfig {
+    /// File with hex encoded private key, `env_key` is used if not set
+    pk_file: Option<String>,
+    env_key: Option<String>,
+    /// Prices older than this many seconds are not signed
+    max_price_age: u64,
+}
+
+pub struct OracleSigner {
+    signer: PrivateKeySigner,
+    max_price_age: u64,
+}
+
+impl OracleSigner {
+    pub fn new(signer: PrivateKeySigner, max_price_age: u64) -> Self {
+        Self {
+            signer,
+            max_price_age,
+        }
+    }
+
+    pub fn from_config(config: OracleConfig) -> Result<Self> {
+        let pk = match config.pk_file {
+            Some(pk_file) => std::fs::read_to_string(&pk_file)
+                .context(format!("Failed to read oracle key from {pk_file}"))?,
+            None => {
+                let env_key = config.env_key.unwrap_or(DEFAULT_ENV_KEY.into());
+                std::env::var(&env_key).context(format!("{env_key} must be set"))?
+            }
+        };
+        let signer = pk
+            .trim()
+            .parse()
+            .map_err(|e| anyhow!("Failed to parse oracle key {e}"))?;
+        Ok(Self::new(signer, config.max_price_age))
+    }
+
+    pub fn address(&self) -> Address {
+        self.signer.address()
+    }
+
+    /// Hash of `abi.encodePacked(contractAddress, timestamp, sharePrice)`,
+    /// the multipool recovers signer from its EIP-191 signed message
+    pub fn price_hash(contract_address: Address, timestamp: u128, share_price: u128) -> B256 {
+        let mut packed = Vec::with_capacity(20 + 16 + 16);
+        packed.extend(contract_address.as_slice());
+        packed.extend(timestamp.to_be_bytes());
+        packed.extend(share_price.to_be_bytes());
+        keccak256(packed)
+    }
+
+    /// 65 bytes `r`, `s`, `v` signature of the price
+    pub fn sign_price(
+        &self,
+        contract_address: Address,
+        timestamp: u128,
+        share_price: u128,
+    ) -> Result<Bytes> {
+        let hash = Self::price_hash(contract_address, timestamp, share_price);
+        let signature = self.signer.sign_message_sync(hash.as_slice())?;
+        Ok(Bytes::from(signature.as_bytes()))
+    }
+
+    /// Signs share price of the multipool if it is not older than `max_price_age`
+    pub fn sign(
+        &self,
+        contract_address: Address,
+        price: MayBeExpired<U256, StdTimeExtractor>,
+    ) -> Result<OraclePrice> {
+        let timestamp = price.time() as u128;
+        let share_price: u128 = price
+            .not_older_than::<StdTimeExtractor>(self.max_price_age)
+            .context("Share price is too old to sign")?
+            .try_into()
+            .context("Share price doesn't fit in uint128")?;
+        Ok(OraclePrice {
+            contractAddress: contract_address,
+            timestamp,
+            sharePrice: share_price,
+            signature: self.sign_price(contract_address, timestamp, share_price)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::{address, b256, bytes, Signature};
+
+    use super::*;
+
+    // first anvil account
+    const KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    const CONTRACT: Address = address!("5FbDB2315678afecb367f032d93F642f64180aa3");
+    // 1.5 in X96
+    const PRICE: u128 = 118842243771396506390315925504;
+
+    fn oracle() -> OracleSigner {
+        OracleSigner::new(KEY.parse().unwrap(), 60)
+    }
+
+    #[test]
+    fn sign_price_test() {
+        let oracle = oracle();
+        assert_eq!(
+            oracle.address(),
+            address!("f39Fd6e51aad88F6F4ce6aB8827279cffFb92266")
+        );
+        assert_eq!(
+            OracleSigner::price_hash(CONTRACT, 1700000000, PRICE),
+            b256!("9491d814a20dde13f1a05c8c7efda5610f05ddd9cb06cee38934f20c074746b8")
+        );
+        assert_eq!(
+            oracle.sign_price(CONTRACT, 1700000000, PRICE).unwrap(),
+            bytes!("da7a5588e95f40433522dd266b12b14686a4e9496b507e0d4397af867a4d5fd00a40423764fe37f53f8196a42202793f26e519dd713c2af58d75f1df043c31a81c")
+        );
+    }
+
+    /// Signer as the multipool recovers it, `ECDSA.recover` of EIP-191 message hash
+    fn recover(hash: B256, signature: &[u8]) -> Address {
+        let mut message = b"\x19Ethereum Signed Message:\n32".to_vec();
+        message.extend(hash.as_slice());
+        let signature = Signature::try_from(signature).unwrap();
+        // OpenZeppelin ECDSA rejects signatures with high s
+        assert!(signature.normalize_s().is_none());
+        signature
+            .recover_address_from_prehash(&keccak256(message))
+            .unwrap()
+    }
+
+    #[test]
+    fn recover_price_signer_test() {
+        let oracle = oracle();
+        let hash = OracleSigner::price_hash(CONTRACT, 1700000000, PRICE);
+        // written by fixtures/oracle_signature.mjs, which doesn't use alloy
+        let external = bytes!("2fb45666f6046013361c509013553d44475d9a5159b81d472b44234115d6a5455ec8a960f75452d51fce6bdad14420efbe27d449fabd50c9b91d376039c952f81b");
+        assert_eq!(recover(hash, &external), oracle.address());
+        let signature = oracle.sign_price(CONTRACT, 1700000000, PRICE).unwrap();
+        assert_eq!(recover(hash, &signature), oracle.address());
+    }
+
+    #[test]
+    fn sign_fresh_price_test() {
+        let oracle = oracle();
+        let price = MayBeExpired::new(U256::from(PRICE));
+        let timestamp = price.time() as u128;
+        let signed = oracle.sign(CONTRACT, price).unwrap();
+        assert_eq!(signed.contractAddress, CONTRACT);
+        assert_eq!(signed.timestamp, timestamp);
+        assert_eq!(signed.sharePrice, PRICE);
+        assert_eq!(
+            signed.signature,
+            oracle.sign_price(CONTRACT, timestamp, PRICE).unwrap()
+        );
+    }
+
+    #[test]
+    fn reject_expired_price_test() {
+        let price = MayBeExpired::with_time(U256::from(PRICE), 1700000000);
+        assert!(oracle().sign(CONTRACT, price).is_err());
+    }
+}
@@ -0,0 +1,54 @@
+use std::sync::Arc;
+
+use alloy::{primitives::I256, providers::Provider};
+
+use super::{Strategy, StrategyKind};
+use crate::trade::{AssetsChoise, TradingData};
+
+/// Brings assets with collected cashbacks closer to target shares, the
+/// multipool pays cashback for decreasing their deviation
+pub struct CashbackCollection;
+
+impl<P: Provider> Strategy<P> for CashbackCollection {
+    fn kind(&self) -> StrategyKind {
+        StrategyKind::CashbackCollection
+    }
+
+    fn candidates(&self, trading_data: &Arc<TradingData<P>>) -> Vec<AssetsChoise<P>> {
+        let multipool = &trading_data.multipool;
+        let mut candidates = Vec::new();
+        for asset in multipool.assets.iter() {
+            if asset.collected_cashbacks.is_zero() {
+                continue;
+            }
+            // positive amount means the asset is below its target share
+            let Ok(amount) = multipool
+                .quantity_to_deviation(&asset.address, I256::ZERO)
+                .map(|amount| amount.any_age())
+            else {
+                continue;
+            };
+            if amount.is_zero() {
+                continue;
+            }
+            for other in multipool.asset_list() {
+                if other == asset.address {
+                    continue;
+                }
+                let (asset1, asset2) = if amount.is_positive() {
+                    (asset.address, other)
+                } else {
+                    (other, asset.address)
+                };
+                candidates.push(AssetsChoise {
+                    trading_data: trading_data.clone(),
+                    asset1,
+                    asset2,
+                    deviation_bound: I256::ZERO,
+                    strategy: StrategyKind::CashbackCollection,
+                });
+            }
+        }
+        candidates
+    }
+}
@@ -0,0 +1,31 @@
+use std::sync::Arc;
+
+use alloy::{primitives::I256, providers::Provider};
+
+use super::{Strategy, StrategyKind};
+use crate::trade::{AssetsChoise, TradingData};
+
+/// Moves value between every pair of assets until both reach target shares
+pub struct DeviationRebalance;
+
+impl<P: Provider> Strategy<P> for DeviationRebalance {
+    fn kind(&self) -> StrategyKind {
+        StrategyKind::DeviationRebalance
+    }
+
+    fn candidates(&self, trading_data: &Arc<TradingData<P>>) -> Vec<AssetsChoise<P>> {
+        let assets = trading_data.multipool.asset_list();
+        assets
+            .iter()
+            .flat_map(|asset1| assets.iter().map(move |asset2| (*asset1, *asset2)))
+            .filter(|(asset1, asset2)| asset1 != asset2)
+            .map(|(asset1, asset2)| AssetsChoise {
+                trading_data: trading_data.clone(),
+                asset1,
+                asset2,
+                deviation_bound: I256::ZERO,
+                strategy: StrategyKind::DeviationRebalance,
+            })
+            .collect()
+    }
+}
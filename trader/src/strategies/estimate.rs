@@ -0,0 +1,230 @@
+use alloy::{
+    dyn_abi::DynSolValue,
+    primitives::{Address, I256, U256},
+    providers::Provider,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use multipool::read::SwapKind;
+use multipool_types::expiry::StdTimeExtractor;
+use std::ops::Shr;
+
+use crate::{
+    contracts::{SiloLens, ERC20},
+    trade::{AssetsChoise, MultipoolChoise, WrapperCall},
+};
+
+impl<P: Provider + Clone> AssetsChoise<P> {
+    fn amount_to_deviation(&self, asset: &Address) -> Result<Option<I256>> {
+        if *asset == self.trading_data.multipool.contract_address() {
+            return Ok(None);
+        }
+        self.trading_data
+            .multipool
+            .quantity_to_deviation(asset, self.deviation_bound)
+            .map(|amount| Some(amount.any_age()))
+            .map_err(|v| anyhow!("{v:?}"))
+    }
+
+    /// Value that can be moved from `asset1` to `asset2` without crossing
+    /// `deviation_bound`. Shares are minted and burned in any amount, so on mint
+    /// and burn only the asset side is limited.
+    fn quote_to_use(&self, price1: U256, price2: U256) -> Result<U256> {
+        let amount1 = self.amount_to_deviation(&self.asset1)?;
+        let amount2 = self.amount_to_deviation(&self.asset2)?;
+
+        println!("{} -> {}", self.asset1, self.asset2);
+        println!("{:?} -> {:?}", amount1, amount2);
+
+        let quoted = |amount: I256, price: U256| -> Result<U256> {
+            Ok(U256::try_from(amount.abs())?
+                .checked_mul(price)
+                .ok_or(anyhow!("overflow"))?
+                .shr(96))
+        };
+
+        match (amount1, amount2) {
+            (Some(amount1), Some(amount2)) => {
+                if (amount1.is_positive() && amount2.is_positive())
+                    || (amount1.is_negative() && amount2.is_negative())
+                {
+                    bail!(anyhow!("same signs"));
+                }
+                if amount1.is_negative() {
+                    bail!(anyhow!("amount1 is neg"));
+                }
+                Ok(quoted(amount1, price1)?.min(quoted(amount2, price2)?))
+            }
+            (Some(amount1), None) => {
+                if !amount1.is_positive() {
+                    bail!(anyhow!("nothing to mint"));
+                }
+                quoted(amount1, price1)
+            }
+            (None, Some(amount2)) => {
+                if !amount2.is_negative() {
+                    bail!(anyhow!("nothing to burn"));
+                }
+                quoted(amount2, price2)
+            }
+            (None, None) => bail!(anyhow!("same assets")),
+        }
+    }
+
+    /// Amount received from the multipool for `amount_in` of `asset1` and the fee
+    /// sent with the call, as quoted by the multipool for a swap, mint or burn
+    fn quote_multipool(&self, amount_in: U256) -> Result<(U256, I256)> {
+        let multipool = &self.trading_data.multipool;
+        let address = multipool.contract_address();
+        let quote = if self.asset2 == address {
+            multipool
+                .quote_mint(&self.asset1, amount_in, SwapKind::ExactIn)
+                .map(|quote| quote.any_age())
+                .map(|quote| (quote.shares, quote.fee))
+        } else if self.asset1 == address {
+            multipool
+                .quote_burn(&self.asset2, amount_in, SwapKind::ExactIn)
+                .map(|quote| quote.any_age())
+                .map(|quote| (quote.amount, quote.fee))
+        } else {
+            multipool
+                .quote_swap(&self.asset1, &self.asset2, amount_in, SwapKind::ExactIn)
+                .map(|quote| quote.any_age())
+                .map(|quote| (quote.amount_out, quote.fee))
+        };
+        quote.map_err(|v| anyhow!("{v:?}"))
+    }
+
+    pub async fn estimate_multipool(self) -> Result<MultipoolChoise<P>> {
+        let price1 = self
+            .trading_data
+            .multipool
+            .get_price(&self.asset1)
+            .map_err(|v| anyhow!("{v:?}"))?
+            .any_age();
+        let price2 = self
+            .trading_data
+            .multipool
+            .get_price(&self.asset2)
+            .map_err(|v| anyhow!("{v:?}"))?
+            .any_age();
+
+        let quote_to_use = self.quote_to_use(price1, price2)?;
+
+        let multipool_amount_in = (quote_to_use << 96) / price1;
+        let (multipool_amount_out, fee) = self.quote_multipool(multipool_amount_in)?;
+
+        let (unwrapped_amount_in, swap_asset_in, wrap_call) = if let Some((silo_pool, base_asset)) =
+            self.trading_data.silo_assets.get(&self.asset1)
+        {
+            let total_supply = ERC20::new(self.asset1, self.trading_data.rpc.clone())
+                .totalSupply()
+                .call()
+                .await?;
+            let collected = SiloLens::new(
+                self.trading_data
+                    .contracts
+                    .silo_lens
+                    .context("Silo lens is not deployed")?,
+                self.trading_data.rpc.clone(),
+            )
+            .totalDepositsWithInterest(*silo_pool, *base_asset)
+            .call()
+            .await?;
+
+            (
+                multipool_amount_in * collected / total_supply,
+                *base_asset,
+                WrapperCall {
+                    wrapper: self
+                        .trading_data
+                        .contracts
+                        .silo_wrapper
+                        .context("Silo wrapper is not deployed")?,
+                    data: DynSolValue::Tuple(vec![
+                        DynSolValue::Address(*silo_pool),
+                        DynSolValue::Address(*base_asset),
+                        DynSolValue::Address(self.asset1),
+                    ])
+                    .abi_encode(),
+                },
+            )
+        } else {
+            (
+                multipool_amount_in,
+                self.asset1,
+                WrapperCall {
+                    wrapper: Address::ZERO,
+                    data: Vec::default(),
+                },
+            )
+        };
+
+        let (unwrapped_amount_out, swap_asset_out, unwrap_call) = if let Some((
+            silo_pool,
+            base_asset,
+        )) =
+            self.trading_data.silo_assets.get(&self.asset2)
+        {
+            let total_supply = ERC20::new(self.asset2, self.trading_data.rpc.clone())
+                .totalSupply()
+                .call()
+                .await?;
+
+            let collected = SiloLens::new(
+                self.trading_data
+                    .contracts
+                    .silo_lens
+                    .context("Silo lens is not deployed")?,
+                self.trading_data.rpc.clone(),
+            )
+            .totalDepositsWithInterest(*silo_pool, *base_asset)
+            .call()
+            .await?;
+
+            (
+                multipool_amount_out * collected / total_supply,
+                *base_asset,
+                WrapperCall {
+                    wrapper: self
+                        .trading_data
+                        .contracts
+                        .silo_wrapper
+                        .context("Silo wrapper is not deployed")?,
+                    data: DynSolValue::Tuple(vec![
+                        DynSolValue::Address(*silo_pool),
+                        DynSolValue::Address(*base_asset),
+                        DynSolValue::Address(self.asset2),
+                    ])
+                    .abi_encode(),
+                },
+            )
+        } else {
+            (
+                multipool_amount_out,
+                self.asset2,
+                WrapperCall {
+                    wrapper: Address::ZERO,
+                    data: Vec::default(),
+                },
+            )
+        };
+
+        Ok(MultipoolChoise {
+            trading_data_with_assets: self,
+
+            swap_asset_in,
+            swap_asset_out,
+
+            multipool_amount_in,
+            multipool_amount_out,
+
+            unwrapped_amount_in,
+            unwrapped_amount_out,
+
+            wrap_call,
+            unwrap_call,
+
+            fee,
+        })
+    }
+}
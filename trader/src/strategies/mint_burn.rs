@@ -0,0 +1,44 @@
+use std::sync::Arc;
+
+use alloy::{primitives::I256, providers::Provider};
+
+use super::{Strategy, StrategyKind};
+use crate::trade::{AssetsChoise, TradingData};
+
+/// Mints shares for assets below target share and burns shares for assets
+/// above it, shares are traded on their own Uniswap pools
+pub struct MintBurnArbitrage;
+
+impl<P: Provider> Strategy<P> for MintBurnArbitrage {
+    fn kind(&self) -> StrategyKind {
+        StrategyKind::MintBurnArbitrage
+    }
+
+    fn candidates(&self, trading_data: &Arc<TradingData<P>>) -> Vec<AssetsChoise<P>> {
+        let multipool = &trading_data.multipool;
+        multipool
+            .asset_list()
+            .into_iter()
+            .filter_map(|asset| {
+                let amount = multipool
+                    .quantity_to_deviation(&asset, I256::ZERO)
+                    .ok()?
+                    .any_age();
+                let (asset1, asset2) = if amount.is_positive() {
+                    (asset, multipool.contract_address())
+                } else if amount.is_negative() {
+                    (multipool.contract_address(), asset)
+                } else {
+                    return None;
+                };
+                Some(AssetsChoise {
+                    trading_data: trading_data.clone(),
+                    asset1,
+                    asset2,
+                    deviation_bound: I256::ZERO,
+                    strategy: StrategyKind::MintBurnArbitrage,
+                })
+            })
+            .collect()
+    }
+}
@@ -1,172 +1,128 @@
-use alloy::{
-    dyn_abi::DynSolValue,
-    primitives::{Address, I256, U256},
-    providers::Provider,
-};
-use anyhow::{anyhow, bail, Result};
-use multipool_types::expiry::StdTimeExtractor;
-use std::ops::Shr;
+use std::{collections::HashMap, sync::Arc};
+
+use alloy::{primitives::Address, providers::Provider};
+use serde::Deserialize;
 
 use crate::{
-    contracts::{SiloLens, ERC20, SILO_LENS, SILO_WRAPPER},
-    trade::{AssetsChoise, MultipoolChoise, WrapperCall},
+    contracts::TraderContracts,
+    trade::{AssetsChoise, TradingData},
 };
 
-impl<P: Provider + Clone> AssetsChoise<P> {
-    pub async fn estimate_multipool(self) -> Result<MultipoolChoise<P>> {
-        let price1 = self
-            .trading_data
-            .multipool
-            .get_price(&self.asset1)
-            .map_err(|v| anyhow!("{v:?}"))?;
-        let price2 = self
-            .trading_data
-            .multipool
-            .get_price(&self.asset2)
-            .map_err(|v| anyhow!("{v:?}"))?;
-
-        let amount1 = self
-            .trading_data
-            .multipool
-            .quantity_to_deviation(&self.asset1, self.deviation_bound)
-            .map_err(|v| anyhow!("{v:?}"))?;
-
-        let amount2 = self
-            .trading_data
-            .multipool
-            .quantity_to_deviation(&self.asset2, self.deviation_bound)
-            .map_err(|v| anyhow!("{v:?}"))?;
-
-        println!("{} -> {}", self.asset1, self.asset2);
-        println!("{} -> {}", amount1, amount2);
-
-        if (amount1.is_positive() && amount2.is_positive())
-            || (amount1.is_negative() && amount2.is_negative())
-        {
-            bail!(anyhow!("same signs"));
+pub mod cashback;
+pub mod deviation;
+pub mod estimate;
+pub mod mint_burn;
+
+pub use cashback::CashbackCollection;
+pub use deviation::DeviationRebalance;
+pub use mint_burn::MintBurnArbitrage;
+
+/// Produces candidate trades of a multipool. Every candidate is estimated
+/// against the multipool and Uniswap and executed if it is profitable.
+pub trait Strategy<P: Provider>: Send + Sync {
+    fn kind(&self) -> StrategyKind;
+
+    fn candidates(&self, trading_data: &Arc<TradingData<P>>) -> Vec<AssetsChoise<P>>;
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum StrategyKind {
+    DeviationRebalance,
+    CashbackCollection,
+    MintBurnArbitrage,
+}
+
+impl StrategyKind {
+    pub fn strategy<P: Provider>(&self) -> Box<dyn Strategy<P>> {
+        match self {
+            Self::DeviationRebalance => Box::new(DeviationRebalance),
+            Self::CashbackCollection => Box::new(CashbackCollection),
+            Self::MintBurnArbitrage => Box::new(MintBurnArbitrage),
+        }
+    }
+
+    /// Strategy type of stored trade stats
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::DeviationRebalance => "deviationRebalance",
+            Self::CashbackCollection => "collectCashbacks",
+            Self::MintBurnArbitrage => "mintBurnArbitrage",
+        }
+    }
+
+    /// Receiver of cashbacks paid by the multipool for the trade
+    pub fn cashback_receiver(&self, contracts: &TraderContracts) -> Address {
+        match self {
+            Self::CashbackCollection => contracts.cashback_vault,
+            _ => Address::ZERO,
         }
+    }
+}
+
+fn default_strategies() -> Vec<StrategyKind> {
+    vec![StrategyKind::DeviationRebalance]
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct StrategiesConfig {
+    /// Strategies of pools that are not listed in `pools`
+    #[serde(default = "default_strategies")]
+    default: Vec<StrategyKind>,
+    #[serde(default)]
+    pools: HashMap<Address, Vec<StrategyKind>>,
+}
 
-        if amount1.is_negative() {
-            bail!(anyhow!("amount1 is neg"));
+impl Default for StrategiesConfig {
+    fn default() -> Self {
+        Self {
+            default: default_strategies(),
+            pools: HashMap::new(),
         }
+    }
+}
+
+impl StrategiesConfig {
+    pub fn for_pool(&self, multipool: &Address) -> &[StrategyKind] {
+        self.pools.get(multipool).unwrap_or(&self.default)
+    }
+
+    /// Strategies run for `multipool`, built once when its hook starts
+    pub fn build<P: Provider>(&self, multipool: &Address) -> Vec<Box<dyn Strategy<P>>> {
+        self.for_pool(multipool)
+            .iter()
+            .map(StrategyKind::strategy)
+            .collect()
+    }
+}
 
-        let amount1 = U256::try_from(amount1.abs())?;
-        let amount2 = U256::try_from(amount2.abs())?;
-
-        let quoted_amount1: U256 = amount1
-            .checked_mul(price1)
-            .ok_or(anyhow!("overflow"))?
-            .shr(96);
-        let quoted_amount2: U256 = amount2
-            .checked_mul(price2)
-            .ok_or(anyhow!("overflow"))?
-            .shr(96);
-
-        let quote_to_use = quoted_amount1.min(quoted_amount2);
-
-        let amount_to_use = (quote_to_use << 96) / price1;
-
-        // TODO: calculate fee
-        let fee: I256 = I256::unchecked_from(100000000_u128);
-
-        let amount_of_in = I256::from_raw(amount_to_use);
-        let amount_of_out = I256::unchecked_from(-1000000);
-
-        let multipool_amount_in = U256::try_from(amount_of_in.abs())?;
-        let multipool_amount_out = U256::try_from(amount_of_out.abs())?;
-
-        let (unwrapped_amount_in, swap_asset_in, wrap_call) = if let Some((silo_pool, base_asset)) =
-            self.trading_data.silo_assets.get(&self.asset1)
-        {
-            let total_supply = ERC20::new(self.asset1, self.trading_data.rpc.clone())
-                .totalSupply()
-                .call()
-                .await?;
-            let collected = SiloLens::new(SILO_LENS, self.trading_data.rpc.clone())
-                .totalDepositsWithInterest(*silo_pool, *base_asset)
-                .call()
-                .await?;
-
-            (
-                multipool_amount_in * collected / total_supply,
-                *base_asset,
-                WrapperCall {
-                    wrapper: SILO_WRAPPER,
-                    data: DynSolValue::Tuple(vec![
-                        DynSolValue::Address(*silo_pool),
-                        DynSolValue::Address(*base_asset),
-                        DynSolValue::Address(self.asset1),
-                    ])
-                    .abi_encode(),
-                },
-            )
-        } else {
-            (
-                multipool_amount_in,
-                self.asset1,
-                WrapperCall {
-                    wrapper: Address::ZERO,
-                    data: Vec::default(),
-                },
-            )
-        };
-
-        let (unwrapped_amount_out, swap_asset_out, unwrap_call) = if let Some((
-            silo_pool,
-            base_asset,
-        )) =
-            self.trading_data.silo_assets.get(&self.asset2)
-        {
-            let total_supply = ERC20::new(self.asset2, self.trading_data.rpc.clone())
-                .totalSupply()
-                .call()
-                .await?;
-
-            let collected = SiloLens::new(SILO_LENS, self.trading_data.rpc.clone())
-                .totalDepositsWithInterest(*silo_pool, *base_asset)
-                .call()
-                .await?;
-
-            (
-                multipool_amount_out * collected / total_supply,
-                *base_asset,
-                WrapperCall {
-                    wrapper: SILO_WRAPPER,
-                    data: DynSolValue::Tuple(vec![
-                        DynSolValue::Address(*silo_pool),
-                        DynSolValue::Address(*base_asset),
-                        DynSolValue::Address(self.asset2),
-                    ])
-                    .abi_encode(),
-                },
-            )
-        } else {
-            (
-                multipool_amount_out,
-                self.asset2,
-                WrapperCall {
-                    wrapper: Address::ZERO,
-                    data: Vec::default(),
-                },
-            )
-        };
-
-        Ok(MultipoolChoise {
-            trading_data_with_assets: self,
-
-            swap_asset_in,
-            swap_asset_out,
-
-            multipool_amount_in,
-            multipool_amount_out,
-
-            unwrapped_amount_in,
-            unwrapped_amount_out,
-
-            wrap_call,
-            unwrap_call,
-
-            fee,
-        })
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::address;
+
+    use super::*;
+
+    #[test]
+    fn strategies_config_test() {
+        let config: StrategiesConfig = serde_yaml::from_str(
+            "
+            pools:
+              '0x1000000000000000000000000000000000000001':
+                - cashback_collection
+                - mint_burn_arbitrage
+            ",
+        )
+        .unwrap();
+        assert_eq!(
+            config.for_pool(&address!("1000000000000000000000000000000000000001")),
+            [
+                StrategyKind::CashbackCollection,
+                StrategyKind::MintBurnArbitrage
+            ]
+        );
+        assert_eq!(
+            config.for_pool(&Address::ZERO),
+            [StrategyKind::DeviationRebalance]
+        );
     }
 }
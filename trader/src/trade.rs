@@ -1,11 +1,17 @@
 use std::{collections::HashMap, sync::Arc};
 
-use crate::cache::multipool::Multipool;
 use crate::contracts::trader::Trader::OraclePrice;
+use crate::contracts::TraderContracts;
+use crate::gas::GasOracle;
+use crate::strategies::StrategyKind;
+use crate::tx_manager::TxManager;
+use crate::uniswap::route::{Route, RoutingConfig};
+use crate::uniswap::simulator::Simulator;
 use alloy::{
     primitives::{Address, I256, U256},
     providers::Provider,
 };
+use multipool::Multipool;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -27,7 +33,11 @@ pub struct TradingData<P: Provider> {
     pub multipool: Multipool,
     pub oracle_price: OraclePrice,
     pub silo_assets: HashMap<Address, (Address, Address)>,
-    pub weth: Address,
+    pub contracts: TraderContracts,
+    pub routing: RoutingConfig,
+    pub simulator: Arc<Simulator>,
+    pub gas_oracle: Arc<GasOracle>,
+    pub tx_manager: Arc<TxManager>,
 }
 
 pub struct AssetsChoise<P: Provider> {
@@ -35,6 +45,7 @@ pub struct AssetsChoise<P: Provider> {
     pub asset1: Address,
     pub asset2: Address,
     pub deviation_bound: I256,
+    pub strategy: StrategyKind,
 }
 
 pub struct WrapperCall {
@@ -65,6 +76,8 @@ pub struct SwapOutcome {
     pub best_pool: Address,
     pub zero_for_one: bool,
     pub best_fee: u32,
+    pub gas_estimate: U256,
+    pub route: Route,
 }
 
 pub struct UniswapChoise<P: Provider> {
@@ -0,0 +1,297 @@
+use std::time::Duration;
+
+use alloy::{
+    network::ReceiptResponse,
+    primitives::{Address, Log, TxHash, U256},
+    providers::Provider,
+    rpc::types::{TransactionReceipt, TransactionRequest},
+};
+use anyhow::Result;
+use serde::Deserialize;
+use tokio::{
+    sync::Mutex,
+    time::{sleep, Instant},
+};
+
+use crate::gas::Fees;
+
+fn default_replace_after() -> u64 {
+    12
+}
+
+fn default_max_replacements() -> u32 {
+    3
+}
+
+fn default_fee_bump() -> u128 {
+    15
+}
+
+fn default_poll_interval() -> u64 {
+    1000
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct TxManagerConfig {
+    /// Seconds to wait for a receipt before the transaction is replaced
+    #[serde(default = "default_replace_after")]
+    pub replace_after: u64,
+    /// Replacements with bumped fees before the transaction is cancelled
+    #[serde(default = "default_max_replacements")]
+    pub max_replacements: u32,
+    /// Percent added to both fees of each replacement, nodes require at least 10
+    #[serde(default = "default_fee_bump")]
+    pub fee_bump: u128,
+    /// Milliseconds between receipt requests
+    #[serde(default = "default_poll_interval")]
+    pub poll_interval: u64,
+}
+
+impl Default for TxManagerConfig {
+    fn default() -> Self {
+        Self {
+            replace_after: default_replace_after(),
+            max_replacements: default_max_replacements(),
+            fee_bump: default_fee_bump(),
+            poll_interval: default_poll_interval(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxStatus {
+    Success,
+    Reverted,
+    /// Replaced by an empty transfer to self
+    Cancelled,
+    /// No transaction with the nonce was included
+    Dropped,
+}
+
+impl TxStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::Reverted => "reverted",
+            Self::Cancelled => "cancelled",
+            Self::Dropped => "dropped",
+        }
+    }
+}
+
+/// Final state of a sent transaction, `hash` is the included replacement if any
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxOutcome {
+    pub hash: TxHash,
+    pub nonce: u64,
+    pub status: TxStatus,
+    pub gas_used: u64,
+    pub effective_gas_price: u128,
+    pub block_number: Option<u64>,
+    /// Logs emitted by the included transaction
+    pub logs: Vec<Log>,
+}
+
+impl TxOutcome {
+    pub fn gas_cost(&self) -> U256 {
+        U256::from(self.gas_used) * U256::from(self.effective_gas_price)
+    }
+}
+
+/// Fees of a replacement, both are raised by at least `percent` and 1 wei
+pub fn bump_fees(fees: &Fees, percent: u128) -> Fees {
+    let bump = |fee: u128| (fee * (100 + percent)).div_ceil(100).max(fee + 1);
+    Fees {
+        base_fee: fees.base_fee,
+        max_fee_per_gas: bump(fees.max_fee_per_gas),
+        max_priority_fee_per_gas: bump(fees.max_priority_fee_per_gas),
+    }
+}
+
+/// Sends transactions of the trader account. Nonces are assigned locally so
+/// concurrent trades don't race on them.
+pub struct TxManager {
+    address: Address,
+    config: TxManagerConfig,
+    /// Next nonce, requested from the node if unknown
+    nonce: Mutex<Option<u64>>,
+}
+
+impl TxManager {
+    pub fn new(address: Address, config: TxManagerConfig) -> Self {
+        Self {
+            address,
+            config,
+            nonce: Mutex::new(None),
+        }
+    }
+
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    pub async fn next_nonce<P: Provider>(&self, rpc: &P) -> Result<u64> {
+        let mut nonce = self.nonce.lock().await;
+        let next = match *nonce {
+            Some(next) => next,
+            None => rpc.get_transaction_count(self.address).pending().await?,
+        };
+        *nonce = Some(next + 1);
+        Ok(next)
+    }
+
+    /// Nonce is requested again after a transaction failed to take its place
+    pub async fn reset_nonce(&self) {
+        *self.nonce.lock().await = None;
+    }
+
+    /// Sends `tx` and waits for its receipt. Stuck transaction is replaced
+    /// with bumped fees, after `max_replacements` it is cancelled.
+    pub async fn send<P: Provider>(
+        &self,
+        rpc: &P,
+        tx: TransactionRequest,
+        mut fees: Fees,
+    ) -> Result<TxOutcome> {
+        let nonce = self.next_nonce(rpc).await?;
+        let tx = tx.from(self.address).nonce(nonce);
+        let cancellation = TransactionRequest::default()
+            .from(self.address)
+            .to(self.address)
+            .value(U256::ZERO)
+            .gas_limit(21_000)
+            .nonce(nonce);
+
+        let mut hashes = Vec::new();
+        let mut cancel_hash = None;
+        for attempt in 0..=self.config.max_replacements + 1 {
+            let cancel = attempt > self.config.max_replacements;
+            if attempt > 0 {
+                fees = bump_fees(&fees, self.config.fee_bump);
+            }
+            let request = if cancel { &cancellation } else { &tx }
+                .clone()
+                .max_fee_per_gas(fees.max_fee_per_gas)
+                .max_priority_fee_per_gas(fees.max_priority_fee_per_gas);
+            match rpc.send_transaction(request).await {
+                Ok(pending) => {
+                    let hash = *pending.tx_hash();
+                    println!("Sent transaction {hash} with nonce {nonce}, attempt {attempt}");
+                    hashes.push(hash);
+                    if cancel {
+                        cancel_hash = Some(hash);
+                    }
+                }
+                Err(e) if hashes.is_empty() => {
+                    self.reset_nonce().await;
+                    return Err(e.into());
+                }
+                // an earlier transaction may have been included meanwhile
+                Err(e) => println!("Failed to replace transaction with nonce {nonce}: {e:?}"),
+            }
+            if let Some(receipt) = self.wait_receipt(rpc, &hashes).await {
+                return Ok(Self::outcome(nonce, &receipt, cancel_hash));
+            }
+        }
+
+        self.reset_nonce().await;
+        Ok(TxOutcome {
+            hash: hashes[0],
+            nonce,
+            status: TxStatus::Dropped,
+            gas_used: 0,
+            effective_gas_price: 0,
+            block_number: None,
+            logs: Vec::new(),
+        })
+    }
+
+    async fn wait_receipt<P: Provider>(
+        &self,
+        rpc: &P,
+        hashes: &[TxHash],
+    ) -> Option<TransactionReceipt> {
+        let deadline = Instant::now() + Duration::from_secs(self.config.replace_after);
+        loop {
+            for hash in hashes {
+                match rpc.get_transaction_receipt(*hash).await {
+                    Ok(Some(receipt)) => return Some(receipt),
+                    Ok(None) => {}
+                    Err(e) => println!("Failed to get receipt of {hash}: {e:?}"),
+                }
+            }
+            if Instant::now() >= deadline {
+                return None;
+            }
+            sleep(Duration::from_millis(self.config.poll_interval)).await;
+        }
+    }
+
+    fn outcome(nonce: u64, receipt: &TransactionReceipt, cancel_hash: Option<TxHash>) -> TxOutcome {
+        let hash = receipt.transaction_hash();
+        let status = if Some(hash) == cancel_hash {
+            TxStatus::Cancelled
+        } else if receipt.status() {
+            TxStatus::Success
+        } else {
+            TxStatus::Reverted
+        };
+        TxOutcome {
+            hash,
+            nonce,
+            status,
+            gas_used: receipt.gas_used(),
+            effective_gas_price: receipt.effective_gas_price(),
+            block_number: receipt.block_number(),
+            logs: receipt
+                .inner
+                .logs()
+                .iter()
+                .map(|log| log.inner.clone())
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{primitives::address, providers::ProviderBuilder, transports::mock::Asserter};
+
+    use super::*;
+
+    #[test]
+    fn bump_fees_test() {
+        let fees = Fees {
+            base_fee: 100,
+            max_fee_per_gas: 201,
+            max_priority_fee_per_gas: 1,
+        };
+        assert_eq!(
+            bump_fees(&fees, 15),
+            Fees {
+                base_fee: 100,
+                max_fee_per_gas: 232,
+                max_priority_fee_per_gas: 2,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn nonce_test() {
+        let asserter = Asserter::new();
+        let rpc = ProviderBuilder::new().connect_mocked_client(asserter.clone());
+        let manager = TxManager::new(
+            address!("f39Fd6e51aad88F6F4ce6aB8827279cffFb92266"),
+            TxManagerConfig::default(),
+        );
+
+        // only the first nonce is requested from the node
+        asserter.push_success(&U256::from(7));
+        assert_eq!(manager.next_nonce(&rpc).await.unwrap(), 7);
+        assert_eq!(manager.next_nonce(&rpc).await.unwrap(), 8);
+
+        manager.reset_nonce().await;
+        asserter.push_success(&U256::from(8));
+        assert_eq!(manager.next_nonce(&rpc).await.unwrap(), 8);
+    }
+}
@@ -0,0 +1,402 @@
+//! Port of Uniswap V3 core libraries that are used by `UniswapV3Pool.swap`.
+//! Prices are Q64.96 square roots of token1/token0 ratio.
+
+use alloy::primitives::{U256, U512};
+use anyhow::{anyhow, ensure, Result};
+
+pub const MIN_TICK: i32 = -887272;
+pub const MAX_TICK: i32 = -MIN_TICK;
+pub const MIN_SQRT_RATIO: U256 = U256::from_limbs([4295128739, 0, 0, 0]);
+pub const MAX_SQRT_RATIO: U256 =
+    U256::from_limbs([0x5d951d5263988d26, 0xefd1fc6a50648849, 0xfffd8963, 0]);
+const Q96: U256 = U256::from_limbs([0, 1 << 32, 0, 0]);
+const FEE_DENOMINATOR: u32 = 1_000_000;
+
+/// `FullMath.mulDiv`, `a * b / denominator` with 512 bit intermediate product
+pub fn mul_div(a: U256, b: U256, denominator: U256) -> Result<U256> {
+    ensure!(!denominator.is_zero(), "Division by zero");
+    let result = U512::from(a) * U512::from(b) / U512::from(denominator);
+    ensure!(result.bit_len() <= 256, "mulDiv overflow");
+    Ok(result.to())
+}
+
+/// `FullMath.mulDivRoundingUp`
+pub fn mul_div_rounding_up(a: U256, b: U256, denominator: U256) -> Result<U256> {
+    let result = mul_div(a, b, denominator)?;
+    if a.mul_mod(b, denominator).is_zero() {
+        Ok(result)
+    } else {
+        result
+            .checked_add(U256::from(1))
+            .ok_or(anyhow!("mulDiv overflow"))
+    }
+}
+
+fn div_rounding_up(a: U256, b: U256) -> U256 {
+    a.div_ceil(b)
+}
+
+/// `LiquidityMath.addDelta`
+pub fn add_delta(liquidity: u128, delta: i128) -> Result<u128> {
+    liquidity
+        .checked_add_signed(delta)
+        .ok_or(anyhow!("Liquidity overflow"))
+}
+
+/// `TickMath.getSqrtRatioAtTick`
+pub fn get_sqrt_ratio_at_tick(tick: i32) -> Result<U256> {
+    let abs_tick = tick.unsigned_abs();
+    ensure!(abs_tick <= MAX_TICK as u32, "Tick out of range");
+
+    const FACTORS: [u128; 19] = [
+        0xfff97272373d413259a46990580e213a,
+        0xfff2e50f5f656932ef12357cf3c7fdcc,
+        0xffe5caca7e10e4e61c3624eaa0941cd0,
+        0xffcb9843d60f6159c9db58835c926644,
+        0xff973b41fa98c081472e6896dfb254c0,
+        0xff2ea16466c96a3843ec78b326b52861,
+        0xfe5dee046a99a2a811c461f1969c3053,
+        0xfcbe86c7900a88aedcffc83b479aa3a4,
+        0xf987a7253ac413176f2b074cf7815e54,
+        0xf3392b0822b70005940c7a398e4b70f3,
+        0xe7159475a2c29b7443b29c7fa6e889d9,
+        0xd097f3bdfd2022b8845ad8f792aa5825,
+        0xa9f746462d870fdf8a65dc1f90e061e5,
+        0x70d869a156d2a1b890bb3df62baf32f7,
+        0x31be135f97d08fd981231505542fcfa6,
+        0x9aa508b5b7a84e1c677de54f3e99bc9,
+        0x5d6af8dedb81196699c329225ee604,
+        0x2216e584f5fa1ea926041bedfe98,
+        0x48a170391f7dc42444e8fa2,
+    ];
+    let mut ratio = if abs_tick & 1 != 0 {
+        U256::from(0xfffcb933bd6fad37aa2d162d1a594001_u128)
+    } else {
+        U256::from(1) << 128
+    };
+    for (bit, factor) in FACTORS.iter().enumerate() {
+        if abs_tick & (2 << bit) != 0 {
+            ratio = (ratio * U256::from(*factor)) >> 128;
+        }
+    }
+    if tick > 0 {
+        ratio = U256::MAX / ratio;
+    }
+    // rounds up to be sure that getTickAtSqrtRatio of the result is the tick
+    let remainder: U256 = ratio % (U256::from(1) << 32);
+    Ok((ratio >> 32) + U256::from(u8::from(!remainder.is_zero())))
+}
+
+/// `TickMath.getTickAtSqrtRatio`, the greatest tick with ratio not above `sqrt_price_x96`
+pub fn get_tick_at_sqrt_ratio(sqrt_price_x96: U256) -> Result<i32> {
+    ensure!(
+        sqrt_price_x96 >= MIN_SQRT_RATIO && sqrt_price_x96 < MAX_SQRT_RATIO,
+        "Sqrt price out of range"
+    );
+    let (mut low, mut high) = (MIN_TICK, MAX_TICK);
+    while low < high {
+        let middle = low + (high - low + 1) / 2;
+        if get_sqrt_ratio_at_tick(middle)? <= sqrt_price_x96 {
+            low = middle;
+        } else {
+            high = middle - 1;
+        }
+    }
+    Ok(low)
+}
+
+fn to_uint160(value: U256) -> Result<U256> {
+    ensure!(value.bit_len() <= 160, "Sqrt price overflow");
+    Ok(value)
+}
+
+/// `SqrtPriceMath.getNextSqrtPriceFromAmount0RoundingUp`
+fn next_sqrt_price_from_amount0(
+    sqrt_price_x96: U256,
+    liquidity: u128,
+    amount: U256,
+    add: bool,
+) -> Result<U256> {
+    if amount.is_zero() {
+        return Ok(sqrt_price_x96);
+    }
+    let numerator1: U256 = U256::from(liquidity) << 96;
+    let product = amount.checked_mul(sqrt_price_x96);
+    if add {
+        if let Some(denominator) = product.and_then(|product| numerator1.checked_add(product)) {
+            return mul_div_rounding_up(numerator1, sqrt_price_x96, denominator);
+        }
+        Ok(div_rounding_up(
+            numerator1,
+            numerator1 / sqrt_price_x96 + amount,
+        ))
+    } else {
+        let product = product.ok_or(anyhow!("Sqrt price underflow"))?;
+        ensure!(numerator1 > product, "Sqrt price underflow");
+        to_uint160(mul_div_rounding_up(
+            numerator1,
+            sqrt_price_x96,
+            numerator1 - product,
+        )?)
+    }
+}
+
+/// `SqrtPriceMath.getNextSqrtPriceFromAmount1RoundingDown`
+fn next_sqrt_price_from_amount1(
+    sqrt_price_x96: U256,
+    liquidity: u128,
+    amount: U256,
+    add: bool,
+) -> Result<U256> {
+    let liquidity = U256::from(liquidity);
+    let fits_u160 = amount.bit_len() <= 160;
+    if add {
+        let quotient = if fits_u160 {
+            (amount << 96) / liquidity
+        } else {
+            mul_div(amount, Q96, liquidity)?
+        };
+        to_uint160(
+            sqrt_price_x96
+                .checked_add(quotient)
+                .ok_or(anyhow!("Sqrt price overflow"))?,
+        )
+    } else {
+        let quotient = if fits_u160 {
+            div_rounding_up(amount << 96, liquidity)
+        } else {
+            mul_div_rounding_up(amount, Q96, liquidity)?
+        };
+        ensure!(sqrt_price_x96 > quotient, "Sqrt price underflow");
+        Ok(sqrt_price_x96 - quotient)
+    }
+}
+
+/// `SqrtPriceMath.getNextSqrtPriceFromInput`
+pub fn next_sqrt_price_from_input(
+    sqrt_price_x96: U256,
+    liquidity: u128,
+    amount_in: U256,
+    zero_for_one: bool,
+) -> Result<U256> {
+    ensure!(!sqrt_price_x96.is_zero() && liquidity > 0, "No liquidity");
+    if zero_for_one {
+        next_sqrt_price_from_amount0(sqrt_price_x96, liquidity, amount_in, true)
+    } else {
+        next_sqrt_price_from_amount1(sqrt_price_x96, liquidity, amount_in, true)
+    }
+}
+
+/// `SqrtPriceMath.getNextSqrtPriceFromOutput`
+pub fn next_sqrt_price_from_output(
+    sqrt_price_x96: U256,
+    liquidity: u128,
+    amount_out: U256,
+    zero_for_one: bool,
+) -> Result<U256> {
+    ensure!(!sqrt_price_x96.is_zero() && liquidity > 0, "No liquidity");
+    if zero_for_one {
+        next_sqrt_price_from_amount1(sqrt_price_x96, liquidity, amount_out, false)
+    } else {
+        next_sqrt_price_from_amount0(sqrt_price_x96, liquidity, amount_out, false)
+    }
+}
+
+/// `SqrtPriceMath.getAmount0Delta`
+pub fn amount0_delta(
+    sqrt_ratio_a: U256,
+    sqrt_ratio_b: U256,
+    liquidity: u128,
+    round_up: bool,
+) -> Result<U256> {
+    let (a, b) = if sqrt_ratio_a > sqrt_ratio_b {
+        (sqrt_ratio_b, sqrt_ratio_a)
+    } else {
+        (sqrt_ratio_a, sqrt_ratio_b)
+    };
+    ensure!(!a.is_zero(), "Zero sqrt price");
+    let numerator1: U256 = U256::from(liquidity) << 96;
+    let numerator2 = b - a;
+    if round_up {
+        Ok(div_rounding_up(
+            mul_div_rounding_up(numerator1, numerator2, b)?,
+            a,
+        ))
+    } else {
+        Ok(mul_div(numerator1, numerator2, b)? / a)
+    }
+}
+
+/// `SqrtPriceMath.getAmount1Delta`
+pub fn amount1_delta(
+    sqrt_ratio_a: U256,
+    sqrt_ratio_b: U256,
+    liquidity: u128,
+    round_up: bool,
+) -> Result<U256> {
+    let (a, b) = if sqrt_ratio_a > sqrt_ratio_b {
+        (sqrt_ratio_b, sqrt_ratio_a)
+    } else {
+        (sqrt_ratio_a, sqrt_ratio_b)
+    };
+    if round_up {
+        mul_div_rounding_up(U256::from(liquidity), b - a, Q96)
+    } else {
+        mul_div(U256::from(liquidity), b - a, Q96)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwapStep {
+    pub sqrt_price_next_x96: U256,
+    pub amount_in: U256,
+    pub amount_out: U256,
+    pub fee_amount: U256,
+}
+
+/// `SwapMath.computeSwapStep`, `amount_remaining` is positive for exact input
+pub fn compute_swap_step(
+    sqrt_price_current_x96: U256,
+    sqrt_price_target_x96: U256,
+    liquidity: u128,
+    amount_remaining: alloy::primitives::I256,
+    fee_pips: u32,
+) -> Result<SwapStep> {
+    let zero_for_one = sqrt_price_current_x96 >= sqrt_price_target_x96;
+    let exact_in = !amount_remaining.is_negative();
+    let remaining = amount_remaining.unsigned_abs();
+    let fee_pips = U256::from(fee_pips);
+    let fee_complement = U256::from(FEE_DENOMINATOR) - fee_pips;
+
+    let mut amount_in = U256::ZERO;
+    let mut amount_out = U256::ZERO;
+    let sqrt_price_next_x96 = if exact_in {
+        let remaining_less_fee = mul_div(remaining, fee_complement, U256::from(FEE_DENOMINATOR))?;
+        amount_in = if zero_for_one {
+            amount0_delta(
+                sqrt_price_target_x96,
+                sqrt_price_current_x96,
+                liquidity,
+                true,
+            )?
+        } else {
+            amount1_delta(
+                sqrt_price_current_x96,
+                sqrt_price_target_x96,
+                liquidity,
+                true,
+            )?
+        };
+        if remaining_less_fee >= amount_in {
+            sqrt_price_target_x96
+        } else {
+            next_sqrt_price_from_input(
+                sqrt_price_current_x96,
+                liquidity,
+                remaining_less_fee,
+                zero_for_one,
+            )?
+        }
+    } else {
+        amount_out = if zero_for_one {
+            amount1_delta(
+                sqrt_price_target_x96,
+                sqrt_price_current_x96,
+                liquidity,
+                false,
+            )?
+        } else {
+            amount0_delta(
+                sqrt_price_current_x96,
+                sqrt_price_target_x96,
+                liquidity,
+                false,
+            )?
+        };
+        if remaining >= amount_out {
+            sqrt_price_target_x96
+        } else {
+            next_sqrt_price_from_output(sqrt_price_current_x96, liquidity, remaining, zero_for_one)?
+        }
+    };
+
+    let max = sqrt_price_target_x96 == sqrt_price_next_x96;
+    if zero_for_one {
+        if !(max && exact_in) {
+            amount_in =
+                amount0_delta(sqrt_price_next_x96, sqrt_price_current_x96, liquidity, true)?;
+        }
+        if !max || exact_in {
+            amount_out = amount1_delta(
+                sqrt_price_next_x96,
+                sqrt_price_current_x96,
+                liquidity,
+                false,
+            )?;
+        }
+    } else {
+        if !(max && exact_in) {
+            amount_in =
+                amount1_delta(sqrt_price_current_x96, sqrt_price_next_x96, liquidity, true)?;
+        }
+        if !max || exact_in {
+            amount_out = amount0_delta(
+                sqrt_price_current_x96,
+                sqrt_price_next_x96,
+                liquidity,
+                false,
+            )?;
+        }
+    }
+
+    if !exact_in && amount_out > remaining {
+        amount_out = remaining;
+    }
+    let fee_amount = if exact_in && sqrt_price_next_x96 != sqrt_price_target_x96 {
+        remaining - amount_in
+    } else {
+        mul_div_rounding_up(amount_in, fee_pips, fee_complement)?
+    };
+    Ok(SwapStep {
+        sqrt_price_next_x96,
+        amount_in,
+        amount_out,
+        fee_amount,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sqrt_ratio_bounds_test() {
+        assert_eq!(get_sqrt_ratio_at_tick(MIN_TICK).unwrap(), MIN_SQRT_RATIO);
+        assert_eq!(get_sqrt_ratio_at_tick(MAX_TICK).unwrap(), MAX_SQRT_RATIO);
+        assert_eq!(
+            MAX_SQRT_RATIO,
+            "1461446703485210103287273052203988822378723970342"
+                .parse::<U256>()
+                .unwrap()
+        );
+        assert_eq!(get_sqrt_ratio_at_tick(0).unwrap(), Q96);
+        assert!(get_sqrt_ratio_at_tick(MAX_TICK + 1).is_err());
+    }
+
+    #[test]
+    fn tick_at_sqrt_ratio_test() {
+        assert_eq!(get_tick_at_sqrt_ratio(MIN_SQRT_RATIO).unwrap(), MIN_TICK);
+        assert_eq!(
+            get_tick_at_sqrt_ratio(MAX_SQRT_RATIO - U256::from(1)).unwrap(),
+            MAX_TICK - 1
+        );
+        for tick in [-200000, -60, -1, 0, 1, 887, 200000] {
+            let ratio = get_sqrt_ratio_at_tick(tick).unwrap();
+            assert_eq!(get_tick_at_sqrt_ratio(ratio).unwrap(), tick);
+            assert_eq!(
+                get_tick_at_sqrt_ratio(ratio - U256::from(1)).unwrap(),
+                tick - 1
+            );
+        }
+    }
+}
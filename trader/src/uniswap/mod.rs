@@ -1,25 +1,20 @@
 use alloy::dyn_abi::DynSolValue;
-use alloy::primitives::{Address, U160};
+use alloy::primitives::Address;
 use alloy::providers::Provider;
 use anyhow::{anyhow, Result};
-use compute_address::{compute_pool_address, FeeAmount, FACTORY_ADDRESS};
+use route::{find_routes, Route};
+use simulator::gas_estimate;
 
-use crate::contracts::IQuoterV2::{QuoteExactInputSingleParams, QuoteExactOutputSingleParams};
-use crate::contracts::{Quoter, MULTICALL_ADDRESS, QUOTERV2_ADDRESS, WETH_ADDRESS};
+use crate::contracts::Quoter;
 use crate::trade::{MultipoolChoise, SwapOutcome, UniswapChoise};
 use alloy::primitives::aliases::U256;
-use alloy::providers::Failure;
 use alloy::providers::MulticallBuilder;
-use alloy::providers::{CallItem, MulticallItem};
 
 mod compute_address;
-
-#[derive(Debug)]
-pub struct PoolSwapData {
-    address: Address,
-    fee: u32,
-    base_is_token0: bool,
-}
+pub mod math;
+pub mod pool;
+pub mod route;
+pub mod simulator;
 
 pub enum AmountWithDirection {
     ExactInput(U256),
@@ -27,115 +22,146 @@ pub enum AmountWithDirection {
 }
 
 impl<P: Provider> MultipoolChoise<P> {
+    /// Finds route buying `swap_asset_in` for the least WETH and route selling
+    /// `swap_asset_out` for the most WETH, both net of gas
     pub async fn estimate_uniswap(self) -> Result<UniswapChoise<P>> {
-        let asset1 = self.swap_asset_in;
-        let asset2 = self.swap_asset_out;
-        let rpc = &self.trading_data_with_assets.trading_data.rpc;
-        let q = Quoter::new(QUOTERV2_ADDRESS, rpc);
-        let (in_pools, in_calls): (
-            Vec<PoolSwapData>,
-            Vec<CallItem<Quoter::quoteExactOutputSingleCall>>,
-        ) = FeeAmount::iter()
-            .map(|fee| {
-                let input_uniswap_pool = compute_pool_address(
-                    FACTORY_ADDRESS,
-                    WETH_ADDRESS,
-                    self.swap_asset_in,
-                    fee,
-                    None,
-                );
-                let pool = PoolSwapData {
-                    address: input_uniswap_pool,
-                    fee: fee.into(),
-                    base_is_token0: WETH_ADDRESS < self.swap_asset_in,
-                };
-                let call = q.quoteExactOutputSingle(QuoteExactOutputSingleParams {
-                    tokenIn: WETH_ADDRESS,
-                    tokenOut: asset1,
-                    amount: self.unwrapped_amount_in,
-                    fee: fee.into(),
-                    sqrtPriceLimitX96: U160::ZERO,
-                });
-                let target = call.target();
-                let input = call.input();
-                (
-                    pool,
-                    CallItem::<Quoter::quoteExactOutputSingleCall>::new(target, input),
-                )
-            })
-            .unzip();
-        let (out_pools, out_calls): (
-            Vec<PoolSwapData>,
-            Vec<CallItem<Quoter::quoteExactOutputSingleCall>>,
-        ) = FeeAmount::iter()
-            .map(|fee| {
-                let output_uniswap_pool = compute_pool_address(
-                    FACTORY_ADDRESS,
-                    self.swap_asset_out,
-                    WETH_ADDRESS,
-                    fee,
-                    None,
-                );
+        let trading_data = &self.trading_data_with_assets.trading_data;
+        let rpc = &trading_data.rpc;
+        let simulator = &trading_data.simulator;
+        let contracts = &trading_data.contracts;
+        let q = Quoter::new(contracts.quoter, rpc);
+        let gas_price = U256::from(trading_data.gas_oracle.fees(rpc).await?.gas_price());
 
-                let pool = PoolSwapData {
-                    address: output_uniswap_pool,
-                    fee: fee.into(),
-                    base_is_token0: WETH_ADDRESS < self.swap_asset_out,
-                };
-                let call = q.quoteExactInputSingle(QuoteExactInputSingleParams {
-                    tokenIn: asset2,
-                    tokenOut: WETH_ADDRESS,
-                    amountIn: self.unwrapped_amount_out,
-                    fee: fee.into(),
-                    sqrtPriceLimitX96: U160::ZERO,
-                });
-                let target = call.target();
-                let input = call.input();
-                (
-                    pool,
-                    CallItem::<Quoter::quoteExactOutputSingleCall>::new(target, input),
-                )
-            })
-            .unzip();
+        let in_routes = find_routes(
+            contracts.uniswap_factory,
+            contracts.weth,
+            self.swap_asset_in,
+        );
+        let out_routes = find_routes(
+            contracts.uniswap_factory,
+            self.swap_asset_out,
+            contracts.weth,
+        );
+        let mut pools: Vec<Address> = in_routes
+            .iter()
+            .chain(&out_routes)
+            .flat_map(|route| route.hops())
+            .map(|hop| hop.pool)
+            .collect();
+        pools.sort();
+        pools.dedup();
+        if let Err(e) = simulator.load(rpc, &pools).await {
+            println!("Failed to load pools {e:?}");
+        }
 
-        let result = MulticallBuilder::new_dynamic(rpc)
-            .extend_calls(in_calls)
-            .extend_calls(out_calls)
-            .try_aggregate(false)
-            .await?
-            .into_iter()
-            .map(|r| r.map(|v| v.amountIn))
-            .collect::<Vec<Result<U256, Failure>>>();
-        println!("Result ------ {:?}", result);
+        // quoter is called only for routes that can't be quoted locally
+        let mut inputs = Vec::new();
+        let mut in_remote = Vec::new();
+        for route in in_routes {
+            match simulator.quote_exact_output(&route, self.unwrapped_amount_in) {
+                Ok(quote) => {
+                    let gas = gas_estimate(route.fees.len(), quote.ticks_crossed);
+                    inputs.push((route, quote.amount, gas));
+                }
+                Err(_) if simulator.is_missing(&route) => {}
+                Err(_) => in_remote.push(route),
+            }
+        }
+        let mut outputs = Vec::new();
+        let mut out_remote = Vec::new();
+        for route in out_routes {
+            match simulator.quote_exact_input(&route, self.unwrapped_amount_out) {
+                Ok(quote) => {
+                    let gas = gas_estimate(route.fees.len(), quote.ticks_crossed);
+                    outputs.push((route, quote.amount, gas));
+                }
+                Err(_) if simulator.is_missing(&route) => {}
+                Err(_) => out_remote.push(route),
+            }
+        }
 
-        let (best_input_pool, input_value) = in_pools
+        let in_calls: Vec<_> = in_remote
             .iter()
-            .zip(result.iter().take(4))
-            .filter_map(|(a, v)| Some(a).zip(v.clone().ok()))
-            .min_by(|x, y| x.1.cmp(&y.1))
-            .ok_or(anyhow!("Pools not found"))?;
-
-        let (best_output_pool, output_value) = out_pools
+            .map(|route| q.quoteExactOutput(route.encode_reversed(), self.unwrapped_amount_in))
+            .collect();
+        let out_calls: Vec<_> = out_remote
             .iter()
-            .zip(result.iter().skip(4))
-            .filter_map(|(a, v)| Some(a).zip(v.clone().ok()))
-            .min_by(|x, y| x.1.cmp(&y.1))
+            .map(|route| q.quoteExactInput(route.encode(), self.unwrapped_amount_out))
+            .collect();
+        let (in_quotes, out_quotes) = tokio::try_join!(
+            async {
+                if in_calls.is_empty() {
+                    return Ok(Vec::new());
+                }
+                MulticallBuilder::new_dynamic(rpc)
+                    .extend(in_calls)
+                    .try_aggregate(false)
+                    .await
+            },
+            async {
+                if out_calls.is_empty() {
+                    return Ok(Vec::new());
+                }
+                MulticallBuilder::new_dynamic(rpc)
+                    .extend(out_calls)
+                    .try_aggregate(false)
+                    .await
+            },
+        )?;
+        inputs.extend(
+            in_remote
+                .into_iter()
+                .zip(in_quotes)
+                .filter_map(|(route, quote)| {
+                    let quote = quote.ok()?;
+                    Some((route, quote.amountIn, quote.gasEstimate))
+                }),
+        );
+        outputs.extend(
+            out_remote
+                .into_iter()
+                .zip(out_quotes)
+                .filter_map(|(route, quote)| {
+                    let quote = quote.ok()?;
+                    Some((route, quote.amountOut, quote.gasEstimate))
+                }),
+        );
+
+        let input = inputs
+            .into_iter()
+            .min_by_key(|(_, amount, gas)| *amount + *gas * gas_price)
+            .ok_or(anyhow!("Pools not found"))?;
+        let output = outputs
+            .into_iter()
+            .filter_map(|(route, amount, gas)| {
+                let net = amount.checked_sub(gas * gas_price)?;
+                Some((route, amount, gas, net))
+            })
+            .max_by_key(|(.., net)| *net)
             .ok_or(anyhow!("Pools not found"))?;
 
         Ok(UniswapChoise {
             trading_data: self,
-            input: SwapOutcome {
-                estimated: input_value,
-                best_pool: best_input_pool.address,
-                zero_for_one: best_input_pool.base_is_token0,
-                best_fee: best_input_pool.fee,
-            },
-            output: SwapOutcome {
-                estimated: output_value,
-                best_pool: best_output_pool.address,
-                zero_for_one: !best_output_pool.base_is_token0,
-                best_fee: best_output_pool.fee,
-            },
+            input: SwapOutcome::new(input.0, input.1, input.2)?,
+            output: SwapOutcome::new(output.0, output.1, output.2)?,
+        })
+    }
+}
+
+impl SwapOutcome {
+    fn new(route: Route, estimated: U256, gas_estimate: U256) -> Result<Self> {
+        let hop = route
+            .hops()
+            .first()
+            .copied()
+            .ok_or(anyhow!("Empty route"))?;
+        Ok(Self {
+            estimated,
+            best_pool: hop.pool,
+            zero_for_one: hop.zero_for_one,
+            best_fee: hop.fee,
+            gas_estimate,
+            route,
         })
     }
 }
@@ -0,0 +1,150 @@
+use alloy::primitives::{Address, Bytes};
+use serde::Deserialize;
+
+use super::compute_address::{compute_pool_address, FeeAmount};
+
+fn default_bitmap_words() -> i16 {
+    2
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct RoutingConfig {
+    /// Tick bitmap words on each side of the current one that are loaded
+    /// into the simulator
+    #[serde(default = "default_bitmap_words")]
+    pub bitmap_words: i16,
+}
+
+impl Default for RoutingConfig {
+    fn default() -> Self {
+        Self {
+            bitmap_words: default_bitmap_words(),
+        }
+    }
+}
+
+/// Uniswap V3 swap path through pools of `factory`, `fees[i]` is a fee of
+/// the pool between `tokens[i]` and `tokens[i + 1]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Route {
+    pub factory: Address,
+    pub tokens: Vec<Address>,
+    pub fees: Vec<FeeAmount>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hop {
+    pub pool: Address,
+    pub fee: u32,
+    pub zero_for_one: bool,
+}
+
+impl Route {
+    pub fn hops(&self) -> Vec<Hop> {
+        self.tokens
+            .windows(2)
+            .zip(&self.fees)
+            .map(|(pair, fee)| Hop {
+                pool: compute_pool_address(self.factory, pair[0], pair[1], *fee, None),
+                fee: (*fee).into(),
+                zero_for_one: pair[0] < pair[1],
+            })
+            .collect()
+    }
+
+    /// Path of `quoteExactInput`, tokens with 3 byte fees between them
+    pub fn encode(&self) -> Bytes {
+        encode_path(self.tokens.iter(), self.fees.iter())
+    }
+
+    /// Path of `quoteExactOutput` is encoded from the output token
+    pub fn encode_reversed(&self) -> Bytes {
+        encode_path(self.tokens.iter().rev(), self.fees.iter().rev())
+    }
+}
+
+fn encode_path<'a>(
+    mut tokens: impl Iterator<Item = &'a Address>,
+    fees: impl Iterator<Item = &'a FeeAmount>,
+) -> Bytes {
+    let mut path = Vec::new();
+    if let Some(token) = tokens.next() {
+        path.extend(token.as_slice());
+    }
+    for (fee, token) in fees.zip(tokens) {
+        path.extend(&u32::from(*fee).to_be_bytes()[1..]);
+        path.extend(token.as_slice());
+    }
+    path.into()
+}
+
+/// Direct routes from `from` to `to` through a pool of every fee tier, the
+/// trader contract swaps through a single pool on each side of the multipool
+pub fn find_routes(factory: Address, from: Address, to: Address) -> Vec<Route> {
+    if from == to {
+        return Vec::new();
+    }
+    FeeAmount::iter()
+        .map(|fee| Route {
+            factory,
+            tokens: vec![from, to],
+            fees: vec![fee],
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::{address, bytes};
+
+    use super::*;
+
+    const WETH: Address = address!("1000000000000000000000000000000000000001");
+    const USDC: Address = address!("2000000000000000000000000000000000000002");
+    const ASSET: Address = address!("4000000000000000000000000000000000000004");
+    const FACTORY: Address = address!("961235a9020B05C44DF1026D956D1F4D78014276");
+
+    #[test]
+    fn find_routes_test() {
+        let routes = find_routes(FACTORY, WETH, ASSET);
+        assert_eq!(routes.len(), 4);
+        assert!(routes
+            .iter()
+            .all(|route| route.tokens == [WETH, ASSET] && route.fees.len() == 1));
+        assert!(find_routes(FACTORY, WETH, WETH).is_empty());
+    }
+
+    #[test]
+    fn encode_path_test() {
+        let route = Route {
+            factory: FACTORY,
+            tokens: vec![WETH, USDC, ASSET],
+            fees: vec![FeeAmount::LOW, FeeAmount::HIGH],
+        };
+        assert_eq!(
+            route.encode(),
+            bytes!(
+                "1000000000000000000000000000000000000001"
+                "0001f4"
+                "2000000000000000000000000000000000000002"
+                "002710"
+                "4000000000000000000000000000000000000004"
+            )
+        );
+        assert_eq!(
+            route.encode_reversed(),
+            bytes!(
+                "4000000000000000000000000000000000000004"
+                "002710"
+                "2000000000000000000000000000000000000002"
+                "0001f4"
+                "1000000000000000000000000000000000000001"
+            )
+        );
+
+        let hops = route.hops();
+        assert_eq!(hops.len(), 2);
+        assert!(hops[0].zero_for_one);
+        assert_eq!(hops[1].fee, 10000);
+    }
+}
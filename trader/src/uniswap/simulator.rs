@@ -0,0 +1,305 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use alloy::{
+    eips::{BlockId, BlockNumberOrTag},
+    primitives::{aliases::I24, Address, Log, B256, U256},
+    providers::{MulticallBuilder, Provider},
+    rpc::types::Filter,
+    sol_types::SolEventInterface,
+};
+use anyhow::{Context, Result};
+use dashmap::DashMap;
+use tokio::sync::Mutex;
+
+use super::{
+    math::{MAX_TICK, MIN_TICK},
+    pool::{PoolState, Quote, TickInfo},
+    route::Route,
+};
+use crate::contracts::IUniswapV3Pool::{self, IUniswapV3PoolEvents};
+
+/// Logs are requested in ranges of at most this many blocks
+const LOGS_RANGE: u64 = 500;
+/// Rough gas usage of a swap and of each crossed tick, used instead of
+/// quoter gas estimate for local quotes
+const SWAP_GAS: u64 = 100_000;
+const TICK_CROSS_GAS: u64 = 25_000;
+
+pub fn gas_estimate(hops: usize, ticks_crossed: u32) -> U256 {
+    U256::from(SWAP_GAS * hops as u64 + TICK_CROSS_GAS * ticks_crossed as u64)
+}
+
+/// Block that all pools are in the state of
+#[derive(Debug, Clone, Copy, Default)]
+struct SyncedBlock {
+    number: u64,
+    hash: B256,
+}
+
+/// Local copies of Uniswap V3 pools that are kept up to date with pool logs
+pub struct Simulator {
+    /// Pools that don't exist are stored as `None`
+    pools: DashMap<Address, Option<PoolState>>,
+    /// `None` until the first block is fetched
+    block: Mutex<Option<SyncedBlock>>,
+    bitmap_words: i16,
+}
+
+impl Simulator {
+    pub fn new(bitmap_words: i16) -> Self {
+        Self {
+            pools: DashMap::new(),
+            block: Mutex::new(None),
+            bitmap_words,
+        }
+    }
+
+    /// Loads pools that are not known yet in the state of the synced block
+    pub async fn load<P: Provider>(&self, rpc: &P, pools: &[Address]) -> Result<()> {
+        let mut block = self.block.lock().await;
+        let block = match *block {
+            Some(block) => block,
+            None => *block.insert(fetch_block(rpc, BlockNumberOrTag::Latest).await?),
+        };
+        let pools: Vec<Address> = pools
+            .iter()
+            .filter(|address| !self.pools.contains_key(*address))
+            .copied()
+            .collect();
+        self.load_at(rpc, &pools, block).await
+    }
+
+    /// Replaces states of `pools` with their states at `block`
+    async fn load_at<P: Provider>(
+        &self,
+        rpc: &P,
+        pools: &[Address],
+        block: SyncedBlock,
+    ) -> Result<()> {
+        let at = BlockId::hash(block.hash);
+        for address in pools {
+            let state = if rpc.get_code_at(*address).block_id(at).await?.is_empty() {
+                None
+            } else {
+                Some(load_pool(rpc, *address, at, self.bitmap_words).await?)
+            };
+            self.pools.insert(*address, state);
+        }
+        Ok(())
+    }
+
+    /// Applies logs of loaded pools emitted since the last sync. Pools are
+    /// reloaded if the synced block was reorged or some of their logs were removed
+    pub async fn sync<P: Provider>(&self, rpc: &P) -> Result<()> {
+        let mut block = self.block.lock().await;
+        let latest = fetch_block(rpc, BlockNumberOrTag::Latest).await?;
+        let addresses: Vec<Address> = self
+            .pools
+            .iter()
+            .filter(|pool| pool.value().is_some())
+            .map(|pool| *pool.key())
+            .collect();
+        let Some(synced) = *block else {
+            *block = Some(latest);
+            return Ok(());
+        };
+        if addresses.is_empty() {
+            *block = Some(latest);
+            return Ok(());
+        }
+        // logs since a reorged block can't be applied on top of its state
+        let canonical = fetch_block(rpc, synced.number.into()).await?;
+        if canonical.hash != synced.hash {
+            println!("Block {} was reorged, reloading pools", synced.number);
+            self.load_at(rpc, &addresses, latest).await?;
+            *block = Some(latest);
+            return Ok(());
+        }
+
+        let mut reorged = BTreeSet::new();
+        let mut from = synced.number;
+        while from < latest.number {
+            let to = latest.number.min(from + LOGS_RANGE);
+            let filter = Filter::new()
+                .address(addresses.clone())
+                .from_block(from + 1)
+                .to_block(to);
+            for log in rpc.get_logs(&filter).await? {
+                if log.removed {
+                    reorged.insert(log.address());
+                } else if !reorged.contains(&log.address()) {
+                    self.apply_log(&log.inner)?;
+                }
+            }
+            from = to;
+        }
+        if !reorged.is_empty() {
+            let reorged: Vec<Address> = reorged.into_iter().collect();
+            self.load_at(rpc, &reorged, latest).await?;
+        }
+        *block = Some(latest);
+        Ok(())
+    }
+
+    pub fn apply_log(&self, log: &Log) -> Result<()> {
+        let Ok(event) = IUniswapV3PoolEvents::decode_log(log) else {
+            return Ok(());
+        };
+        let Some(mut pool) = self.pools.get_mut(&log.address) else {
+            return Ok(());
+        };
+        let Some(pool) = pool.as_mut() else {
+            return Ok(());
+        };
+        match event.data {
+            IUniswapV3PoolEvents::Swap(e) => {
+                pool.on_swap(U256::from(e.sqrtPriceX96), e.liquidity, e.tick.as_i32())
+            }
+            IUniswapV3PoolEvents::Mint(e) => pool.on_liquidity_change(
+                e.tickLower.as_i32(),
+                e.tickUpper.as_i32(),
+                i128::try_from(e.amount)?,
+            )?,
+            IUniswapV3PoolEvents::Burn(e) => pool.on_liquidity_change(
+                e.tickLower.as_i32(),
+                e.tickUpper.as_i32(),
+                -i128::try_from(e.amount)?,
+            )?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// True if some pool of the route is known to not exist
+    pub fn is_missing(&self, route: &Route) -> bool {
+        route
+            .hops()
+            .iter()
+            .any(|hop| matches!(self.pools.get(&hop.pool).as_deref(), Some(None)))
+    }
+
+    fn with_pool<T>(&self, address: Address, f: impl FnOnce(&PoolState) -> Result<T>) -> Result<T> {
+        let pool = self.pools.get(&address).context("Pool is not loaded")?;
+        f(pool.as_ref().context("Pool doesn't exist")?)
+    }
+
+    pub fn quote_exact_input(&self, route: &Route, amount_in: U256) -> Result<Quote> {
+        let mut quote = Quote {
+            amount: amount_in,
+            ticks_crossed: 0,
+        };
+        for (pair, hop) in route.tokens.windows(2).zip(route.hops()) {
+            let next = self.with_pool(hop.pool, |pool| {
+                pool.quote_exact_input(pair[0], quote.amount)
+            })?;
+            quote.amount = next.amount;
+            quote.ticks_crossed += next.ticks_crossed;
+        }
+        Ok(quote)
+    }
+
+    pub fn quote_exact_output(&self, route: &Route, amount_out: U256) -> Result<Quote> {
+        let mut quote = Quote {
+            amount: amount_out,
+            ticks_crossed: 0,
+        };
+        for (pair, hop) in route.tokens.windows(2).zip(route.hops()).rev() {
+            let next = self.with_pool(hop.pool, |pool| {
+                pool.quote_exact_output(pair[0], quote.amount)
+            })?;
+            quote.amount = next.amount;
+            quote.ticks_crossed += next.ticks_crossed;
+        }
+        Ok(quote)
+    }
+}
+
+async fn fetch_block<P: Provider>(rpc: &P, number: BlockNumberOrTag) -> Result<SyncedBlock> {
+    let block = rpc
+        .get_block_by_number(number)
+        .await?
+        .with_context(|| format!("Block {number} not found"))?;
+    Ok(SyncedBlock {
+        number: block.header.number,
+        hash: block.header.hash,
+    })
+}
+
+/// Reads state of the pool and its initialized ticks around the current one
+pub async fn load_pool<P: Provider>(
+    rpc: &P,
+    address: Address,
+    block: BlockId,
+    bitmap_words: i16,
+) -> Result<PoolState> {
+    let contract = IUniswapV3Pool::new(address, rpc);
+    let (slot0, liquidity, fee, tick_spacing, token0, token1) = rpc
+        .multicall()
+        .add(contract.slot0())
+        .add(contract.liquidity())
+        .add(contract.fee())
+        .add(contract.tickSpacing())
+        .add(contract.token0())
+        .add(contract.token1())
+        .block(block)
+        .aggregate()
+        .await?;
+
+    let mut pool = PoolState {
+        address,
+        token0,
+        token1,
+        fee: fee.to(),
+        tick_spacing: tick_spacing.as_i32(),
+        sqrt_price_x96: U256::from(slot0.sqrtPriceX96),
+        tick: slot0.tick.as_i32(),
+        liquidity,
+        ticks: BTreeMap::new(),
+        words: (0, 0),
+    };
+    let spacing = pool.tick_spacing;
+    let (min_word, ..) = pool.word(MIN_TICK.div_euclid(spacing));
+    let (max_word, ..) = pool.word(MAX_TICK.div_euclid(spacing));
+    let (word, ..) = pool.word(pool.tick.div_euclid(spacing));
+    pool.words = (
+        word.saturating_sub(bitmap_words).max(min_word),
+        word.saturating_add(bitmap_words).min(max_word),
+    );
+
+    let bitmaps = MulticallBuilder::new_dynamic(rpc)
+        .extend((pool.words.0..=pool.words.1).map(|word| contract.tickBitmap(word)))
+        .block(block)
+        .aggregate()
+        .await?;
+    let ticks: Vec<i32> = (pool.words.0..=pool.words.1)
+        .zip(bitmaps)
+        .flat_map(|(word, bitmap)| {
+            (0..256)
+                .filter(move |bit| bitmap.bit(*bit))
+                .map(move |bit| (word as i32 * 256 + bit as i32) * spacing)
+        })
+        .collect();
+    let calls = ticks
+        .iter()
+        .map(|tick| Ok(contract.ticks(I24::try_from(*tick)?)))
+        .collect::<Result<Vec<_>>>()?;
+    let infos = MulticallBuilder::new_dynamic(rpc)
+        .extend(calls)
+        .block(block)
+        .aggregate()
+        .await?;
+    pool.ticks = ticks
+        .into_iter()
+        .zip(infos)
+        .map(|(tick, info)| {
+            (
+                tick,
+                TickInfo {
+                    liquidity_gross: info.liquidityGross,
+                    liquidity_net: info.liquidityNet,
+                },
+            )
+        })
+        .collect();
+    Ok(pool)
+}